@@ -15,10 +15,26 @@ fn bench_subsets(seed: usize) -> Duration {
     now.elapsed()
 }
 
+fn bench_subsets_in(seed: usize) -> Duration {
+    let mut trie = set_trie::SetTrie::new();
+
+    let mut current = trie.entry(0..1).or_insert(0);
+    for i in 1..seed {
+        current = current.entry(i - 1..i).or_insert(i)
+    }
+
+    let now = Instant::now();
+    trie.subsets_in(0..seed).count();
+    now.elapsed()
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("SetTrie::subsets 20000", |b| {
         b.iter_custom(|_| bench_subsets(black_box(200000)))
     });
+    c.bench_function("SetTrie::subsets_in 20000", |b| {
+        b.iter_custom(|_| bench_subsets_in(black_box(200000)))
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
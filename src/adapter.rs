@@ -0,0 +1,241 @@
+use crate::SetTrie;
+
+/// Normalizes keys before they are stored in or used to query an [`AdaptedSetTrie`], so that
+/// keys which differ only in presentation, such as `"Rust"` and `" rust"`, are the same key.
+///
+/// Adapters are chained as tuples, `(A, B)` applying `A` and then `B`, and any `Fn(K) -> K` is an
+/// adapter.
+pub trait KeyAdapter<K> {
+    /// Returns the normalized form of `key`.
+    fn normalize(&self, key: K) -> K;
+}
+
+impl<K, F> KeyAdapter<K> for F
+where
+    F: Fn(K) -> K,
+{
+    fn normalize(&self, key: K) -> K {
+        self(key)
+    }
+}
+
+impl<K, A, B> KeyAdapter<K> for (A, B)
+where
+    A: KeyAdapter<K>,
+    B: KeyAdapter<K>,
+{
+    fn normalize(&self, key: K) -> K {
+        self.1.normalize(self.0.normalize(key))
+    }
+}
+
+/// Removes leading and trailing whitespace.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Trim;
+
+impl KeyAdapter<String> for Trim {
+    fn normalize(&self, key: String) -> String {
+        let trimmed = key.trim();
+        if trimmed.len() == key.len() {
+            key
+        } else {
+            trimmed.to_owned()
+        }
+    }
+}
+
+/// Folds case by converting keys to lowercase, according to the Unicode `Lowercase` property.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CaseFold;
+
+impl KeyAdapter<String> for CaseFold {
+    fn normalize(&self, key: String) -> String {
+        if key.chars().any(|c| c.to_lowercase().ne(std::iter::once(c))) {
+            key.to_lowercase()
+        } else {
+            key
+        }
+    }
+}
+
+/// Converts keys to Unicode Normalization Form C, so that precomposed and decomposed forms of
+/// the same characters are the same key.
+#[cfg(feature = "unicode-normalization")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Nfc;
+
+#[cfg(feature = "unicode-normalization")]
+impl KeyAdapter<String> for Nfc {
+    fn normalize(&self, key: String) -> String {
+        use unicode_normalization::UnicodeNormalization;
+
+        if unicode_normalization::is_nfc(&key) {
+            key
+        } else {
+            key.nfc().collect()
+        }
+    }
+}
+
+/// A [`SetTrie`] of which every key is normalized by a [`KeyAdapter`], both on insertion and in
+/// queries, so that no key reaches the trie without being normalized.
+///
+/// ```rust
+/// use set_trie::{AdaptedSetTrie, CaseFold, Trim};
+///
+/// let mut trie = AdaptedSetTrie::new((Trim, CaseFold));
+/// trie.insert(vec!["Rust ".to_string(), "Trie".to_string()], 1);
+/// trie.insert(vec!["rust".to_string()], 2);
+///
+/// let query = vec!["trie".to_string(), " RUST".to_string()];
+/// assert_eq!(trie.subsets(query), vec![&2, &1]);
+/// assert_eq!(trie.get(vec!["RUST".to_string()]), Some(&[2][..]));
+/// ```
+#[derive(Debug)]
+pub struct AdaptedSetTrie<K, T, A> {
+    trie: SetTrie<K, T>,
+    adapter: A,
+}
+
+impl<K, T, A> AdaptedSetTrie<K, T, A> {
+    /// Create a new, empty trie normalizing its keys with `adapter`.
+    pub const fn new(adapter: A) -> Self {
+        Self {
+            trie: SetTrie::new(),
+            adapter,
+        }
+    }
+
+    /// The adapter normalizing the keys of the trie.
+    #[must_use]
+    pub const fn adapter(&self) -> &A {
+        &self.adapter
+    }
+
+    /// Returns the trie, of which the keys are normalized.
+    #[must_use]
+    pub fn into_inner(self) -> SetTrie<K, T> {
+        self.trie
+    }
+}
+
+impl<K, T, A> AdaptedSetTrie<K, T, A>
+where
+    K: Ord,
+    A: KeyAdapter<K>,
+{
+    /// The sorted, deduplicated and normalized `keys`, as they are stored in the trie.
+    pub fn query(&self, keys: impl IntoIterator<Item = K>) -> Vec<K> {
+        let mut keys: Vec<K> = keys
+            .into_iter()
+            .map(|key| self.adapter.normalize(key))
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+
+    /// Insert the item in the set of normalized `keys`. See [`SetTrie::insert`].
+    pub fn insert(&mut self, keys: impl IntoIterator<Item = K>, item: T)
+    where
+        K: Clone,
+    {
+        let keys = self.query(keys);
+        self.trie.insert(keys, item);
+    }
+
+    /// Inserts multiple items in the set of normalized `keys`. See [`SetTrie::insert_many`].
+    pub fn insert_many(
+        &mut self,
+        keys: impl IntoIterator<Item = K>,
+        items: impl IntoIterator<Item = T>,
+    ) where
+        K: Clone,
+    {
+        let keys = self.query(keys);
+        self.trie.insert_many(keys, items);
+    }
+
+    /// Removes the set of normalized `keys`, returning its values if it was stored. See
+    /// [`SetTrie::remove`].
+    pub fn remove(&mut self, keys: impl IntoIterator<Item = K>) -> Option<Vec<T>>
+    where
+        K: Clone,
+    {
+        let keys = self.query(keys);
+        self.trie.remove(&keys)
+    }
+
+    /// The values of the set of normalized `keys`, if it is stored.
+    #[must_use]
+    pub fn get(&self, keys: impl IntoIterator<Item = K>) -> Option<&[T]> {
+        let keys = self.query(keys);
+        self.trie
+            .root
+            .find(&keys)
+            .map(|node| node.leaves.as_slice())
+            .filter(|values| !values.is_empty())
+    }
+
+    /// The values stored under subsets of the normalized `keys`. See [`SetTrie::subsets`].
+    ///
+    /// The values are collected, as the normalized keys do not outlive the call.
+    #[must_use]
+    pub fn subsets(&self, keys: impl IntoIterator<Item = K>) -> Vec<&T> {
+        let keys = self.query(keys);
+        self.trie.subsets(&keys).collect()
+    }
+
+    /// The values stored under supersets of the normalized `keys`. See [`SetTrie::supersets`].
+    ///
+    /// The values are collected, as the normalized keys do not outlive the call.
+    #[must_use]
+    pub fn supersets(&self, keys: impl IntoIterator<Item = K>) -> Vec<&T> {
+        let keys = self.query(keys);
+        self.trie.supersets(&keys).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AdaptedSetTrie, CaseFold, KeyAdapter, Trim};
+
+    #[test]
+    fn adapted() {
+        let keys = |keys: &[&str]| keys.iter().map(|&key| key.to_string()).collect::<Vec<_>>();
+
+        let mut trie = AdaptedSetTrie::new((Trim, CaseFold));
+        trie.insert(keys(&["b", " A ", "B"]), 'x');
+        trie.insert(keys(&["c"]), 'y');
+        trie.insert_many(keys(&["a\t", "C"]), vec!['z', 'w']);
+
+        assert_eq!(trie.subsets(keys(&["A", "b ", "d"])), vec![&'x']);
+        assert_eq!(trie.supersets(keys(&[" a"])), vec![&'x', &'z', &'w']);
+        assert_eq!(trie.get(keys(&["C ", "A"])), Some(&['z', 'w'][..]));
+        assert_eq!(trie.get(keys(&["a"])), None);
+        assert_eq!(trie.remove(keys(&["A", "B", "b"])), Some(vec!['x']));
+
+        let trie = trie.into_inner();
+        assert_eq!(trie.values().collect::<Vec<_>>(), vec![&'z', &'w', &'y']);
+        assert_eq!(trie.check_invariants(), Ok(()));
+
+        // titlecase letters are neither upper nor lowercase, but still fold.
+        assert_eq!(CaseFold.normalize("\u{1c5}".to_string()), "\u{1c6}");
+        assert_eq!(CaseFold.normalize("\u{1c4}".to_string()), "\u{1c6}");
+
+        let reversed = |key: String| key.chars().rev().collect::<String>();
+        assert_eq!((CaseFold, reversed).normalize("Ab".to_string()), "ba");
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    #[test]
+    fn nfc() {
+        let mut trie = AdaptedSetTrie::new(crate::Nfc);
+        trie.insert(vec!["caf\u{e9}".to_string()], 1);
+        assert_eq!(
+            trie.query(vec!["cafe\u{301}".to_string()]),
+            vec!["caf\u{e9}"]
+        );
+        assert_eq!(trie.remove(vec!["cafe\u{301}".to_string()]), Some(vec![1]));
+    }
+}
@@ -0,0 +1,50 @@
+use crate::SetTrie;
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+
+/// Generates a trie from arbitrary `(keys, value)` pairs. The keys of every pair are sorted and
+/// deduplicated before insertion, so any input produces a valid trie.
+impl<'a, K, T> Arbitrary<'a> for SetTrie<K, T>
+where
+    K: Arbitrary<'a> + Ord + Clone,
+    T: Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut trie = Self::new();
+        for pair in u.arbitrary_iter::<(Vec<K>, T)>()? {
+            insert_sorted(&mut trie, pair?);
+        }
+        Ok(trie)
+    }
+
+    fn arbitrary_take_rest(u: Unstructured<'a>) -> Result<Self> {
+        let mut trie = Self::new();
+        for pair in u.arbitrary_take_rest_iter::<(Vec<K>, T)>()? {
+            insert_sorted(&mut trie, pair?);
+        }
+        Ok(trie)
+    }
+}
+
+fn insert_sorted<K: Ord + Clone, T>(trie: &mut SetTrie<K, T>, (mut keys, item): (Vec<K>, T)) {
+    keys.sort();
+    keys.dedup();
+    trie.insert(keys, item);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SetTrie;
+    use arbitrary::{Arbitrary, Unstructured};
+
+    #[test]
+    fn arbitrary() {
+        // odd bytes keep the iterator going until the data runs out.
+        let data: Vec<u8> = (1..=255).step_by(2).cycle().take(4096).collect();
+        let trie = SetTrie::<u8, u8>::arbitrary_take_rest(Unstructured::new(&data)).unwrap();
+        assert!(trie.values().count() > 0);
+
+        for (keys, _) in trie.iter_bfs() {
+            assert!(keys.windows(2).all(|w| w[0] < w[1]));
+        }
+    }
+}
@@ -0,0 +1,96 @@
+/// Index of a value in an [`Arena`], together with the generation of its slot when the value was
+/// inserted.
+#[allow(clippy::redundant_pub_crate)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) struct Index {
+    slot: usize,
+    generation: u64,
+}
+
+/// A slot of an [`Arena`], of which the generation is bumped whenever its value is removed.
+#[derive(Debug, Clone)]
+struct Slot<T> {
+    generation: u64,
+    item: Option<T>,
+}
+
+impl<T> Slot<T> {
+    /// The value of the slot, if `index` was handed out for it.
+    fn get(&self, index: Index) -> Option<&T> {
+        self.item
+            .as_ref()
+            .filter(|_| self.generation == index.generation)
+    }
+}
+
+/// Values kept outside of a trie, which stores their indices instead. The slots of removed values
+/// are reused by later insertions, and every slot counts how often its value was removed, so that
+/// the index of a removed value never resolves to a value inserted after it.
+#[allow(clippy::redundant_pub_crate)]
+#[derive(Debug, Clone)]
+pub(crate) struct Arena<T> {
+    slots: Vec<Slot<T>>,
+    // slots which hold no value, reused last in, first out.
+    free: Vec<usize>,
+}
+
+impl<T> Arena<T> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            slots: vec![],
+            free: vec![],
+        }
+    }
+
+    /// The number of values in the arena.
+    pub(crate) const fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    /// Stores the item in a free slot, returning its index.
+    pub(crate) fn insert(&mut self, item: T) -> Index {
+        if let Some(slot) = self.free.pop() {
+            self.slots[slot].item = Some(item);
+            Index {
+                slot,
+                generation: self.slots[slot].generation,
+            }
+        } else {
+            self.slots.push(Slot {
+                generation: 0,
+                item: Some(item),
+            });
+            Index {
+                slot: self.slots.len() - 1,
+                generation: 0,
+            }
+        }
+    }
+
+    /// The value at `index`, or `None` if it has been removed.
+    pub(crate) fn get(&self, index: Index) -> Option<&T> {
+        self.slots.get(index.slot)?.get(index)
+    }
+
+    /// Mutably returns the value at `index`, or `None` if it has been removed.
+    pub(crate) fn get_mut(&mut self, index: Index) -> Option<&mut T> {
+        let slot = self.slots.get_mut(index.slot)?;
+        if slot.generation != index.generation {
+            return None;
+        }
+        slot.item.as_mut()
+    }
+
+    /// Removes the value at `index`, returning it if it was not removed before. Its slot is
+    /// reused by later insertions.
+    pub(crate) fn remove(&mut self, index: Index) -> Option<T> {
+        let slot = self.slots.get_mut(index.slot)?;
+        if slot.generation != index.generation {
+            return None;
+        }
+        let item = slot.item.take()?;
+        slot.generation += 1;
+        self.free.push(index.slot);
+        Some(item)
+    }
+}
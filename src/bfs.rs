@@ -0,0 +1,113 @@
+use crate::Node;
+use std::collections::VecDeque;
+
+/// Iterator for [`SetTrie::values_bfs`](crate::SetTrie::values_bfs).
+#[derive(Debug, Clone)]
+pub struct ValuesBfs<'a, K, T> {
+    leaves: std::slice::Iter<'a, T>,
+    queue: VecDeque<&'a Node<K, T>>,
+}
+
+impl<'a, K, T> ValuesBfs<'a, K, T> {
+    pub(crate) fn new(root: &'a Node<K, T>) -> Self {
+        ValuesBfs {
+            leaves: [].iter(),
+            queue: std::iter::once(root).collect(),
+        }
+    }
+}
+
+impl<'a, K, T> Iterator for ValuesBfs<'a, K, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some(item);
+            }
+            let node = self.queue.pop_front()?;
+            self.queue.extend(&node.children);
+            self.leaves = node.leaves.iter();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.leaves.len(), None)
+    }
+}
+
+/// Iterator for [`SetTrie::iter_bfs`](crate::SetTrie::iter_bfs).
+#[derive(Debug, Clone)]
+pub struct IterBfs<'a, K, T> {
+    path: Vec<&'a K>,
+    leaves: std::slice::Iter<'a, T>,
+    // every queued node together with its key path.
+    queue: VecDeque<(Vec<&'a K>, &'a Node<K, T>)>,
+}
+
+impl<'a, K, T> IterBfs<'a, K, T> {
+    pub(crate) fn new(root: &'a Node<K, T>) -> Self {
+        IterBfs {
+            path: vec![],
+            leaves: [].iter(),
+            queue: std::iter::once((vec![], root)).collect(),
+        }
+    }
+}
+
+impl<'a, K, T> Iterator for IterBfs<'a, K, T> {
+    type Item = (Vec<&'a K>, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some((self.path.clone(), item));
+            }
+            let (path, node) = self.queue.pop_front()?;
+            self.queue.extend(node.children().map(|(k, n)| {
+                let mut child = Vec::with_capacity(path.len() + 1);
+                child.extend_from_slice(&path);
+                child.push(k);
+                (child, n)
+            }));
+            self.path = path;
+            self.leaves = node.leaves.iter();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.leaves.len(), None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SetTrie;
+
+    #[test]
+    fn bfs() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[1, 2, 3], 'a');
+        trie.insert(&[1], 'b');
+        trie.insert(&[2, 3], 'c');
+        trie.insert(&[], 'd');
+        trie.insert(&[1, 3], 'e');
+        trie.insert(&[3], 'f');
+
+        assert_eq!(
+            trie.values_bfs().collect::<Vec<_>>(),
+            vec![&'d', &'b', &'f', &'e', &'c', &'a']
+        );
+        assert_eq!(
+            trie.iter_bfs().collect::<Vec<_>>(),
+            vec![
+                (vec![], &'d'),
+                (vec![&&1], &'b'),
+                (vec![&&3], &'f'),
+                (vec![&&1, &&3], &'e'),
+                (vec![&&2, &&3], &'c'),
+                (vec![&&1, &&2, &&3], &'a'),
+            ]
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{Entry, SetTrie};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Deref;
+
+/// A [`SetTrie`] which also maintains the reverse index from every value to the sets it is stored
+/// under, answering "which sets hold this value" without scanning the trie.
+///
+/// Queries are available through `Deref`, mutations only through the wrapper so that the index
+/// stays in sync.
+///
+/// ```rust
+/// use set_trie::BiSetTrie;
+///
+/// let mut trie = BiSetTrie::new();
+/// trie.insert(vec!["rust", "trie"], "doc-1");
+/// trie.insert(vec!["rust"], "doc-1");
+/// trie.insert(vec!["rust"], "doc-2");
+///
+/// assert_eq!(trie.sets_of(&"doc-1"), &[vec!["rust", "trie"], vec!["rust"]]);
+/// assert_eq!(trie.remove_value(&"doc-1"), 2);
+/// assert_eq!(trie.subsets(&["rust", "trie"]).collect::<Vec<_>>(), vec![&"doc-2"]);
+/// ```
+#[derive(Debug)]
+pub struct BiSetTrie<K, T> {
+    trie: SetTrie<K, T>,
+    sets: HashMap<T, Vec<Vec<K>>>,
+}
+
+impl<K, T> Default for BiSetTrie<K, T> {
+    fn default() -> Self {
+        Self {
+            trie: SetTrie::new(),
+            sets: HashMap::new(),
+        }
+    }
+}
+
+impl<K, T> BiSetTrie<K, T> {
+    /// Create a new, empty `BiSetTrie`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K, T> BiSetTrie<K, T>
+where
+    K: Ord + Clone,
+    T: Eq + Hash + Clone,
+{
+    /// Insert the item in the given node and records the set in the reverse index. Will create the
+    /// node if needed.
+    pub fn insert(&mut self, keys: impl IntoIterator<Item = K>, item: T) {
+        let keys: Vec<K> = keys.into_iter().collect();
+        let sets = self.sets.entry(item.clone()).or_default();
+        if !sets.contains(&keys) {
+            sets.push(keys.clone());
+        }
+        self.trie.insert(keys, item);
+    }
+
+    /// The sets under which `item` is stored, in order of insertion.
+    #[must_use]
+    pub fn sets_of(&self, item: &T) -> &[Vec<K>] {
+        self.sets.get(item).map_or(&[], Vec::as_slice)
+    }
+
+    /// Removes every occurrence of `item` from the trie, visiting only the sets it is stored
+    /// under. Returns the number of removed values.
+    pub fn remove_value(&mut self, item: &T) -> usize {
+        self.remove_by_value(item)
+            .into_iter()
+            .map(|(_, removed)| removed)
+            .sum()
+    }
+
+    /// Removes every occurrence of `item` from the trie, visiting only the sets it is stored
+    /// under. Returns those sets together with the number of removed occurrences, in order of
+    /// insertion. See [`SetTrie::remove_by_value`].
+    pub fn remove_by_value(&mut self, item: &T) -> Vec<(Vec<K>, usize)> {
+        let mut removed = vec![];
+        for keys in self.sets.remove(item).unwrap_or_default() {
+            if let Some(entry) = self.trie.entry(keys.iter().cloned()).find() {
+                let mut entry = Entry::Existing(entry);
+                let len = entry.items().len();
+                entry.update_leaves(|leaves| leaves.retain(|v| v != item));
+                let retained = entry.items().len();
+                if retained < len {
+                    removed.push((keys, len - retained));
+                }
+            }
+        }
+        removed
+    }
+
+    /// Removes the set `keys`, returning its values if it was stored. See [`SetTrie::remove`].
+    pub fn remove(&mut self, keys: &[K]) -> Option<Vec<T>> {
+        let values = self.trie.remove(keys)?;
+        for value in &values {
+            if let Some(sets) = self.sets.get_mut(value) {
+                sets.retain(|set| set.as_slice() != keys);
+                if sets.is_empty() {
+                    self.sets.remove(value);
+                }
+            }
+        }
+        Some(values)
+    }
+}
+
+impl<K, T> Deref for BiSetTrie<K, T> {
+    type Target = SetTrie<K, T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.trie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::BiSetTrie;
+
+    #[test]
+    fn bi_set_trie() {
+        let mut trie = BiSetTrie::new();
+        trie.insert(vec![1, 2], 'a');
+        trie.insert(vec![1, 2], 'a');
+        trie.insert(vec![1, 2], 'b');
+        trie.insert(vec![3], 'a');
+
+        assert_eq!(trie.sets_of(&'a'), &[vec![1, 2], vec![3]]);
+        assert_eq!(trie.sets_of(&'c'), &[] as &[Vec<i32>]);
+
+        assert_eq!(trie.remove(&[3]), Some(vec!['a']));
+        assert_eq!(trie.sets_of(&'a'), &[vec![1, 2]]);
+
+        assert_eq!(trie.remove_value(&'a'), 2);
+        assert!(trie.sets_of(&'a').is_empty());
+        assert_eq!(trie.values().collect::<Vec<_>>(), vec![&'b']);
+
+        assert_eq!(trie.remove(&[1, 2]), Some(vec!['b']));
+        assert!(trie.sets_of(&'b').is_empty());
+
+        trie.insert(vec![4], 'c');
+        trie.insert(vec![1, 2], 'c');
+        trie.insert(vec![1, 2], 'c');
+        assert_eq!(
+            trie.remove_by_value(&'c'),
+            vec![(vec![4], 1), (vec![1, 2], 2)]
+        );
+        assert_eq!(trie.remove_value(&'c'), 0);
+    }
+}
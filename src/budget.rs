@@ -0,0 +1,56 @@
+use std::time::Instant;
+
+/// Number of node visits between two checks of the deadline, as reading the clock is relatively
+/// expensive compared to visiting a node.
+const DEADLINE_INTERVAL: usize = 64;
+
+/// Bounds the number of nodes a query may visit below the root, and the time it may take.
+#[allow(clippy::redundant_pub_crate)]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Budget {
+    nodes: usize,
+    deadline: Option<Instant>,
+    spent: usize,
+    exceeded: bool,
+}
+
+impl Budget {
+    pub(crate) const fn unlimited() -> Self {
+        Self {
+            nodes: usize::MAX,
+            deadline: None,
+            spent: 0,
+            exceeded: false,
+        }
+    }
+
+    pub(crate) fn limit_nodes(&mut self, nodes: usize) {
+        self.nodes = self.nodes.min(nodes);
+    }
+
+    pub(crate) fn limit_time(&mut self, deadline: Instant) {
+        self.deadline = Some(self.deadline.map_or(deadline, |d| d.min(deadline)));
+    }
+
+    /// Accounts for visiting a node, returning whether the budget allows it. Once the budget is
+    /// exceeded, no further nodes are allowed.
+    pub(crate) fn visit(&mut self) -> bool {
+        let expired = || {
+            self.spent.is_multiple_of(DEADLINE_INTERVAL)
+                && self
+                    .deadline
+                    .is_some_and(|deadline| Instant::now() >= deadline)
+        };
+        if self.exceeded || self.spent >= self.nodes || expired() {
+            self.exceeded = true;
+            return false;
+        }
+        self.spent += 1;
+        true
+    }
+
+    /// Whether the query was cut short by the budget.
+    pub(crate) const fn exceeded(&self) -> bool {
+        self.exceeded
+    }
+}
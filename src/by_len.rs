@@ -0,0 +1,308 @@
+use crate::stats::PendingAt;
+use crate::Node;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+/// The order in which [`ByLen`] yields sets of different sizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LenOrder {
+    /// Smallest sets first, the most general matches.
+    Ascending,
+    /// Largest sets first, the most specific matches.
+    Descending,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Query<'b, K> {
+    Subsets(&'b [K]),
+    SuperSets(&'b [K]),
+}
+
+#[derive(Debug, Clone)]
+enum Item<'a, K, T> {
+    // (depth, node, start of the unused query keys for subsets or number of matched keys for
+    // supersets, changes yet to be applied to the counts of the node)
+    Visit(usize, &'a Node<K, T>, usize, PendingAt<'a>),
+    Yield(&'a [T]),
+}
+
+/// Iterator for the [subsets by len](crate::SetTrie::subsets_by_len) and
+/// [supersets by len](crate::SetTrie::supersets_by_len) methods.
+///
+/// Best first search: every pending subtree is ranked by a bound on the sizes of the sets below
+/// it, so a set is yielded as soon as no pending subtree can hold a set which comes before it.
+/// Sets of equal size are yielded in no particular order. The sizes are bounded from above by the
+/// height every node keeps, and by the query keys left for subsets, and from below by the query
+/// keys left for supersets.
+#[derive(Debug, Clone)]
+pub struct ByLen<'a, 'b, K, T> {
+    leaves: std::slice::Iter<'a, T>,
+    heap: BinaryHeap<Entry<'a, K, T>>,
+    query: Query<'b, K>,
+    order: LenOrder,
+    seq: usize,
+}
+
+/// An entry of the heap. Yields precede visits of the same rank, and entries of equal rank are
+/// taken in order of discovery.
+#[derive(Debug, Clone)]
+struct Entry<'a, K, T> {
+    rank: usize,
+    yields: bool,
+    seq: Reverse<usize>,
+    item: Item<'a, K, T>,
+}
+
+impl<K, T> Entry<'_, K, T> {
+    const fn key(&self) -> (usize, bool, Reverse<usize>) {
+        (self.rank, self.yields, self.seq)
+    }
+}
+
+impl<K, T> PartialEq for Entry<'_, K, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl<K, T> Eq for Entry<'_, K, T> {}
+
+impl<K, T> PartialOrd for Entry<'_, K, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K, T> Ord for Entry<'_, K, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl<'a, 'b, K, T> ByLen<'a, 'b, K, T>
+where
+    K: Ord,
+{
+    fn new(
+        root: &'a Node<K, T>,
+        pending: PendingAt<'a>,
+        query: Query<'b, K>,
+        order: LenOrder,
+    ) -> Self {
+        let mut by_len = ByLen {
+            leaves: [].iter(),
+            heap: BinaryHeap::new(),
+            query,
+            order,
+            seq: 0,
+        };
+        by_len.visit_later(0, root, 0, pending);
+        by_len
+    }
+
+    pub(crate) fn subsets(
+        root: &'a Node<K, T>,
+        pending: PendingAt<'a>,
+        keys: &'b [K],
+        order: LenOrder,
+    ) -> Self {
+        Self::new(root, pending, Query::Subsets(keys), order)
+    }
+
+    pub(crate) fn supersets(
+        root: &'a Node<K, T>,
+        pending: PendingAt<'a>,
+        keys: &'b [K],
+        order: LenOrder,
+    ) -> Self {
+        Self::new(root, pending, Query::SuperSets(keys), order)
+    }
+
+    /// Ranks a set size, or a bound on them, such that the heap pops the first size of the order.
+    const fn rank(&self, len: usize) -> usize {
+        match self.order {
+            LenOrder::Ascending => usize::MAX - len,
+            LenOrder::Descending => len,
+        }
+    }
+
+    fn push(&mut self, rank: usize, item: Item<'a, K, T>) {
+        self.heap.push(Entry {
+            rank,
+            yields: matches!(item, Item::Yield(_)),
+            seq: Reverse(self.seq),
+            item,
+        });
+        self.seq += 1;
+    }
+
+    fn visit_later(
+        &mut self,
+        depth: usize,
+        node: &'a Node<K, T>,
+        progress: usize,
+        pending: PendingAt<'a>,
+    ) {
+        let height = pending.apply(node.counts).height;
+        // the smallest and largest size of the sets below the node.
+        let (min, max) = match self.query {
+            Query::Subsets(keys) => (depth, depth + height.min(keys.len() - progress)),
+            Query::SuperSets(keys) => (depth + keys.len() - progress, depth + height),
+        };
+        let bound = match self.order {
+            LenOrder::Ascending => min,
+            LenOrder::Descending => max,
+        };
+        self.push(
+            self.rank(bound),
+            Item::Visit(depth, node, progress, pending),
+        );
+    }
+
+    fn visit(
+        &mut self,
+        depth: usize,
+        node: &'a Node<K, T>,
+        progress: usize,
+        pending: PendingAt<'a>,
+    ) {
+        match self.query {
+            Query::Subsets(keys) => {
+                if !node.leaves.is_empty() {
+                    self.push(self.rank(depth), Item::Yield(&node.leaves));
+                }
+
+                let rest = &keys[progress..];
+                let (Some(from), Some(to)) = (rest.first(), rest.last()) else {
+                    return;
+                };
+                let first = node.search(from).unwrap_or_else(|idx| idx);
+                let mut start = progress;
+                for (idx, (key, child)) in node.between_inclusive(from, to).enumerate() {
+                    match keys[start..].binary_search(key) {
+                        Ok(found) => {
+                            start += found + 1;
+                            self.visit_later(depth + 1, child, start, pending.child(first + idx));
+                        }
+                        Err(found) => start += found,
+                    }
+                }
+            }
+            Query::SuperSets(keys) => {
+                let Some(want) = keys.get(progress) else {
+                    if !node.leaves.is_empty() {
+                        self.push(self.rank(depth), Item::Yield(&node.leaves));
+                    }
+                    for (idx, child) in node.children.iter().enumerate() {
+                        self.visit_later(depth + 1, child, progress, pending.child(idx));
+                    }
+                    return;
+                };
+
+                // as children are sorted, only those up to the next key of the query may still
+                // lead to a superset.
+                for (idx, (key, child)) in node.children().enumerate() {
+                    if key > want {
+                        break;
+                    }
+                    let matched = progress + usize::from(key == want);
+                    self.visit_later(depth + 1, child, matched, pending.child(idx));
+                }
+            }
+        }
+    }
+}
+
+impl<'a, K, T> Iterator for ByLen<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some(item);
+            }
+
+            match self.heap.pop()?.item {
+                Item::Yield(leaves) => self.leaves = leaves.iter(),
+                Item::Visit(depth, node, progress, pending) => {
+                    self.visit(depth, node, progress, pending);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Item;
+    use crate::{LenOrder, SetTrie};
+
+    #[test]
+    fn by_len() {
+        let mut trie = SetTrie::new();
+        trie.insert(vec![1, 2, 3], 'a');
+        trie.insert(vec![1], 'b');
+        trie.insert(vec![1, 3], 'c');
+        trie.insert(vec![2], 'd');
+        trie.insert(vec![], 'e');
+        trie.insert(vec![2, 3, 4], 'f');
+
+        assert_eq!(
+            trie.subsets_by_len(&[1, 2, 3], LenOrder::Ascending)
+                .collect::<Vec<_>>(),
+            vec![&'e', &'b', &'d', &'c', &'a']
+        );
+        assert_eq!(
+            trie.subsets_by_len(&[1, 2, 3], LenOrder::Descending)
+                .collect::<Vec<_>>(),
+            vec![&'a', &'c', &'b', &'d', &'e']
+        );
+        assert_eq!(
+            trie.supersets_by_len(&[3], LenOrder::Ascending)
+                .collect::<Vec<_>>(),
+            vec![&'c', &'f', &'a']
+        );
+        assert_eq!(
+            trie.supersets_by_len(&[3], LenOrder::Descending)
+                .collect::<Vec<_>>(),
+            vec![&'a', &'f', &'c']
+        );
+        assert_eq!(trie.supersets_by_len(&[], LenOrder::Ascending).count(), 6);
+        assert_eq!(trie.subsets_by_len(&[], LenOrder::Descending).count(), 1);
+    }
+
+    #[test]
+    fn descending_supersets_yield_early() {
+        // every set holds its length.
+        let mut trie = SetTrie::new();
+        let mut entry = trie.entry(vec![1]).or_insert(1);
+        for key in 2..5 {
+            entry = entry.entry(vec![key]).or_insert(key);
+        }
+        for key in 5..10 {
+            trie.insert(vec![1, key], 2);
+        }
+
+        let mut longest = trie.supersets_by_len(&[1], LenOrder::Descending);
+        assert_eq!(longest.next(), Some(&4));
+        // the shorter supersets are yet to be visited.
+        assert!(longest
+            .heap
+            .iter()
+            .any(|entry| matches!(entry.item, Item::Visit(..))));
+        assert_eq!(longest.count(), 8);
+
+        // removing the longest set leaves a bound which is too high, but the order holds.
+        trie.remove(&[1, 2, 3, 4]);
+        trie.insert(vec![1, 2, 3], 3);
+        assert_eq!(
+            trie.supersets_by_len(&[1], LenOrder::Descending)
+                .collect::<Vec<_>>(),
+            vec![&3, &3, &2, &2, &2, &2, &2, &2, &1]
+        );
+        assert_eq!(trie.check_invariants(), Ok(()));
+    }
+}
@@ -0,0 +1,214 @@
+use crate::{Node, SetTrie};
+
+/// Iterator for [subsets of complement](SetTrie::subsets_of_complement) method.
+#[derive(Debug, Clone)]
+pub struct SubsetsOfComplement<'a, 'b, K, T> {
+    leaves: std::slice::Iter<'a, T>,
+    next: Vec<&'a Node<K, T>>,
+    excluded: &'b [K],
+}
+
+impl<'a, 'b, K, T> SubsetsOfComplement<'a, 'b, K, T> {
+    pub(crate) fn new(root: &'a Node<K, T>, excluded: &'b [K]) -> Self {
+        Self {
+            leaves: [].iter(),
+            next: vec![root],
+            excluded,
+        }
+    }
+}
+
+impl<'a, K, T> Iterator for SubsetsOfComplement<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some(item);
+            }
+            let node = self.next.pop()?;
+            let excluded = self.excluded;
+            self.next.extend(
+                node.children()
+                    .rev()
+                    .filter(|(key, _)| excluded.binary_search(key).is_err())
+                    .map(|(_, child)| child),
+            );
+            self.leaves = node.leaves.iter();
+        }
+    }
+}
+
+/// Iterator for [supersets of complement](SetTrie::supersets_of_complement) method.
+#[derive(Debug, Clone)]
+pub struct SuperSetsOfComplement<'a, 'b, K, T> {
+    leaves: std::slice::Iter<'a, T>,
+    // (node, index of the next key of the universe which is not excluded)
+    next: Vec<(&'a Node<K, T>, usize)>,
+    universe: &'b [K],
+    excluded: &'b [K],
+}
+
+impl<'a, 'b, K, T> SuperSetsOfComplement<'a, 'b, K, T>
+where
+    K: Ord,
+{
+    pub(crate) fn new(root: &'a Node<K, T>, universe: &'b [K], excluded: &'b [K]) -> Self {
+        let mut supersets = Self {
+            leaves: [].iter(),
+            next: vec![],
+            universe,
+            excluded,
+        };
+        let start = supersets.skip_excluded(0);
+        supersets.next.push((root, start));
+        supersets
+    }
+
+    /// The index of the first key of the universe from `idx` onwards which is not excluded.
+    fn skip_excluded(&self, mut idx: usize) -> usize {
+        while let Some(key) = self.universe.get(idx) {
+            if self.excluded.binary_search(key).is_err() {
+                break;
+            }
+            idx += 1;
+        }
+        idx
+    }
+}
+
+impl<'a, K, T> Iterator for SuperSetsOfComplement<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some(item);
+            }
+            let (node, idx) = self.next.pop()?;
+            match self.universe.get(idx) {
+                // every key of the complement has been encountered, so any descendant is a
+                // superset.
+                None => {
+                    self.next
+                        .extend(node.children().rev().map(|(_, child)| (child, idx)));
+                    self.leaves = node.leaves.iter();
+                }
+                // as children are sorted, only the children up to the next key of the complement
+                // may still lead to a superset.
+                Some(want) => {
+                    let len = match node.search(want) {
+                        Ok(found) => {
+                            let next = self.skip_excluded(idx + 1);
+                            self.next.push((node.child_at(found).1, next));
+                            found
+                        }
+                        Err(len) => len,
+                    };
+                    self.next.extend(
+                        node.children()
+                            .take(len)
+                            .rev()
+                            .map(|(_, child)| (child, idx)),
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl<K, T> SetTrie<K, T>
+where
+    K: Ord,
+{
+    /// Iterates over all values stored under subsets of the complement of `excluded`: the sets
+    /// which share no key with `excluded`, such as the sets using only keys outside a blocklist.
+    /// The complement is never collected, as children are checked against `excluded` while the
+    /// trie is traversed. Values are visited in the same order as [`SetTrie::subsets`].
+    ///
+    /// `excluded` must be sorted.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&["eval", "fs"], "plugin-1");
+    /// trie.insert(&["fs", "net"], "plugin-2");
+    /// trie.insert(&["net"], "plugin-3");
+    ///
+    /// let allowed = trie.subsets_of_complement(&[&"eval"]).collect::<Vec<_>>();
+    /// assert_eq!(allowed, vec![&"plugin-2", &"plugin-3"]);
+    /// ```
+    #[must_use]
+    pub fn subsets_of_complement<'a, 'b>(
+        &'a self,
+        excluded: &'b [K],
+    ) -> SubsetsOfComplement<'a, 'b, K, T> {
+        SubsetsOfComplement::new(&self.root, excluded)
+    }
+
+    /// Iterates over all values stored under supersets of the complement of `excluded` within
+    /// `universe`: the sets which contain every key of `universe` which is not in `excluded`. The
+    /// complement is never collected, as the next key of it is found while the trie is traversed.
+    /// Values are visited in the same order as [`SetTrie::supersets`].
+    ///
+    /// Both `universe` and `excluded` must be sorted.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 2, 3], "foo");
+    /// trie.insert(&[1, 3], "bar");
+    /// trie.insert(&[1, 2], "baz");
+    ///
+    /// let universe = [&1, &2, &3];
+    /// let matches = trie.supersets_of_complement(&universe, &[&2]).collect::<Vec<_>>();
+    /// assert_eq!(matches, vec![&"foo", &"bar"]);
+    /// ```
+    #[must_use]
+    pub fn supersets_of_complement<'a, 'b>(
+        &'a self,
+        universe: &'b [K],
+        excluded: &'b [K],
+    ) -> SuperSetsOfComplement<'a, 'b, K, T> {
+        SuperSetsOfComplement::new(&self.root, universe, excluded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SetTrie;
+
+    #[test]
+    fn complement() {
+        let mut v = SetTrie::new();
+        v.insert(vec![], 'a');
+        v.insert(vec![1, 2], 'b');
+        v.insert(vec![1, 3], 'c');
+        v.insert(vec![1, 2, 3, 4], 'd');
+        v.insert(vec![2, 3], 'e');
+        v.insert(vec![3], 'f');
+
+        let universe = [1, 2, 3, 4];
+        let excluded = [2, 4];
+        let complement = [1, 3];
+        assert_eq!(
+            v.subsets_of_complement(&excluded).collect::<Vec<_>>(),
+            v.subsets(&complement).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            v.supersets_of_complement(&universe, &excluded)
+                .collect::<Vec<_>>(),
+            v.supersets(&complement).collect::<Vec<_>>()
+        );
+        assert_eq!(v.supersets_of_complement(&universe, &universe).count(), 6);
+        assert_eq!(
+            v.supersets_of_complement(&universe, &[])
+                .collect::<Vec<_>>(),
+            vec![&'d']
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use crate::PersistentSetTrie;
+use arc_swap::ArcSwap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// A [`PersistentSetTrie`] shared between many readers and a single writer at a time, which
+/// publishes every update atomically, read-copy-update style.
+///
+/// Readers take a [snapshot](Self::snapshot) and traverse it without any synchronization, so
+/// queries never wait for a writer, nor a writer for queries. Loading the current root is an
+/// atomic pointer load, which takes no lock and does not wait for other threads. A writer
+/// [updates](Self::update) a private version of the trie, in which only the nodes on the modified
+/// key paths are copied, and then atomically swaps it in as the current root.
+///
+/// ```rust
+/// use set_trie::ConcurrentSetTrie;
+/// use std::sync::Arc;
+///
+/// let trie = Arc::new(ConcurrentSetTrie::new());
+/// trie.update(|trie| trie.insert_mut(vec![1, 2], "foo"));
+///
+/// let before = trie.snapshot();
+/// let writer = {
+///     let trie = Arc::clone(&trie);
+///     std::thread::spawn(move || trie.update(|trie| trie.insert_mut(vec![1], "bar")))
+/// };
+/// writer.join().unwrap();
+///
+/// assert_eq!(before.subsets(&[1, 2]).count(), 1);
+/// assert_eq!(trie.snapshot().subsets(&[1, 2]).count(), 2);
+/// ```
+#[derive(Debug)]
+pub struct ConcurrentSetTrie<K, T> {
+    current: ArcSwap<PersistentSetTrie<K, T>>,
+    // serializes writers, so that no update is lost.
+    writer: Mutex<()>,
+}
+
+impl<K, T> Default for ConcurrentSetTrie<K, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, T> ConcurrentSetTrie<K, T> {
+    /// Create a new, empty `ConcurrentSetTrie`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::from(PersistentSetTrie::new())
+    }
+
+    /// The current version of the trie, which is unaffected by later updates.
+    #[must_use]
+    pub fn snapshot(&self) -> PersistentSetTrie<K, T> {
+        self.current.load().snapshot()
+    }
+
+    /// Applies `f` to a private version of the trie, and publishes the result once `f` returns.
+    /// Readers see either the version before or after the update, never a part of it. Updates
+    /// from other threads wait for this one to be published.
+    pub fn update<R>(&self, f: impl FnOnce(&mut PersistentSetTrie<K, T>) -> R) -> R {
+        // a writer which panicked has not published anything, so the lock is still consistent.
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut next = self.snapshot();
+        let result = f(&mut next);
+        self.current.store(Arc::new(next));
+        result
+    }
+
+    /// Returns the current version of the trie.
+    pub fn into_inner(self) -> PersistentSetTrie<K, T> {
+        let current = self.current.into_inner();
+        Arc::try_unwrap(current).unwrap_or_else(|current| current.snapshot())
+    }
+}
+
+impl<K, T> From<PersistentSetTrie<K, T>> for ConcurrentSetTrie<K, T> {
+    fn from(trie: PersistentSetTrie<K, T>) -> Self {
+        Self {
+            current: ArcSwap::from_pointee(trie),
+            writer: Mutex::new(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ConcurrentSetTrie;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+
+    #[test]
+    fn concurrent() {
+        let trie = ConcurrentSetTrie::new();
+        let done = AtomicBool::new(false);
+
+        thread::scope(|scope| {
+            let reader = scope.spawn(|| {
+                let mut seen = 0;
+                while !done.load(Ordering::Acquire) {
+                    // every version holds the pair inserted by a single update, or nothing.
+                    let snapshot = trie.snapshot();
+                    let count = snapshot.values().count();
+                    assert_eq!(count % 2, 0);
+                    assert!(count >= seen);
+                    seen = count;
+                }
+            });
+
+            for i in 0..100 {
+                trie.update(|trie| {
+                    trie.insert_mut(vec![i], i);
+                    trie.insert_mut(vec![i, i + 1], i);
+                });
+            }
+            done.store(true, Ordering::Release);
+            reader.join().unwrap();
+        });
+
+        assert_eq!(trie.update(|trie| trie.remove_mut(&[3])), Some(vec![3]));
+        assert_eq!(trie.into_inner().values().count(), 199);
+    }
+}
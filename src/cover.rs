@@ -0,0 +1,239 @@
+use crate::Node;
+
+/// A stored set and its values, as part of a cover.
+type Part<'a, K, T> = (Vec<&'a K>, &'a [T]);
+
+/// A candidate set of a search frame: the query positions it covers, and the set itself.
+type Candidate<'a, K, T> = (Vec<usize>, Part<'a, K, T>);
+
+/// Iterator for [exact covers](crate::SetTrie::exact_covers) method.
+///
+/// Backtracking in the style of Algorithm X: the smallest uncovered key of the query has to be
+/// covered by some set, so every frame branches over the stored sets containing that key. As the
+/// sets are sorted, these are exactly the sets below the child of the root for that key which
+/// consist of uncovered query keys only.
+#[derive(Debug, Clone)]
+pub struct ExactCovers<'a, 'b, K, T> {
+    root: &'a Node<K, T>,
+    keys: &'b [K],
+    covered: Vec<bool>,
+    // the candidates of every frame, and the number of them which have been tried.
+    frames: Vec<(Vec<Candidate<'a, K, T>>, usize)>,
+    chosen: Vec<Part<'a, K, T>>,
+    // whether the last chosen set still needs to be expanded into a new frame.
+    expand: bool,
+}
+
+impl<'a, 'b, K, T> ExactCovers<'a, 'b, K, T>
+where
+    K: Ord,
+{
+    pub(crate) fn new(root: &'a Node<K, T>, keys: &'b [K]) -> Self {
+        ExactCovers {
+            root,
+            keys,
+            covered: vec![false; keys.len()],
+            frames: vec![],
+            chosen: vec![],
+            expand: true,
+        }
+    }
+
+    /// The stored sets containing the query key at `first`, and no covered or non-query keys.
+    fn candidates(&self, first: usize) -> Vec<Candidate<'a, K, T>> {
+        let mut found = vec![];
+        let Some((key, node)) = self
+            .root
+            .search(&self.keys[first])
+            .ok()
+            .map(|idx| self.root.child_at(idx))
+        else {
+            return found;
+        };
+
+        let mut next = vec![(vec![first], vec![key], node)];
+        while let Some((positions, path, node)) = next.pop() {
+            let last = positions[positions.len() - 1];
+            for (key, child) in node.children().rev() {
+                match self.keys[last + 1..].binary_search(key) {
+                    Ok(idx) if !self.covered[last + 1 + idx] => {
+                        let mut positions = positions.clone();
+                        positions.push(last + 1 + idx);
+                        let mut path = path.clone();
+                        path.push(key);
+                        next.push((positions, path, child));
+                    }
+                    _ => {}
+                }
+            }
+            if !node.leaves.is_empty() {
+                found.push((positions, (path, node.leaves.as_slice())));
+            }
+        }
+        found
+    }
+}
+
+impl<'a, K, T> Iterator for ExactCovers<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = Vec<Part<'a, K, T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.expand {
+                self.expand = false;
+                match self.covered.iter().position(|covered| !covered) {
+                    None => return Some(self.chosen.clone()),
+                    Some(first) => {
+                        let candidates = self.candidates(first);
+                        self.frames.push((candidates, 0));
+                    }
+                }
+            }
+
+            let (candidates, tried) = self.frames.last_mut()?;
+            // undo the previous choice of the frame.
+            if *tried > 0 {
+                for &pos in &candidates[*tried - 1].0 {
+                    self.covered[pos] = false;
+                }
+                self.chosen.pop();
+            }
+
+            let Some((positions, part)) = candidates.get(*tried) else {
+                self.frames.pop();
+                continue;
+            };
+            *tried += 1;
+            for &pos in positions {
+                self.covered[pos] = true;
+            }
+            self.chosen.push(part.clone());
+            self.expand = true;
+        }
+    }
+}
+
+/// Greedily selects stored sets until `keys` is covered, each time picking the set which covers
+/// the most keys which are not yet covered. Stops early if no set covers any of the remaining
+/// keys.
+pub fn greedy<'a, K: Ord, T>(root: &'a Node<K, T>, keys: &[K]) -> Vec<Part<'a, K, T>> {
+    let mut covered = vec![false; keys.len()];
+    let mut chosen = vec![];
+    while let Some((path, leaves)) = most_uncovered(root, keys, &covered) {
+        for key in &path {
+            if let Ok(idx) = keys.binary_search(key) {
+                covered[idx] = true;
+            }
+        }
+        chosen.push((path, leaves));
+    }
+    chosen
+}
+
+/// The first stored set covering the most keys which are not yet `covered`, if it covers any.
+///
+/// Branch and bound DFS: a subtree can at most cover the uncovered keys after its path, so it is
+/// skipped if those can not improve on the best set found so far.
+fn most_uncovered<'a, K: Ord, T>(
+    root: &'a Node<K, T>,
+    keys: &[K],
+    covered: &[bool],
+) -> Option<Part<'a, K, T>> {
+    // the number of uncovered keys from every position of the query on.
+    let mut remaining = vec![0; keys.len() + 1];
+    for idx in (0..keys.len()).rev() {
+        remaining[idx] = remaining[idx + 1] + usize::from(!covered[idx]);
+    }
+
+    let mut best: Option<(usize, Part<'a, K, T>)> = None;
+    let mut path = vec![];
+    // (depth, key, node, covered keys on the path, query position after the key)
+    let mut next: Vec<_> = root
+        .children()
+        .rev()
+        .map(|(key, child)| (1, key, child, 0, 0))
+        .collect();
+    while let Some((depth, key, node, hits, pos)) = next.pop() {
+        let (hits, pos) = match keys[pos..].binary_search(key) {
+            Ok(idx) => (hits + usize::from(!covered[pos + idx]), pos + idx + 1),
+            Err(idx) => (hits, pos + idx),
+        };
+        let best_hits = best.as_ref().map_or(0, |(hits, _)| *hits);
+        if hits + remaining[pos] <= best_hits {
+            continue;
+        }
+
+        path.truncate(depth - 1);
+        path.push(key);
+        if hits > best_hits && !node.leaves.is_empty() {
+            best = Some((hits, (path.clone(), node.leaves.as_slice())));
+        }
+        next.extend(
+            node.children()
+                .rev()
+                .map(|(key, child)| (depth + 1, key, child, hits, pos)),
+        );
+    }
+    best.map(|(_, part)| part)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SetTrie;
+
+    #[test]
+    fn exact_covers() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[1, 2], 'a');
+        trie.insert(&[3], 'b');
+        trie.insert(&[1], 'c');
+        trie.insert(&[2, 3], 'd');
+        trie.insert(&[1, 2, 3], 'e');
+        trie.insert(&[2, 4], 'f');
+        trie.insert(&[], 'g');
+
+        let covers: Vec<Vec<char>> = trie
+            .exact_covers(&[&1, &2, &3])
+            .map(|cover| cover.iter().map(|(_, values)| values[0]).collect())
+            .collect();
+        assert_eq!(covers, vec![vec!['c', 'd'], vec!['a', 'b'], vec!['e']]);
+
+        assert_eq!(
+            trie.exact_covers(&[&1, &2, &4]).collect::<Vec<_>>(),
+            vec![vec![(vec![&&1], &['c'][..]), (vec![&&2, &&4], &['f'][..])]]
+        );
+        assert_eq!(trie.exact_covers(&[&1, &4]).count(), 0);
+        assert_eq!(trie.exact_covers(&[]).count(), 1);
+    }
+
+    #[test]
+    fn cover_greedy() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[1, 2, 3], 'a');
+        trie.insert(&[3, 4, 5, 6], 'b');
+        trie.insert(&[1, 4], 'c');
+        trie.insert(&[2, 5, 7], 'd');
+        trie.insert(&[6, 9], 'e');
+
+        let chosen: Vec<char> = trie
+            .cover_greedy(&[&1, &2, &3, &4, &5, &6])
+            .iter()
+            .map(|(_, values)| values[0])
+            .collect();
+        assert_eq!(chosen, vec!['b', 'a']);
+
+        // 8 can not be covered.
+        let chosen = trie.cover_greedy(&[&1, &2, &7, &8]);
+        assert_eq!(
+            chosen,
+            vec![
+                (vec![&&1, &&2, &&3], &['a'][..]),
+                (vec![&&2, &&5, &&7], &['d'][..])
+            ]
+        );
+        assert!(trie.cover_greedy(&[]).is_empty());
+    }
+}
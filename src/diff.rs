@@ -0,0 +1,291 @@
+use crate::{Node, SetTrie};
+use std::cmp::Ordering;
+use std::iter::Peekable;
+
+/// A set which differs between two tries, yielded by [`TrieDiff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change<'a, K, T> {
+    /// The set only holds values in the other trie.
+    Added(Vec<&'a K>, &'a [T]),
+    /// The set only holds values in this trie.
+    Removed(Vec<&'a K>, &'a [T]),
+    /// The set holds different values in both tries, given as the values in this trie followed by
+    /// those in the other trie.
+    Changed(Vec<&'a K>, &'a [T], &'a [T]),
+}
+
+impl<K, T> Change<'_, K, T>
+where
+    K: Clone,
+    T: Clone,
+{
+    /// Clones the keys and values of the change, so that it can outlive the tries it was taken
+    /// from, for instance to be serialized and [applied](SetTrie::apply) elsewhere.
+    #[must_use]
+    pub fn into_owned(self) -> OwnedChange<K, T> {
+        let owned = |keys: Vec<&K>| keys.into_iter().cloned().collect();
+        match self {
+            Self::Added(keys, values) => OwnedChange::Added(owned(keys), values.to_vec()),
+            Self::Removed(keys, values) => OwnedChange::Removed(owned(keys), values.to_vec()),
+            Self::Changed(keys, old, new) => {
+                OwnedChange::Changed(owned(keys), old.to_vec(), new.to_vec())
+            }
+        }
+    }
+}
+
+/// A [`Change`] which owns its keys and values, created by [into owned](Change::into_owned).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OwnedChange<K, T> {
+    /// The set only holds values in the other trie.
+    Added(Vec<K>, Vec<T>),
+    /// The set only holds values in this trie.
+    Removed(Vec<K>, Vec<T>),
+    /// The set holds different values in both tries, given as the values in this trie followed by
+    /// those in the other trie.
+    Changed(Vec<K>, Vec<T>, Vec<T>),
+}
+
+impl<K, T> From<Change<'_, K, T>> for OwnedChange<K, T>
+where
+    K: Clone,
+    T: Clone,
+{
+    fn from(change: Change<'_, K, T>) -> Self {
+        change.into_owned()
+    }
+}
+
+// (depth, key, node in this trie, node in the other trie)
+type Pair<'a, K, T> = (
+    usize,
+    Option<&'a K>,
+    Option<&'a Node<K, T>>,
+    Option<&'a Node<K, T>>,
+);
+
+/// Iterator for [diff](SetTrie::diff) method.
+#[derive(Debug, Clone)]
+pub struct TrieDiff<'a, K, T> {
+    next: Vec<Pair<'a, K, T>>,
+    path: Vec<&'a K>,
+}
+
+impl<'a, K, T> TrieDiff<'a, K, T>
+where
+    K: Ord,
+{
+    pub(crate) fn new(old: &'a Node<K, T>, new: &'a Node<K, T>) -> Self {
+        Self {
+            next: vec![(0, None, Some(old), Some(new))],
+            path: vec![],
+        }
+    }
+
+    /// Pushes the children of both nodes, pairing up the children with equal keys.
+    fn push_children(
+        &mut self,
+        depth: usize,
+        old: Option<&'a Node<K, T>>,
+        new: Option<&'a Node<K, T>>,
+    ) {
+        let mut old = children(old);
+        let mut new = children(new);
+        let pending = self.next.len();
+        loop {
+            let ordering = match (old.peek(), new.peek()) {
+                (Some((a, _)), Some((b, _))) => a.cmp(b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break,
+            };
+            let (key, old, new) = match ordering {
+                Ordering::Less => old.next().map(|(k, n)| (k, Some(n), None)),
+                Ordering::Greater => new.next().map(|(k, n)| (k, None, Some(n))),
+                Ordering::Equal => old
+                    .next()
+                    .zip(new.next())
+                    .map(|((k, a), (_, b))| (k, Some(a), Some(b))),
+            }
+            .expect("the peeked child exists");
+            self.next.push((depth + 1, Some(key), old, new));
+        }
+        // the smallest key is visited first.
+        self.next[pending..].reverse();
+    }
+}
+
+fn children<K, T>(node: Option<&Node<K, T>>) -> Peekable<impl Iterator<Item = (&K, &Node<K, T>)>> {
+    node.into_iter().flat_map(Node::children).peekable()
+}
+
+impl<'a, K, T> Iterator for TrieDiff<'a, K, T>
+where
+    K: Ord,
+    T: PartialEq,
+{
+    type Item = Change<'a, K, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((depth, key, old, new)) = self.next.pop() {
+            self.path.truncate(depth.saturating_sub(1));
+            self.path.extend(key);
+            self.push_children(depth, old, new);
+
+            let old = old.map_or(&[][..], |node| &node.leaves);
+            let new = new.map_or(&[][..], |node| &node.leaves);
+            let change = match (old.is_empty(), new.is_empty()) {
+                (true, false) => Change::Added(self.path.clone(), new),
+                (false, true) => Change::Removed(self.path.clone(), old),
+                (false, false) if old != new => Change::Changed(self.path.clone(), old, new),
+                _ => continue,
+            };
+            return Some(change);
+        }
+        None
+    }
+}
+
+impl<K, T> SetTrie<K, T>
+where
+    K: Ord,
+{
+    /// Iterates over the sets which hold different values in `self` and `other`, in lexicographic
+    /// order. Both tries are traversed in lockstep, so subtrees which only exist in one of them
+    /// are reported in full.
+    ///
+    /// ```rust
+    /// use set_trie::{Change, SetTrie};
+    ///
+    /// let mut old = SetTrie::new();
+    /// old.insert(&[1], "foo");
+    /// old.insert(&[1, 2], "bar");
+    ///
+    /// let mut new = SetTrie::new();
+    /// new.insert(&[1], "foo");
+    /// new.insert(&[1, 2], "baz");
+    /// new.insert(&[2], "qux");
+    ///
+    /// assert_eq!(
+    ///     old.diff(&new).collect::<Vec<_>>(),
+    ///     vec![
+    ///         Change::Changed(vec![&&1, &&2], &["bar"][..], &["baz"][..]),
+    ///         Change::Added(vec![&&2], &["qux"][..]),
+    ///     ]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn diff<'a>(&'a self, other: &'a Self) -> TrieDiff<'a, K, T> {
+        TrieDiff::new(&self.root, &other.root)
+    }
+
+    /// Replays the changes of a [diff](Self::diff) onto this trie: the sets which were added or
+    /// changed are set to hold exactly their new values, and the removed sets are removed. Applying
+    /// `old.diff(&new)` to a copy of `old` turns it into a copy of `new`. Both borrowed and
+    /// [owned](OwnedChange) changes are accepted, the latter without cloning.
+    ///
+    /// ```rust
+    /// use set_trie::SetTrie;
+    ///
+    /// let mut old = SetTrie::new();
+    /// old.insert(&[1], "foo");
+    /// old.insert(&[1, 2], "bar");
+    ///
+    /// let mut new = SetTrie::new();
+    /// new.insert(&[1, 2], "bar");
+    /// new.insert(&[2], "baz");
+    ///
+    /// let mut replica = SetTrie::new();
+    /// replica.apply(SetTrie::new().diff(&old));
+    /// replica.apply(old.diff(&new));
+    /// assert_eq!(replica.diff(&new).count(), 0);
+    /// ```
+    pub fn apply<C>(&mut self, diff: impl IntoIterator<Item = C>)
+    where
+        K: Clone,
+        C: Into<OwnedChange<K, T>>,
+    {
+        for change in diff {
+            match change.into() {
+                OwnedChange::Added(keys, values) | OwnedChange::Changed(keys, _, values) => {
+                    self.entry(keys).or_create().update_leaves(|leaves| {
+                        *leaves = values;
+                    });
+                }
+                OwnedChange::Removed(keys, _) => {
+                    self.remove(&keys);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Change, SetTrie};
+
+    #[test]
+    fn diff() {
+        let mut old = SetTrie::new();
+        old.insert(vec![], 'a');
+        old.insert(vec![1, 2], 'b');
+        old.insert(vec![1, 3], 'c');
+        old.insert(vec![2, 4], 'd');
+        old.entry(vec![5]).or_create();
+
+        let mut new = SetTrie::new();
+        new.insert(vec![], 'a');
+        new.insert(vec![1], 'e');
+        new.insert(vec![1, 3], 'c');
+        new.insert(vec![1, 3], 'f');
+        new.insert(vec![3], 'g');
+        new.entry(vec![2, 4]).or_create();
+
+        assert_eq!(
+            old.diff(&new).collect::<Vec<_>>(),
+            vec![
+                Change::Added(vec![&1], &['e'][..]),
+                Change::Removed(vec![&1, &2], &['b'][..]),
+                Change::Changed(vec![&1, &3], &['c'][..], &['c', 'f'][..]),
+                Change::Removed(vec![&2, &4], &['d'][..]),
+                Change::Added(vec![&3], &['g'][..]),
+            ]
+        );
+        assert_eq!(old.diff(&old).count(), 0);
+        assert_eq!(new.diff(&SetTrie::new()).count(), 4);
+
+        let mut replica = SetTrie::new();
+        replica.apply(SetTrie::new().diff(&old));
+        assert_eq!(replica.diff(&old).count(), 0);
+
+        replica.apply(old.diff(&new));
+        assert_eq!(replica.diff(&new).count(), 0);
+        assert_eq!(replica.set_count(), new.set_count());
+        assert_eq!(replica.check_invariants(), Ok(()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn apply_serialized() {
+        use crate::OwnedChange;
+
+        let mut old = SetTrie::new();
+        old.insert(vec![1, 2], 'a');
+        old.insert(vec![2], 'b');
+
+        let mut new = SetTrie::new();
+        new.insert(vec![1, 2], 'c');
+        new.insert(vec![3], 'd');
+
+        let changes: Vec<OwnedChange<i32, char>> = old.diff(&new).map(Change::into_owned).collect();
+        let json = serde_json::to_string(&changes).unwrap();
+        let changes: Vec<OwnedChange<i32, char>> = serde_json::from_str(&json).unwrap();
+
+        let mut replica = SetTrie::new();
+        replica.apply(SetTrie::new().diff(&old));
+        replica.apply(changes);
+        assert_eq!(replica.diff(&new).count(), 0);
+        assert_eq!(replica.check_invariants(), Ok(()));
+    }
+}
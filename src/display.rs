@@ -0,0 +1,330 @@
+use crate::{Node, SetTrie};
+use std::fmt::{self, Debug, Display, Formatter};
+
+/// Human-readable rendering of a [`SetTrie`], created by [`SetTrie::display_tree`]. Every node is
+/// rendered on its own line, indented by its depth, followed by the values stored in it.
+///
+/// The rendering is iterative, so it is safe to use on very deep tries.
+#[derive(Debug, Clone)]
+pub struct DisplayTree<'a, K, T> {
+    root: &'a Node<K, T>,
+    max_depth: Option<usize>,
+}
+
+impl<'a, K, T> DisplayTree<'a, K, T> {
+    pub(crate) const fn new(trie: &'a SetTrie<K, T>) -> Self {
+        DisplayTree {
+            root: &trie.root,
+            max_depth: None,
+        }
+    }
+
+    /// Only renders nodes up to `depth` levels below the root. Truncated subtrees are rendered as
+    /// `...`.
+    #[must_use]
+    pub const fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+}
+
+/// Writes the branch drawing for a node, given for each of its ancestors whether it was the last of
+/// its siblings.
+fn write_prefix(f: &mut Formatter<'_>, prefix: &[bool], last: bool) -> fmt::Result {
+    for ancestor_last in prefix {
+        f.write_str(if *ancestor_last { "    " } else { "│   " })?;
+    }
+    f.write_str(if last { "└── " } else { "├── " })
+}
+
+fn write_leaves<T: Debug>(f: &mut Formatter<'_>, leaves: &[T]) -> fmt::Result {
+    if leaves.is_empty() {
+        writeln!(f)
+    } else {
+        writeln!(f, " {leaves:?}")
+    }
+}
+
+impl<'a, K, T> Display for DisplayTree<'a, K, T>
+where
+    K: Debug,
+    T: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let max_depth = self.max_depth.unwrap_or(usize::MAX);
+
+        f.write_str(".")?;
+        write_leaves(f, &self.root.leaves)?;
+
+        // (depth, is last child, key, node)
+        let mut stack: Vec<(usize, bool, &K, &Node<K, T>)> = vec![];
+        let push_children = |stack: &mut Vec<_>, depth, node: &'a Node<K, T>| {
+            let last = node.children.len().saturating_sub(1);
+            stack.extend(
+                node.children()
+                    .enumerate()
+                    .rev()
+                    .map(|(i, (k, n))| (depth, i == last, k, n)),
+            );
+        };
+
+        // for every ancestor of the current node, whether it was the last of its siblings.
+        let mut prefix: Vec<bool> = vec![];
+
+        if max_depth == 0 {
+            if !self.root.children.is_empty() {
+                write_prefix(f, &prefix, true)?;
+                writeln!(f, "...")?;
+            }
+            return Ok(());
+        }
+
+        push_children(&mut stack, 1, self.root);
+        while let Some((depth, last, key, node)) = stack.pop() {
+            prefix.truncate(depth - 1);
+            write_prefix(f, &prefix, last)?;
+            write!(f, "{key:?}")?;
+            write_leaves(f, &node.leaves)?;
+            prefix.push(last);
+
+            if depth < max_depth {
+                push_children(&mut stack, depth + 1, node);
+            } else if !node.children.is_empty() {
+                write_prefix(f, &prefix, true)?;
+                writeln!(f, "...")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Nodes deeper than this are not rendered by the `Debug` implementation of [`Node`].
+const DEBUG_MAX_DEPTH: usize = 8;
+/// Children after the first `DEBUG_MAX_CHILDREN` of a node are not rendered by the `Debug`
+/// implementation of [`Node`].
+const DEBUG_MAX_CHILDREN: usize = 16;
+
+/// Starts a new line indented by `level` when pretty-printing.
+fn newline(f: &mut Formatter<'_>, level: usize) -> fmt::Result {
+    if f.alternate() {
+        f.write_str("\n")?;
+        for _ in 0..level {
+            f.write_str("    ")?;
+        }
+    }
+    Ok(())
+}
+
+/// A step of the `Debug` rendering of a node.
+enum DebugFrame<'a, K, T> {
+    /// A child at the given depth, which is the first of its siblings if `bool` is set.
+    Child(usize, bool, &'a K, &'a Node<K, T>),
+    /// Marks the children of a node at the given depth as truncated.
+    Truncated(usize),
+    /// Closes the node at the given depth after all of its children.
+    Close(usize),
+}
+
+/// Pushes the rendered children of `node` at `depth`, so that the first child is popped first.
+fn push_children<'a, K, T>(
+    stack: &mut Vec<DebugFrame<'a, K, T>>,
+    depth: usize,
+    node: &'a Node<K, T>,
+) {
+    if node.children.len() > DEBUG_MAX_CHILDREN {
+        stack.push(DebugFrame::Truncated(depth));
+    }
+    stack.extend(
+        node.children()
+            .take(DEBUG_MAX_CHILDREN)
+            .enumerate()
+            .rev()
+            .map(|(i, (key, child))| DebugFrame::Child(depth, i == 0, key, child)),
+    );
+}
+
+/// Writes a node at `depth` up to and including the opening brace of its children.
+fn open_node<K, T: Debug>(f: &mut Formatter<'_>, depth: usize, node: &Node<K, T>) -> fmt::Result {
+    f.write_str("Node {")?;
+    if f.alternate() {
+        newline(f, 2 * depth + 1)?;
+        write!(f, "leaves: {:?},", node.leaves)?;
+        newline(f, 2 * depth + 1)?;
+    } else {
+        write!(f, " leaves: {:?}, ", node.leaves)?;
+    }
+    f.write_str("children: {")
+}
+
+/// Closes the node at `depth`. If it has no rendered children, its children are closed on the same
+/// line.
+fn close_node(f: &mut Formatter<'_>, depth: usize, inline: bool) -> fmt::Result {
+    if !inline {
+        newline(f, 2 * depth + 1)?;
+    }
+    if f.alternate() {
+        f.write_str("},")?;
+        newline(f, 2 * depth)?;
+        f.write_str("}")?;
+        if depth > 0 {
+            // every entry of the children of the parent ends in a comma.
+            f.write_str(",")?;
+        }
+        Ok(())
+    } else {
+        f.write_str("} }")
+    }
+}
+
+/// Starts an entry of the children of a node, at the depth of the child.
+fn open_entry(f: &mut Formatter<'_>, depth: usize, first: bool) -> fmt::Result {
+    if !first && !f.alternate() {
+        f.write_str(", ")?;
+    }
+    newline(f, 2 * depth)
+}
+
+/// Renders the nodes iteratively, so that deep tries do not overflow the stack, and truncates deep
+/// and wide tries to keep the output readable. Use [`SetTrie::display_tree`] to render a whole
+/// trie.
+impl<K, T> Debug for Node<K, T>
+where
+    K: Debug,
+    T: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        open_node(f, 0, self)?;
+        if self.children.is_empty() {
+            return close_node(f, 0, true);
+        }
+
+        let mut stack = vec![DebugFrame::Close(0)];
+        push_children(&mut stack, 1, self);
+        while let Some(frame) = stack.pop() {
+            match frame {
+                DebugFrame::Child(depth, first, key, node) => {
+                    open_entry(f, depth, first)?;
+                    write!(f, "{key:?}: ")?;
+                    open_node(f, depth, node)?;
+                    if node.children.is_empty() {
+                        close_node(f, depth, true)?;
+                    } else if depth >= DEBUG_MAX_DEPTH {
+                        f.write_str("..")?;
+                        close_node(f, depth, true)?;
+                    } else {
+                        stack.push(DebugFrame::Close(depth));
+                        push_children(&mut stack, depth + 1, node);
+                    }
+                }
+                DebugFrame::Truncated(depth) => {
+                    open_entry(f, depth, false)?;
+                    f.write_str("..")?;
+                }
+                DebugFrame::Close(depth) => close_node(f, depth, false)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Renders the root node only, leaving out the settings and statistics of the trie.
+impl<K, T> Debug for SetTrie<K, T>
+where
+    K: Debug,
+    T: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SetTrie").field(&self.root).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DEBUG_MAX_CHILDREN, DEBUG_MAX_DEPTH};
+    use crate::SetTrie;
+
+    #[test]
+    fn debug() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[], "a");
+        trie.insert(&[1, 2], "b");
+        trie.insert(&[3], "c");
+        assert_eq!(
+            format!("{:?}", trie.root),
+            r#"Node { leaves: ["a"], children: {1: Node { leaves: [], children: {2: Node { leaves: ["b"], children: {} }} }, 3: Node { leaves: ["c"], children: {} }} }"#
+        );
+        assert_eq!(
+            format!("{:#?}", trie.root),
+            r#"Node {
+    leaves: ["a"],
+    children: {
+        1: Node {
+            leaves: [],
+            children: {
+                2: Node {
+                    leaves: ["b"],
+                    children: {},
+                },
+            },
+        },
+        3: Node {
+            leaves: ["c"],
+            children: {},
+        },
+    },
+}"#
+        );
+
+        // wide and deep tries are truncated, and rendering does not recurse.
+        let mut trie = SetTrie::new();
+        trie.insert(0..100_000, ());
+        for i in 1..=DEBUG_MAX_CHILDREN {
+            trie.insert(vec![i], ());
+        }
+        let rendered = format!("{:?}", trie.root);
+        assert!(rendered.contains("7: Node { leaves: [], children: {..} }"));
+        assert!(rendered.ends_with("15: Node { leaves: [()], children: {} }, ..} }"));
+        assert_eq!(
+            rendered.matches("Node").count(),
+            DEBUG_MAX_DEPTH + DEBUG_MAX_CHILDREN
+        );
+    }
+
+    #[test]
+    fn display_tree() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[], "a");
+        trie.insert(&[1, 2], "b");
+        trie.insert(&[1, 2, 3], "c");
+        trie.insert(&[1, 4], "d");
+        trie.insert(&[2], "e");
+        trie.insert(&[2], "f");
+
+        assert_eq!(
+            trie.display_tree().to_string(),
+            r#". ["a"]
+├── 1
+│   ├── 2 ["b"]
+│   │   └── 3 ["c"]
+│   └── 4 ["d"]
+└── 2 ["e", "f"]
+"#
+        );
+
+        assert_eq!(
+            trie.display_tree().max_depth(1).to_string(),
+            r#". ["a"]
+├── 1
+│   └── ...
+└── 2 ["e", "f"]
+"#
+        );
+
+        assert_eq!(
+            trie.display_tree().max_depth(0).to_string(),
+            r#". ["a"]
+└── ...
+"#
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use crate::Node;
+
+/// A node which is yet to be visited: (node, number of query keys passed, mismatches so far).
+type Pending<'a, K, T> = (&'a Node<K, T>, usize, usize);
+
+/// Iterator for [within distance](crate::SetTrie::within_distance) method.
+///
+/// Branch and bound DFS: every pending node carries the number of mismatches on its path, being
+/// the keys on the path which are not in the query plus the query keys which were passed over.
+/// Mismatches never decrease further down, so a subtree is skipped as soon as they exceed the
+/// budget.
+#[derive(Debug, Clone)]
+pub struct WithinDistance<'a, 'b, K, T> {
+    leaves: std::slice::Iter<'a, T>,
+    next: Vec<Pending<'a, K, T>>,
+    keys: &'b [K],
+    max: usize,
+}
+
+impl<'a, 'b, K, T> WithinDistance<'a, 'b, K, T>
+where
+    K: Ord,
+{
+    pub(crate) fn new(root: &'a Node<K, T>, keys: &'b [K], max: usize) -> Self {
+        WithinDistance {
+            leaves: [].iter(),
+            next: vec![(root, 0, 0)],
+            keys,
+            max,
+        }
+    }
+
+    fn push_children(&mut self, node: &'a Node<K, T>, matched: usize, cost: usize) {
+        let start = self.next.len();
+        let mut passed = matched;
+        for (key, child) in node.children() {
+            passed += self.keys[passed..].partition_point(|k| k < key);
+            // the query keys passed over are missing from the set, and from those of any larger
+            // sibling.
+            let skipped = cost + passed - matched;
+            if skipped > self.max {
+                break;
+            }
+
+            if self.keys.get(passed) == Some(key) {
+                self.next.push((child, passed + 1, skipped));
+            } else if skipped < self.max {
+                self.next.push((child, passed, skipped + 1));
+            }
+        }
+        // the smallest key is visited first.
+        self.next[start..].reverse();
+    }
+}
+
+impl<'a, K, T> Iterator for WithinDistance<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some(item);
+            }
+
+            let (node, matched, cost) = self.next.pop()?;
+            self.push_children(node, matched, cost);
+            // the query keys after the path are missing from the set.
+            if cost + self.keys.len() - matched <= self.max {
+                self.leaves = node.leaves.iter();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SetTrie;
+
+    #[test]
+    fn within_distance() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[1, 2, 3], 'a');
+        trie.insert(&[1, 2], 'b');
+        trie.insert(&[1, 2, 4], 'c');
+        trie.insert(&[2, 3], 'd');
+        trie.insert(&[4, 5, 6], 'e');
+        trie.insert(&[], 'f');
+
+        let query = [&1, &2, &3];
+        assert_eq!(
+            trie.within_distance(&query, 0).collect::<Vec<_>>(),
+            vec![&'a']
+        );
+        assert_eq!(
+            trie.within_distance(&query, 1).collect::<Vec<_>>(),
+            vec![&'b', &'a', &'d']
+        );
+        assert_eq!(
+            trie.within_distance(&query, 2).collect::<Vec<_>>(),
+            vec![&'b', &'a', &'c', &'d']
+        );
+        assert_eq!(trie.within_distance(&query, 6).count(), 6);
+        assert_eq!(
+            trie.within_distance(&[], 2).collect::<Vec<_>>(),
+            vec![&'f', &'b', &'d']
+        );
+    }
+}
@@ -0,0 +1,101 @@
+/// Iterator for [drain subsets](crate::SetTrie::drain_subsets),
+/// [drain supersets](crate::SetTrie::drain_supersets) and [extract if](crate::SetTrie::extract_if)
+/// methods. The items are removed from the trie when the iterator is created, so dropping it early
+/// does not restore them.
+#[derive(Debug)]
+pub struct Drain<T>(std::vec::IntoIter<T>);
+
+impl<T> Drain<T> {
+    pub(crate) fn new(values: Vec<T>) -> Self {
+        Self(values.into_iter())
+    }
+}
+
+impl<T> Iterator for Drain<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Drain<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<T> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::SetTrie;
+
+    #[test]
+    fn drain_subsets() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[1], 'a');
+        trie.insert(&[1, 2], 'b');
+        trie.insert(&[1, 2, 3], 'c');
+        trie.insert(&[1, 3], 'd');
+        trie.insert(&[2, 3], 'e');
+
+        assert_eq!(
+            trie.drain_subsets(&[&1, &2]).collect::<Vec<_>>(),
+            vec!['a', 'b']
+        );
+        assert_eq!(trie.subsets(&[&1, &2]).count(), 0);
+        assert_eq!(trie.values().collect::<Vec<_>>(), vec![&'c', &'d', &'e']);
+
+        // emptied nodes without children are removed.
+        assert_eq!(trie.drain_subsets(&[&1, &2, &3]).count(), 3);
+        assert!(trie.root.children.is_empty());
+    }
+
+    #[test]
+    fn drain_supersets() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[], 'a');
+        trie.insert(&[1, 2], 'b');
+        trie.insert(&[1, 2, 3], 'c');
+        trie.insert(&[1, 3], 'd');
+        trie.insert(&[2, 3], 'e');
+
+        assert_eq!(
+            trie.drain_supersets(&[&2, &3]).collect::<Vec<_>>(),
+            vec!['c', 'e']
+        );
+        assert_eq!(trie.values().collect::<Vec<_>>(), vec![&'a', &'b', &'d']);
+
+        assert_eq!(
+            trie.drain_supersets(&[]).collect::<Vec<_>>(),
+            vec!['a', 'b', 'd']
+        );
+        assert!(trie.root.children.is_empty());
+    }
+
+    #[test]
+    fn extract_if() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[], 0);
+        trie.insert(&[1], 1);
+        trie.insert(&[1], 2);
+        trie.insert(&[1, 2], 3);
+        trie.insert(&[2], 4);
+
+        let extracted = trie
+            .extract_if(|keys, value| {
+                *value += 10;
+                keys.len() == 1 && *value % 2 == 0
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(extracted, vec![(vec![&1], 12), (vec![&2], 14)]);
+        assert_eq!(trie.values().collect::<Vec<_>>(), vec![&10, &11, &13]);
+        assert_eq!(trie.root.children.len(), 1);
+    }
+}
@@ -0,0 +1,358 @@
+use crate::SetTrie;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+const SNAPSHOT: &str = "snapshot.json";
+const LOG: &str = "log.jsonl";
+
+/// The number of the last record folded into the snapshot, and the values of every stored set.
+type Snapshot<K, T> = (u64, Vec<(Vec<K>, Vec<T>)>);
+
+const INSERT: &str = "insert";
+const REMOVE: &str = "remove";
+
+/// A [`SetTrie`] stored in a directory, which survives restarts without being rebuilt.
+///
+/// Every insertion and removal is appended to a write-ahead log before it is applied. The log is
+/// folded into a snapshot of the whole trie by [snapshot](Self::snapshot), either explicitly or
+/// every so many records if [snapshot every](Self::snapshot_every) is set. Reopening the
+/// directory loads the snapshot and replays the log on top of it.
+///
+/// Both files hold JSON, one record per line for the log. Records are written to the operating
+/// system but not synced to the disk, call [sync](Self::sync) for that. A record which was cut
+/// short by a crash while being written is ignored on reopening. Every record is numbered, and
+/// the snapshot holds the number of the last record folded into it, so that records which are
+/// still in the log after a crash during a snapshot are not applied twice.
+///
+/// ```rust
+/// use set_trie::DurableSetTrie;
+///
+/// let dir = std::env::temp_dir().join("set-trie-durable-doc");
+/// # let _ = std::fs::remove_dir_all(&dir);
+/// let mut trie = DurableSetTrie::open(&dir).unwrap();
+/// trie.insert(vec![1, 2], "foo".to_string()).unwrap();
+/// trie.insert(vec![2], "bar".to_string()).unwrap();
+/// trie.remove(&[2]).unwrap();
+/// drop(trie);
+///
+/// let trie: DurableSetTrie<u32, String> = DurableSetTrie::open(&dir).unwrap();
+/// assert_eq!(trie.supersets(&[2]).collect::<Vec<_>>(), vec![&"foo"]);
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct DurableSetTrie<K, T> {
+    trie: SetTrie<K, T>,
+    dir: PathBuf,
+    log: File,
+    // the length of the log up to the last complete record, and the number of the last record.
+    len: u64,
+    seq: u64,
+    // records in the log, and after how many records a snapshot is taken.
+    records: usize,
+    snapshot_every: Option<usize>,
+}
+
+impl<K, T> DurableSetTrie<K, T>
+where
+    K: Ord + Clone + Serialize + DeserializeOwned,
+    T: Serialize + DeserializeOwned,
+{
+    /// Opens the trie stored in `dir`, creating the directory if it does not exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the files cannot be read or written, or if they are corrupt.
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let mut trie = SetTrie::new();
+        // the number of the last record in the snapshot.
+        let mut seq = 0;
+        match File::open(dir.join(SNAPSHOT)) {
+            Ok(file) => {
+                let (last, sets): Snapshot<K, T> = serde_json::from_reader(BufReader::new(file))?;
+                seq = last;
+                for (keys, items) in sets {
+                    trie.insert_many(keys, items);
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+
+        let path = dir.join(LOG);
+        let mut records = 0;
+        let mut len = 0;
+        if let Ok(file) = File::open(&path) {
+            let mut reader = BufReader::new(file);
+            let mut line = String::new();
+            while reader.read_line(&mut line)? != 0 {
+                if !line.ends_with('\n') {
+                    break;
+                }
+                // records up to the number in the snapshot were folded into it before a crash
+                // prevented emptying the log.
+                if let Some(next) = replay(&mut trie, &line, seq)? {
+                    seq = next;
+                    records += 1;
+                }
+                len += line.len() as u64;
+                line.clear();
+            }
+        }
+
+        let log = OpenOptions::new().create(true).append(true).open(&path)?;
+        log.set_len(len)?;
+
+        Ok(Self {
+            trie,
+            dir,
+            log,
+            len,
+            seq,
+            records,
+            snapshot_every: None,
+        })
+    }
+
+    /// Takes a [snapshot](Self::snapshot) whenever the log holds `records` records, bounding the
+    /// time it takes to reopen the trie. The operation which fills the log succeeds even if the
+    /// snapshot fails, as it is logged already; the snapshot is then retried after every following
+    /// operation, and calling [snapshot](Self::snapshot) returns the error.
+    #[must_use]
+    pub const fn snapshot_every(mut self, records: usize) -> Self {
+        self.snapshot_every = Some(records);
+        self
+    }
+
+    /// Insert the item in the given set. See [`SetTrie::insert`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the insertion cannot be logged, in which case it is not applied.
+    pub fn insert(&mut self, keys: impl IntoIterator<Item = K>, item: T) -> io::Result<()> {
+        let keys: Vec<K> = keys.into_iter().collect();
+        self.append(&(self.seq + 1, INSERT, &keys, &item))?;
+        self.trie.insert(keys, item);
+        self.maybe_snapshot();
+        Ok(())
+    }
+
+    /// Removes the set `keys`, returning its values if it was stored. See [`SetTrie::remove`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the removal cannot be logged, in which case it is not applied.
+    pub fn remove(&mut self, keys: &[K]) -> io::Result<Option<Vec<T>>> {
+        self.append(&(self.seq + 1, REMOVE, keys))?;
+        let removed = self.trie.remove(keys);
+        self.maybe_snapshot();
+        Ok(removed)
+    }
+
+    /// Writes the whole trie to a new snapshot and empties the log. The snapshot replaces the
+    /// previous one atomically, so that a crash leaves either the old or the new one, and records
+    /// left in the log by a crash after the replacement are skipped on reopening.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the snapshot cannot be written.
+    pub fn snapshot(&mut self) -> io::Result<()> {
+        let mut sets: Vec<(Vec<&K>, Vec<&T>)> = vec![];
+        for (path, item) in &self.trie {
+            match sets.last_mut() {
+                Some((last, items)) if *last == path => items.push(item),
+                _ => sets.push((path, vec![item])),
+            }
+        }
+
+        let path = self.dir.join(SNAPSHOT);
+        let tmp = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        serde_json::to_writer(&mut writer, &(self.seq, &sets))?;
+        writer.into_inner()?.sync_all()?;
+        fs::rename(&tmp, &path)?;
+
+        self.log.set_len(0)?;
+        self.len = 0;
+        self.records = 0;
+        Ok(())
+    }
+
+    /// Flushes the log and syncs it to the disk, making all previous operations durable.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the log cannot be written.
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.log.sync_data()
+    }
+
+    /// Appends the record as a single write, truncating the log back to its last complete record
+    /// if the write fails part way.
+    fn append(&mut self, record: &impl Serialize) -> io::Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        if let Err(err) = self.log.write_all(&line) {
+            self.log.set_len(self.len)?;
+            return Err(err);
+        }
+        self.len += line.len() as u64;
+        self.seq += 1;
+        self.records += 1;
+        Ok(())
+    }
+
+    /// Takes a snapshot if the log holds enough records. A failed snapshot leaves the log as it
+    /// is, so that it is retried after the next record.
+    fn maybe_snapshot(&mut self) {
+        if self
+            .snapshot_every
+            .is_some_and(|every| self.records >= every)
+        {
+            // the last operation is logged and applied, whether or not the snapshot succeeds.
+            let _ = self.snapshot();
+        }
+    }
+}
+
+/// Applies a record of the log to the trie, unless its number is at most `seq`. Returns the
+/// number of the record if it was applied.
+fn replay<K, T>(trie: &mut SetTrie<K, T>, line: &str, seq: u64) -> io::Result<Option<u64>>
+where
+    K: Ord + Clone + DeserializeOwned,
+    T: DeserializeOwned,
+{
+    let record: Vec<Value> = serde_json::from_str(line)?;
+    let Some(number) = record.first().and_then(Value::as_u64) else {
+        return Err(invalid_record());
+    };
+    if number <= seq {
+        return Ok(None);
+    }
+    match &record[1..] {
+        [Value::String(op), keys, item] if op == INSERT => {
+            let keys: Vec<K> = serde_json::from_value(keys.clone())?;
+            trie.insert(keys, serde_json::from_value(item.clone())?);
+        }
+        [Value::String(op), keys] if op == REMOVE => {
+            let keys: Vec<K> = serde_json::from_value(keys.clone())?;
+            trie.remove(&keys);
+        }
+        _ => return Err(invalid_record()),
+    }
+    Ok(Some(number))
+}
+
+fn invalid_record() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "invalid log record")
+}
+
+impl<K, T> Deref for DurableSetTrie<K, T> {
+    type Target = SetTrie<K, T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.trie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DurableSetTrie;
+    use std::fs;
+    use std::io::Write;
+
+    #[test]
+    fn durable_set_trie() {
+        let dir = std::env::temp_dir().join(format!("set-trie-durable-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut trie = DurableSetTrie::open(&dir).unwrap().snapshot_every(3);
+        trie.insert(vec![1, 2], 'a').unwrap();
+        trie.insert(vec![1], 'b').unwrap();
+        trie.insert(vec![1, 2], 'c').unwrap();
+        // the snapshot has been taken, these are in the log only.
+        trie.insert(vec![3], 'd').unwrap();
+        assert_eq!(trie.remove(&[1]).unwrap(), Some(vec!['b']));
+        drop(trie);
+
+        // a record cut short by a crash.
+        let mut log = fs::OpenOptions::new()
+            .append(true)
+            .open(dir.join("log.jsonl"))
+            .unwrap();
+        log.write_all(b"[6,\"insert\",[4").unwrap();
+        drop(log);
+
+        let mut trie: DurableSetTrie<i32, char> = DurableSetTrie::open(&dir).unwrap();
+        assert_eq!(
+            trie.iter().collect::<Vec<_>>(),
+            vec![(vec![&1, &2], &'a'), (vec![&1, &2], &'c'), (vec![&3], &'d')]
+        );
+
+        trie.insert(vec![4], 'e').unwrap();
+        trie.snapshot().unwrap();
+        drop(trie);
+        let trie: DurableSetTrie<i32, char> = DurableSetTrie::open(&dir).unwrap();
+        assert_eq!(trie.values().collect::<String>(), "acde");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_snapshot() {
+        let dir = std::env::temp_dir().join(format!("set-trie-snapshot-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut trie = DurableSetTrie::open(&dir).unwrap().snapshot_every(1);
+        // the temporary snapshot cannot be created while a directory is in its place.
+        fs::create_dir(dir.join("snapshot.tmp")).unwrap();
+        trie.insert(vec![1], 'a').unwrap();
+        assert_eq!(trie.remove(&[1]).unwrap(), Some(vec!['a']));
+        assert!(trie.snapshot().is_err());
+        assert!(!dir.join("snapshot.json").exists());
+
+        fs::remove_dir(dir.join("snapshot.tmp")).unwrap();
+        trie.insert(vec![2], 'b').unwrap();
+        assert!(dir.join("snapshot.json").exists());
+        assert_eq!(fs::metadata(dir.join("log.jsonl")).unwrap().len(), 0);
+        drop(trie);
+
+        let trie: DurableSetTrie<i32, char> = DurableSetTrie::open(&dir).unwrap();
+        assert_eq!(trie.values().collect::<String>(), "b");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn crash_during_snapshot() {
+        let dir = std::env::temp_dir().join(format!("set-trie-crash-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut trie = DurableSetTrie::open(&dir).unwrap();
+        trie.insert(vec![1], 'a').unwrap();
+        trie.insert(vec![2], 'b').unwrap();
+        trie.remove(&[2]).unwrap();
+        trie.insert(vec![2], 'c').unwrap();
+        let log = fs::read(dir.join("log.jsonl")).unwrap();
+        trie.snapshot().unwrap();
+        drop(trie);
+
+        // the snapshot replaced the previous one, but the log was not emptied.
+        fs::write(dir.join("log.jsonl"), log).unwrap();
+        let mut trie: DurableSetTrie<i32, char> = DurableSetTrie::open(&dir).unwrap();
+        assert_eq!(trie.values().collect::<String>(), "ac");
+
+        trie.insert(vec![3], 'd').unwrap();
+        drop(trie);
+        let trie: DurableSetTrie<i32, char> = DurableSetTrie::open(&dir).unwrap();
+        assert_eq!(trie.values().collect::<String>(), "acd");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
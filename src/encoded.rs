@@ -0,0 +1,287 @@
+use crate::{Entry, EntryBuilder, SetTrie};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::hash::Hash;
+use std::ops::Deref;
+
+/// Maps keys to dense `u32` symbols and back. Symbols are handed out in order of first
+/// appearance, unless the dictionary is seeded with a chosen order through
+/// [`from_keys`](Self::from_keys).
+///
+/// ```rust
+/// use set_trie::Dictionary;
+///
+/// let mut dictionary = Dictionary::from_keys(vec!["rare", "common"]);
+/// assert_eq!(dictionary.encode("common"), 1);
+/// assert_eq!(dictionary.encode("new"), 2);
+/// assert_eq!(dictionary.decode(0), Some(&"rare"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Dictionary<K> {
+    symbols: HashMap<K, u32>,
+    keys: Vec<K>,
+}
+
+impl<K> Default for Dictionary<K> {
+    fn default() -> Self {
+        Self {
+            symbols: HashMap::new(),
+            keys: vec![],
+        }
+    }
+}
+
+impl<K> Dictionary<K> {
+    /// Create a new, empty `Dictionary`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of encoded keys.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Whether no keys have been encoded.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// The key of `symbol`, if it has been handed out.
+    #[must_use]
+    pub fn decode(&self, symbol: u32) -> Option<&K> {
+        self.keys.get(symbol as usize)
+    }
+}
+
+impl<K> Dictionary<K>
+where
+    K: Hash + Eq + Clone,
+{
+    /// Create a dictionary which hands out symbols to `keys` in the given order, so that a trie
+    /// using it orders its keys likewise. Duplicate keys keep their first symbol.
+    pub fn from_keys(keys: impl IntoIterator<Item = K>) -> Self {
+        let mut dictionary = Self::new();
+        for key in keys {
+            dictionary.encode(key);
+        }
+        dictionary
+    }
+
+    /// The symbol of `key`, handing out the next symbol if it is new.
+    ///
+    /// # Panics
+    ///
+    /// If more than `u32::MAX` keys are encoded.
+    pub fn encode(&mut self, key: K) -> u32 {
+        if let Some(&symbol) = self.symbols.get(&key) {
+            return symbol;
+        }
+
+        let symbol = u32::try_from(self.keys.len()).expect("encoded more than u32::MAX keys");
+        self.keys.push(key.clone());
+        self.symbols.insert(key, symbol);
+        symbol
+    }
+
+    /// The symbol of `key`, if it has been encoded.
+    #[must_use]
+    pub fn get<Q>(&self, key: &Q) -> Option<u32>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.symbols.get(key).copied()
+    }
+
+    /// The sorted, deduplicated symbols of `keys`, ready to be used as a query. Keys which have
+    /// not been encoded map to the next free symbol, which no stored set contains.
+    pub fn symbols<'k, Q>(&self, keys: impl IntoIterator<Item = &'k Q>) -> Vec<u32>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized + 'k,
+    {
+        let free = u32::try_from(self.keys.len()).unwrap_or(u32::MAX);
+        let mut symbols: Vec<u32> = keys
+            .into_iter()
+            .map(|key| self.get(key).unwrap_or(free))
+            .collect();
+        symbols.sort_unstable();
+        symbols.dedup();
+        symbols
+    }
+}
+
+/// A [`SetTrie`] which stores its keys as dense `u32` symbols of a [`Dictionary`].
+///
+/// This shrinks the nodes and turns key comparisons into integer comparisons, which pays off when
+/// `K` is large or expensive to compare.
+///
+/// Sets are inserted and removed by their keys; queries are available through `Deref` on the
+/// symbols returned by [encode](Self::encode), and [decode](Self::decode) maps symbols in the
+/// output back to keys.
+///
+/// ```rust
+/// use set_trie::EncodedSetTrie;
+///
+/// let mut trie = EncodedSetTrie::new();
+/// trie.insert(vec![(2, 'b'), (1, 'a')], "foo");
+/// trie.insert(vec![(1, 'a')], "bar");
+///
+/// let query = trie.encode(&[(1, 'a')]);
+/// assert_eq!(trie.supersets(&query).collect::<Vec<_>>(), vec![&"foo", &"bar"]);
+///
+/// let (first, _) = trie.first().unwrap();
+/// assert_eq!(trie.decode(first), vec![&(2, 'b'), &(1, 'a')]);
+/// ```
+#[derive(Debug)]
+pub struct EncodedSetTrie<K, T> {
+    trie: SetTrie<u32, T>,
+    dictionary: Dictionary<K>,
+}
+
+impl<K, T> Default for EncodedSetTrie<K, T> {
+    fn default() -> Self {
+        Self::with_dictionary(Dictionary::new())
+    }
+}
+
+impl<K, T> EncodedSetTrie<K, T> {
+    /// Create a new, empty `EncodedSetTrie`, handing out symbols in order of first appearance.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new, empty `EncodedSetTrie` using `dictionary`, which may be seeded with a chosen
+    /// [key order](Dictionary::from_keys).
+    #[must_use]
+    pub const fn with_dictionary(dictionary: Dictionary<K>) -> Self {
+        Self {
+            trie: SetTrie::new(),
+            dictionary,
+        }
+    }
+
+    /// The dictionary mapping the keys of the trie to symbols.
+    #[must_use]
+    pub const fn dictionary(&self) -> &Dictionary<K> {
+        &self.dictionary
+    }
+
+    /// Maps the `symbols` of a key path yielded by the trie back to their keys.
+    ///
+    /// # Panics
+    ///
+    /// If a symbol was not handed out by the dictionary of this trie.
+    pub fn decode<'a>(&self, symbols: impl IntoIterator<Item = &'a u32>) -> Vec<&K> {
+        symbols
+            .into_iter()
+            .map(|&symbol| {
+                self.dictionary
+                    .decode(symbol)
+                    .expect("symbol is not encoded by this trie")
+            })
+            .collect()
+    }
+}
+
+impl<K, T> EncodedSetTrie<K, T>
+where
+    K: Hash + Eq + Clone,
+{
+    /// The sorted symbols of `keys`, to be passed to the queries of the trie. See
+    /// [`Dictionary::symbols`].
+    pub fn encode<'k, Q>(&self, keys: impl IntoIterator<Item = &'k Q>) -> Vec<u32>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized + 'k,
+    {
+        self.dictionary.symbols(keys)
+    }
+
+    /// A view into the node of the set `keys`, encoding any new keys. See [`SetTrie::entry`].
+    #[must_use]
+    pub fn entry(
+        &mut self,
+        keys: impl IntoIterator<Item = K>,
+    ) -> EntryBuilder<'_, u32, T, std::vec::IntoIter<u32>> {
+        self.entry_by(keys, Dictionary::encode)
+    }
+
+    /// A view into the node of the set `keys`, of which every key is mapped to its symbol by
+    /// `encode`.
+    pub(crate) fn entry_by<I: IntoIterator>(
+        &mut self,
+        keys: I,
+        mut encode: impl FnMut(&mut Dictionary<K>, I::Item) -> u32,
+    ) -> EntryBuilder<'_, u32, T, std::vec::IntoIter<u32>> {
+        let mut symbols: Vec<u32> = keys
+            .into_iter()
+            .map(|key| encode(&mut self.dictionary, key))
+            .collect();
+        symbols.sort_unstable();
+        symbols.dedup();
+        self.trie.entry(symbols)
+    }
+
+    /// Insert the item in the given set, encoding any new keys. See [`SetTrie::insert`].
+    pub fn insert(&mut self, keys: impl IntoIterator<Item = K>, item: T) -> Entry<'_, u32, T> {
+        self.entry(keys).and_insert(item)
+    }
+
+    /// Removes the set `keys`, returning its values if it was stored. See [`SetTrie::remove`].
+    pub fn remove<'k, Q>(&mut self, keys: impl IntoIterator<Item = &'k Q>) -> Option<Vec<T>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized + 'k,
+    {
+        let symbols = self.dictionary.symbols(keys);
+        self.trie.remove(&symbols)
+    }
+}
+
+impl<K, T> Deref for EncodedSetTrie<K, T> {
+    type Target = SetTrie<u32, T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.trie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Dictionary, EncodedSetTrie};
+
+    #[test]
+    fn encoded_set_trie() {
+        // 30 sorts before 10 in the trie.
+        let mut trie = EncodedSetTrie::with_dictionary(Dictionary::from_keys(vec![30, 20, 30]));
+        trie.insert(vec![10, 30], 'a');
+        trie.insert(vec![20, 20], 'b');
+        trie.insert(vec![10], 'c');
+        assert_eq!(trie.dictionary().len(), 3);
+
+        assert_eq!(
+            trie.subsets(&trie.encode(&[10, 20, 30]))
+                .collect::<Vec<_>>(),
+            vec![&'a', &'b', &'c']
+        );
+        assert_eq!(
+            trie.supersets(&trie.encode(&[10])).collect::<Vec<_>>(),
+            vec![&'a', &'c']
+        );
+        assert_eq!(trie.supersets(&trie.encode(&[10, 40])).count(), 0);
+
+        let (first, _) = trie.first().unwrap();
+        assert_eq!(trie.decode(first), vec![&30, &10]);
+
+        assert_eq!(trie.remove(&[30, 10]), Some(vec!['a']));
+        assert_eq!(trie.remove(&[40]), None);
+        assert_eq!(trie.values().collect::<Vec<_>>(), vec![&'b', &'c']);
+    }
+}
@@ -1,32 +1,61 @@
 #![allow(clippy::module_name_repetitions)]
 
+use crate::stats::{KeyCounts, PendingCounts, SetCounts, SubtreeCounts};
 use crate::{Node, SetTrie};
 
 /// `EntryBuilder` for the [entry](SetTrie::entry) method. Entries are lazily evaluated, thus the builder
 /// is used to provide the configuration, while the [entry](Entry) is already evaluated.
 pub struct EntryBuilder<'a, K, T, IK>
 where
-    IK: Iterator<Item = K> + 'a,
+    IK: Iterator<Item = K>,
     K: Ord,
 {
     node: &'a mut Node<K, T>,
+    // the key path of `node`.
+    path: Vec<&'a K>,
+    // the number of nodes holding values in the whole trie.
+    sets: &'a mut SetCounts,
+    // the changes to the counts of the ancestors of the node, yet to be applied.
+    pending: &'a mut PendingCounts,
+    // the number of nodes of every key below which values are stored in the whole trie.
+    key_counts: &'a mut KeyCounts<K>,
     keys: IK,
 }
 
 impl<'a, K, T, IK> EntryBuilder<'a, K, T, IK>
 where
-    IK: Iterator<Item = K> + 'a,
-    K: Ord,
+    IK: Iterator<Item = K>,
+    K: Ord + Clone,
 {
     pub(crate) fn new(trie: &'a mut SetTrie<K, T>, keys: IK) -> Self {
+        // the entry starts a new path from the root.
+        trie.pending.flush(&mut trie.root, &mut trie.keys);
         EntryBuilder {
-            node: &mut trie.0,
+            node: &mut trie.root,
+            path: vec![],
+            sets: &mut trie.sets,
+            pending: &mut trie.pending,
+            key_counts: &mut trie.keys,
             keys,
         }
     }
 
-    pub(crate) fn from_node(node: &'a mut Node<K, T>, keys: IK) -> Self {
-        EntryBuilder { node, keys }
+    pub(crate) const fn from_node(
+        node: &'a mut Node<K, T>,
+        path: Vec<&'a K>,
+        sets: &'a mut SetCounts,
+        pending: &'a mut PendingCounts,
+        key_counts: &'a mut KeyCounts<K>,
+        keys: IK,
+    ) -> Self {
+        EntryBuilder {
+            node,
+            path,
+            sets,
+            pending,
+            key_counts,
+            keys,
+        }
     }
 }
 
@@ -48,6 +77,11 @@ where
     K: Ord,
 {
     node: &'a mut Node<K, T>,
+    path: Vec<&'a K>,
+    sets: &'a mut SetCounts,
+    // the changes to the counts of the ancestors of the node, yet to be applied.
+    pending: &'a mut PendingCounts,
+    key_counts: &'a mut KeyCounts<K>,
 }
 
 /// Indicates that the entry already exists.
@@ -56,113 +90,172 @@ where
     K: Ord,
 {
     node: &'a mut Node<K, T>,
+    path: Vec<&'a K>,
+    sets: &'a mut SetCounts,
+    // the changes to the counts of the ancestors of the node, yet to be applied.
+    pending: &'a mut PendingCounts,
+    key_counts: &'a mut KeyCounts<K>,
 }
 
-impl<'a, K, T, IK> EntryBuilder<'a, K, T, IK>
+impl<'a, K, T> ExistingEntry<'a, K, T>
 where
-    IK: Iterator<Item = K> + 'a,
     K: Ord,
+{
+    /// The set represented by the entry.
+    #[must_use]
+    pub fn path(&self) -> Vec<&K> {
+        self.path.clone()
+    }
+
+    /// Converts the entry into its items, which live as long as the borrow of the trie.
+    #[must_use]
+    pub fn into_items(self) -> &'a [T] {
+        &self.node.leaves
+    }
+
+    /// Converts the entry into its items, which can be mutated for as long as the trie is
+    /// borrowed. See [`Entry::into_items_mut`].
+    #[must_use]
+    pub fn into_items_mut(self) -> &'a mut [T] {
+        &mut self.node.leaves
+    }
+}
+
+impl<'a, K, T> CreatedEntry<'a, K, T>
+where
+    K: Ord,
+{
+    /// Converts the entry into its items, which live as long as the borrow of the trie.
+    #[must_use]
+    pub fn into_items(self) -> &'a [T] {
+        &self.node.leaves
+    }
+
+    /// Converts the entry into its items, which can be mutated for as long as the trie is
+    /// borrowed. See [`Entry::into_items_mut`].
+    #[must_use]
+    pub fn into_items_mut(self) -> &'a mut [T] {
+        &mut self.node.leaves
+    }
+}
+
+impl<'a, K, T, IK> EntryBuilder<'a, K, T, IK>
+where
+    IK: Iterator<Item = K>,
+    K: Ord + Clone,
 {
     /// Extends the entry, creating it if needed
     pub fn and_extend(self, default: impl IntoIterator<Item = T>) -> Entry<'a, K, T> {
-        match self.or_create() {
-            Entry::Existing(e) => {
-                e.node.leaves.extend(default.into_iter());
-                Entry::Existing(e)
-            }
-            Entry::Created(e) => {
-                e.node.leaves.extend(default.into_iter());
-                Entry::Created(e)
-            }
-        }
+        let mut entry = self.or_create();
+        entry.update_leaves(|leaves| leaves.extend(default));
+        entry
     }
 
     /// Inserts into the entry, creating it if needed
     pub fn and_insert(self, default: T) -> Entry<'a, K, T> {
-        match self.or_create() {
-            Entry::Existing(e) => {
-                e.node.leaves.push(default);
-                Entry::Existing(e)
-            }
-            Entry::Created(e) => {
-                e.node.leaves.push(default);
-                Entry::Created(e)
-            }
-        }
+        let mut entry = self.or_create();
+        entry.push_value(default);
+        entry
     }
 
     /// Finds the entry, and if it does not exist, extends with the provided value.
     pub fn or_extend(self, default: impl IntoIterator<Item = T>) -> Entry<'a, K, T> {
-        match self.or_create() {
-            entry @ Entry::Existing(_) => entry,
-            Entry::Created(e) => {
-                e.node.leaves.extend(default.into_iter());
-                Entry::Created(e)
-            }
+        let mut entry = self.or_create();
+        if let Entry::Created(_) = entry {
+            entry.update_leaves(|leaves| leaves.extend(default));
         }
+        entry
     }
 
     /// Finds the entry, and if it does not exist, inserts the value.
     pub fn or_insert(self, default: T) -> Entry<'a, K, T> {
-        match self.or_create() {
-            entry @ Entry::Existing(_) => entry,
-            Entry::Created(e) => {
-                e.node.leaves.push(default);
-                Entry::Created(e)
-            }
+        let mut entry = self.or_create();
+        if let Entry::Created(_) = entry {
+            entry.push_value(default);
         }
+        entry
     }
 
     /// Finds the entry, and if it does not exist, creates it.
     pub fn or_create(self) -> Entry<'a, K, T> {
         let mut node = self.node;
+        let mut path = self.path;
+        let sets = self.sets;
+        let pending = self.pending;
+        let key_counts = self.key_counts;
         let mut created = false;
 
         for key in self.keys {
-            node = match node.children.binary_search_by(|(k, _)| k.cmp(&key)) {
-                Ok(idx) => &mut (node.children[idx].1),
+            let idx = match node.search(&key) {
+                Ok(idx) => idx,
                 Err(idx) => {
                     created = true;
-                    node.children.insert(idx, (key, Node::new()));
-                    &mut (node.children[idx].1)
+                    node.insert_child(idx, key, Node::new());
+                    idx
                 }
-            }
+            };
+            let Node { keys, children, .. } = node;
+            pending.push(idx);
+            path.push(&keys[idx]);
+            node = &mut children[idx];
         }
 
         if created {
-            return Entry::Created(CreatedEntry { node });
+            return Entry::Created(CreatedEntry {
+                node,
+                path,
+                sets,
+                pending,
+                key_counts,
+            });
         }
-        Entry::Existing(ExistingEntry { node })
+        Entry::Existing(ExistingEntry {
+            node,
+            path,
+            sets,
+            pending,
+            key_counts,
+        })
     }
 
     /// Finds the entry, but does not create one. This method short circuits on the first missing
     /// key.
     pub fn find(self) -> Option<ExistingEntry<'a, K, T>> {
         let mut node = self.node;
+        let mut path = self.path;
+        let pending = self.pending;
 
         for key in self.keys {
-            node = match node.children.binary_search_by(|(k, _)| k.cmp(&key)) {
-                Ok(idx) => &mut (node.children[idx].1),
-                Err(_) => return None,
-            }
+            let idx = node.search(&key).ok()?;
+            let Node { keys, children, .. } = node;
+            pending.push(idx);
+            path.push(&keys[idx]);
+            node = &mut children[idx];
         }
-        Some(ExistingEntry { node })
+        Some(ExistingEntry {
+            node,
+            path,
+            sets: self.sets,
+            pending,
+            key_counts: self.key_counts,
+        })
     }
 
     /// Returns all associated items of an entry.
-    pub fn items(self) -> Option<&'a Vec<T>> {
-        self.find().map(|node| &node.node.leaves)
+    pub fn items(self) -> Option<&'a [T]> {
+        self.find().map(|node| node.node.leaves.as_slice())
     }
 
-    /// Mutably returns all associated items of an entry.
-    pub fn items_mut(self) -> Option<&'a mut Vec<T>> {
-        self.find().map(|node| &mut node.node.leaves)
+    /// Mutably returns all associated items of an entry. Items are added and removed through the
+    /// [entry](Entry) instead.
+    pub fn items_mut(self) -> Option<&'a mut [T]> {
+        self.find().map(|node| node.node.leaves.as_mut_slice())
     }
 }
 
 impl<'a, K, T> Entry<'a, K, T>
 where
-    K: Ord,
+    K: Ord + Clone,
 {
     fn node(&self) -> &Node<K, T> {
         match self {
@@ -178,18 +271,211 @@ where
         }
     }
 
+    /// Applies `f` to the items of the entry, keeping the number of sets and keys of the trie and
+    /// the counts of the node and its ancestors up to date.
+    pub(crate) fn update_leaves<R>(&mut self, f: impl FnOnce(&mut Vec<T>) -> R) -> R {
+        let (node, path, sets, pending, keys) = self.parts_mut();
+        let len = path.len();
+        let before = SubtreeCounts::of(node.leaves.len());
+        let result = sets.update(len, &mut node.leaves, f);
+        let diff = before.diff(SubtreeCounts::of(node.leaves.len()));
+        let counts = node.counts;
+        node.counts.add(diff);
+        if let Some(key) = path.last() {
+            keys.update(key, counts, node.counts);
+        }
+        pending.add_above(len, diff);
+        if !node.leaves.is_empty() {
+            pending.lengthen(len);
+        }
+        result
+    }
+
+    /// The node of the entry, its key path, the set and key counts of the trie and the pending
+    /// changes to the counts of its ancestors.
+    fn parts_mut(
+        &mut self,
+    ) -> (
+        &mut Node<K, T>,
+        &[&'a K],
+        &mut SetCounts,
+        &mut PendingCounts,
+        &mut KeyCounts<K>,
+    ) {
+        match self {
+            Entry::Existing(e) => (e.node, &e.path, e.sets, e.pending, e.key_counts),
+            Entry::Created(e) => (e.node, &e.path, e.sets, e.pending, e.key_counts),
+        }
+    }
+
+    pub(crate) fn into_parts(
+        self,
+    ) -> (
+        &'a mut Node<K, T>,
+        Vec<&'a K>,
+        &'a mut SetCounts,
+        &'a mut PendingCounts,
+        &'a mut KeyCounts<K>,
+    ) {
+        match self {
+            Entry::Existing(e) => (e.node, e.path, e.sets, e.pending, e.key_counts),
+            Entry::Created(e) => (e.node, e.path, e.sets, e.pending, e.key_counts),
+        }
+    }
+
+    /// The set represented by the entry.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// let entry = trie.entry(vec![1, 2]).or_insert("foo");
+    /// assert_eq!(entry.entry(vec![3]).or_create().path(), vec![&1, &2, &3]);
+    /// ```
+    #[must_use]
+    pub fn path(&self) -> Vec<&K> {
+        match self {
+            Entry::Existing(e) => e.path.clone(),
+            Entry::Created(e) => e.path.clone(),
+        }
+    }
+
     /// Returns all associated items of an entry.
     #[must_use]
-    pub fn items(&self) -> &Vec<T> {
+    pub fn items(&self) -> &[T] {
         &self.node().leaves
     }
 
-    /// Mutably returns all associated items of an entry.
+    /// Mutably returns all associated items of an entry. Use [`push_value`](Self::push_value),
+    /// [`retain_values`](Self::retain_values) and [`drain_values`](Self::drain_values) to add or
+    /// remove items.
     #[must_use]
-    pub fn items_mut(&mut self) -> &mut Vec<T> {
+    pub fn items_mut(&mut self) -> &mut [T] {
         &mut self.node_mut().leaves
     }
 
+    /// The number of values stored in the set of the entry and in the sets below it. Every node
+    /// keeps this count, so it takes constant time, as does
+    /// [descendant set count](Self::descendant_set_count).
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(vec![1], "foo");
+    /// trie.insert(vec![1, 2], "bar");
+    /// trie.insert(vec![1, 2, 3], "baz");
+    /// trie.insert(vec![1, 2, 3], "qux");
+    ///
+    /// let entry = trie.entry(vec![1, 2]).or_create();
+    /// assert_eq!(entry.value_count(), 3);
+    /// assert_eq!(entry.descendant_set_count(), 1);
+    /// assert_eq!(entry.subtree_depth(), 1);
+    /// ```
+    #[must_use]
+    pub fn value_count(&self) -> usize {
+        self.node().counts.values
+    }
+
+    /// The number of stored sets below the entry, excluding the set of the entry itself.
+    #[must_use]
+    pub fn descendant_set_count(&self) -> usize {
+        let node = self.node();
+        node.counts.sets - usize::from(!node.leaves.is_empty())
+    }
+
+    /// The number of keys the longest stored set below the entry has in addition to the keys of
+    /// the entry, or 0 if no sets are stored below it.
+    ///
+    /// # Performance
+    ///
+    /// Unlike the counts, the nodes only keep an upper bound of the depth, as lowering it after
+    /// removing the deepest set would require visiting the siblings of every node on its path.
+    /// The exact depth takes time linear in the number of nodes below the entry.
+    #[must_use]
+    pub fn subtree_depth(&self) -> usize {
+        self.node().count_sets().max_len()
+    }
+
+    /// Adds an item to the entry.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// let mut entry = trie.entry(vec![1, 2]).or_create();
+    /// entry.push_value("foo");
+    /// entry.push_value("bar");
+    /// assert_eq!(entry.items(), &["foo", "bar"]);
+    /// ```
+    pub fn push_value(&mut self, item: T) {
+        self.update_leaves(|leaves| leaves.push(item));
+    }
+
+    /// Retains only the items for which `f` returns `true`, preserving their order.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// let mut entry = trie.entry(vec![1, 2]).and_extend(vec![1, 2, 3, 4]);
+    /// entry.retain_values(|i| i % 2 == 0);
+    /// assert_eq!(entry.items(), &[2, 4]);
+    /// ```
+    pub fn retain_values(&mut self, f: impl FnMut(&T) -> bool) {
+        self.update_leaves(|leaves| leaves.retain(f));
+    }
+
+    /// Removes all items from the entry, returning them in order. The set itself stays in the
+    /// trie.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// let mut entry = trie.entry(vec![1, 2]).and_extend(vec!["foo", "bar"]);
+    /// assert_eq!(entry.drain_values().collect::<Vec<_>>(), vec!["foo", "bar"]);
+    /// assert!(entry.items().is_empty());
+    /// ```
+    pub fn drain_values(&mut self) -> impl Iterator<Item = T> + '_ {
+        let (node, path, sets, pending, keys) = self.parts_mut();
+        let len = path.len();
+        if !node.leaves.is_empty() {
+            sets.remove(len);
+        }
+        let diff = SubtreeCounts::of(node.leaves.len()).negate();
+        let counts = node.counts;
+        node.counts.add(diff);
+        if let Some(key) = path.last() {
+            keys.update(key, counts, node.counts);
+        }
+        pending.add_above(len, diff);
+        node.leaves.drain(..)
+    }
+
+    /// Converts the entry into its items, which live as long as the borrow of the trie.
+    #[must_use]
+    pub fn into_items(self) -> &'a [T] {
+        &self.into_parts().0.leaves
+    }
+
+    /// Converts the entry into its items, which can be mutated for as long as the trie is
+    /// borrowed. Unlike [items mut](Self::items_mut), the items can be returned from a function
+    /// which creates the entry. Like it, they are a slice, as items are added and removed through
+    /// the entry.
+    ///
+    /// ```rust
+    /// fn counters<'a>(trie: &'a mut set_trie::SetTrie<u32, u32>, keys: &[u32]) -> &'a mut [u32] {
+    ///     trie.entry(keys.iter().copied()).or_insert(0).into_items_mut()
+    /// }
+    ///
+    /// let mut trie = set_trie::SetTrie::new();
+    /// counters(&mut trie, &[1, 2])[0] += 1;
+    /// counters(&mut trie, &[1, 2])[0] += 1;
+    /// assert_eq!(trie.entry(vec![1, 2]).items(), Some(&[2][..]));
+    /// ```
+    #[must_use]
+    pub fn into_items_mut(self) -> &'a mut [T] {
+        &mut self.into_parts().0.leaves
+    }
+
+    /// Converts the entry into a reference to the most recently added item, which lives as long
+    /// as the borrow of the trie.
+    #[must_use]
+    pub fn into_last_mut(self) -> Option<&'a mut T> {
+        self.into_parts().0.leaves.last_mut()
+    }
+
     /// Provides a view into a child of the entry. If you are sequentially inserting longer keys,
     /// reusing the entry is more efficient than starting from the root.
     ///
@@ -209,9 +495,7 @@ where
         self,
         keys: IK,
     ) -> EntryBuilder<'a, K, T, IK::IntoIter> {
-        match self {
-            Entry::Created(e) => EntryBuilder::from_node(e.node, keys.into_iter()),
-            Entry::Existing(e) => EntryBuilder::from_node(e.node, keys.into_iter()),
-        }
+        let (node, path, sets, pending, key_counts) = self.into_parts();
+        EntryBuilder::from_node(node, path, sets, pending, key_counts, keys.into_iter())
     }
 }
@@ -0,0 +1,131 @@
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+
+/// Error returned by [try insert](crate::SetTrie::try_insert) when the set already holds a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OccupiedError<T> {
+    /// The value which was not inserted.
+    pub value: T,
+}
+
+impl<T> Display for OccupiedError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("set already holds a value")
+    }
+}
+
+impl<T: Debug> Error for OccupiedError<T> {}
+
+/// Error returned by [check invariants](crate::SetTrie::check_invariants), describing the first
+/// invariant found to be violated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvariantError<K> {
+    /// The node at `path` has a different number of keys than children.
+    KeyCount {
+        /// The key path of the node.
+        path: Vec<K>,
+        /// The number of keys of the node.
+        keys: usize,
+        /// The number of children of the node.
+        children: usize,
+    },
+    /// The keys of the children of the node at `path` are not strictly increasing.
+    Unsorted {
+        /// The key path of the node.
+        path: Vec<K>,
+    },
+    /// The number of values or sets kept by the node at `path` differs from the number stored in
+    /// it and its descendants, or the height it keeps is below that of its longest set.
+    SubtreeCount {
+        /// The key path of the node.
+        path: Vec<K>,
+    },
+    /// The maintained number of stored sets of length `len` differs from the actual number.
+    SetCount {
+        /// The length of the sets.
+        len: usize,
+        /// The maintained number of sets.
+        counted: usize,
+        /// The number of sets actually stored.
+        actual: usize,
+    },
+    /// The maintained number of nodes of `key` below which values are stored differs from the
+    /// actual number.
+    KeyOccurrences {
+        /// The key of the nodes.
+        key: K,
+        /// The maintained number of nodes.
+        counted: usize,
+        /// The number of nodes actually holding values below them.
+        actual: usize,
+    },
+}
+
+impl<K: Debug> Display for InvariantError<K> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::KeyCount {
+                path,
+                keys,
+                children,
+            } => write!(f, "node {path:?} has {keys} keys but {children} children"),
+            Self::Unsorted { path } => write!(f, "children of node {path:?} are not sorted"),
+            Self::SubtreeCount { path } => {
+                write!(
+                    f,
+                    "node {path:?} miscounts the values, sets or height below it"
+                )
+            }
+            Self::SetCount {
+                len,
+                counted,
+                actual,
+            } => write!(
+                f,
+                "{counted} sets of length {len} are counted, but {actual} are stored"
+            ),
+            Self::KeyOccurrences {
+                key,
+                counted,
+                actual,
+            } => write!(
+                f,
+                "{counted} nodes of key {key:?} with values are counted, but {actual} are stored"
+            ),
+        }
+    }
+}
+
+impl<K: Debug> Error for InvariantError<K> {}
+
+/// Error returned by [to json map](crate::SetTrie::to_json_map) and
+/// [from json map](crate::SetTrie::from_json_map).
+#[cfg(feature = "serde_json")]
+#[derive(Debug)]
+pub enum JsonError {
+    /// A key of the set with the given name could not be parsed, or the name cannot be read back
+    /// as the same set.
+    Key(String),
+    /// The values of a set could not be serialized or deserialized.
+    Value(serde_json::Error),
+}
+
+#[cfg(feature = "serde_json")]
+impl Display for JsonError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Key(set) => write!(f, "invalid key in set {set:?}"),
+            Self::Value(err) => write!(f, "invalid values: {err}"),
+        }
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl Error for JsonError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Key(_) => None,
+            Self::Value(err) => Some(err),
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use crate::stats::SubtreeCounts;
+use crate::{Node, SetTrie};
+use std::collections::TryReserveError;
+
+impl<K, T> SetTrie<K, T>
+where
+    K: Ord,
+{
+    /// Insert the item in the given set like [insert](Self::insert), but return an error instead
+    /// of aborting when memory cannot be allocated. Every allocation is made with `try_reserve`
+    /// before the trie is modified.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryReserveError`] if a node could not grow, in which case the item is dropped.
+    /// The nodes created before the failure are left without values, and are removed by
+    /// [vacuum](Self::vacuum).
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.try_insert_alloc(vec![1, 2], "foo").unwrap();
+    ///
+    /// assert_eq!(trie.supersets(&[1]).collect::<Vec<_>>(), vec![&"foo"]);
+    /// ```
+    pub fn try_insert_alloc(
+        &mut self,
+        keys: impl IntoIterator<Item = K>,
+        item: T,
+    ) -> Result<(), TryReserveError>
+    where
+        K: Clone,
+    {
+        self.try_insert_many_alloc(keys, Some(item))
+    }
+
+    /// Inserts multiple items in the given set like [insert many](Self::insert_many), but return
+    /// an error instead of aborting when memory cannot be allocated.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryReserveError`] if a node could not grow. The items inserted before the
+    /// failure are kept, the others are dropped.
+    ///
+    /// The keys of a set stored for the first time are still counted for
+    /// [contains key](Self::contains_key) in a map which aborts when it cannot grow.
+    pub fn try_insert_many_alloc(
+        &mut self,
+        keys: impl IntoIterator<Item = K>,
+        items: impl IntoIterator<Item = T>,
+    ) -> Result<(), TryReserveError>
+    where
+        K: Clone,
+    {
+        self.flush_counts();
+        let mut node = &mut self.root;
+        // the counts of the nodes above the set, which gain its new values, and the keys of the
+        // nodes on its path.
+        let mut ancestors: Vec<&mut SubtreeCounts> = vec![];
+        let mut path: Vec<&K> = vec![];
+        for key in keys {
+            let idx = match node.search(&key) {
+                Ok(idx) => idx,
+                Err(idx) => {
+                    node.keys.try_reserve(1)?;
+                    node.children.try_reserve(1)?;
+                    node.insert_child(idx, key, Node::new());
+                    idx
+                }
+            };
+            ancestors.try_reserve(1)?;
+            path.try_reserve(1)?;
+            let Node {
+                keys,
+                children,
+                counts,
+                ..
+            } = node;
+            ancestors.push(counts);
+            path.push(&keys[idx]);
+            node = &mut children[idx];
+        }
+
+        let len = ancestors.len();
+        self.sets.try_reserve(len)?;
+        let before = SubtreeCounts::of(node.leaves.len());
+        let result = self.sets.update(len, &mut node.leaves, |leaves| {
+            for item in items {
+                leaves.try_reserve(1)?;
+                leaves.push(item);
+            }
+            Ok(())
+        });
+        let diff = before.diff(SubtreeCounts::of(node.leaves.len()));
+        let counts = node.counts;
+        node.counts.add(diff);
+        if let Some(key) = path.last() {
+            self.keys.update(key, counts, node.counts);
+        }
+        let set = !node.leaves.is_empty();
+        for (depth, counts) in ancestors.into_iter().enumerate() {
+            let before = *counts;
+            counts.add(diff);
+            if set {
+                counts.raise(len - depth);
+            }
+            if let Some(key) = depth.checked_sub(1).map(|parent| path[parent]) {
+                self.keys.update(key, before, *counts);
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SetTrie;
+
+    #[test]
+    fn try_insert_alloc() {
+        let mut trie = SetTrie::new();
+        trie.try_insert_alloc(vec![1, 2], 'a').unwrap();
+        trie.try_insert_many_alloc(vec![1, 2], vec!['b', 'c'])
+            .unwrap();
+        trie.try_insert_many_alloc(vec![1], vec![]).unwrap();
+        trie.try_insert_alloc(vec![], 'd').unwrap();
+
+        assert_eq!(trie.values().collect::<String>(), "dabc");
+        assert_eq!(trie.size_histogram(), &[1, 0, 1]);
+        assert_eq!(trie.check_invariants(), Ok(()));
+    }
+}
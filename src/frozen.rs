@@ -0,0 +1,281 @@
+use crate::SetTrie;
+use std::collections::VecDeque;
+use std::ops::Range;
+
+/// An immutable [`SetTrie`], created by [freeze](SetTrie::freeze).
+///
+/// The nodes are numbered in BFS order, so that the children of every node are numbered
+/// consecutively. The keys, the offsets of the children and the offsets of the values of all
+/// nodes are then each stored in a single array, instead of three vectors per node. This saves
+/// the allocations and the vector headers of every node, and keeps the children which a query
+/// scans next to each other in memory.
+///
+/// ```rust
+/// let mut trie = set_trie::SetTrie::new();
+/// trie.insert(&[1, 2], "foo");
+/// trie.insert(&[1, 3], "bar");
+/// trie.insert(&[2], "baz");
+///
+/// let frozen = trie.freeze();
+/// assert_eq!(frozen.subsets(&[&1, &2]).collect::<Vec<_>>(), vec![&"foo", &"baz"]);
+/// assert_eq!(frozen.supersets(&[&1]).collect::<Vec<_>>(), vec![&"foo", &"bar"]);
+/// assert_eq!(frozen.get(&[&2]), Some(&["baz"][..]));
+/// ```
+#[derive(Debug, Clone)]
+pub struct FrozenSetTrie<K, T> {
+    // the key of node `n` is at `n - 1`, as the root has no key.
+    keys: Vec<K>,
+    // the children of node `n` are the nodes `children[n]..children[n + 1]`.
+    children: Vec<usize>,
+    // the values of node `n` are `leaves[values[n]..values[n + 1]]`.
+    values: Vec<usize>,
+    leaves: Vec<T>,
+}
+
+impl<K, T> SetTrie<K, T>
+where
+    K: Ord,
+{
+    /// Compiles the trie into a [`FrozenSetTrie`], which answers the same queries using less
+    /// memory, but can no longer be modified. Nodes without values below them are dropped.
+    #[must_use]
+    pub fn freeze(mut self) -> FrozenSetTrie<K, T>
+    where
+        K: Clone,
+    {
+        self.vacuum();
+
+        let mut frozen = FrozenSetTrie {
+            keys: vec![],
+            children: vec![1],
+            values: vec![0],
+            leaves: vec![],
+        };
+        let mut queue = VecDeque::new();
+        queue.push_back(self.root);
+        let mut next = 1;
+        while let Some(mut node) = queue.pop_front() {
+            frozen.leaves.append(&mut node.leaves);
+            frozen.values.push(frozen.leaves.len());
+            for (key, child) in node.take_children() {
+                frozen.keys.push(key);
+                queue.push_back(child);
+                next += 1;
+            }
+            frozen.children.push(next);
+        }
+        frozen
+    }
+}
+
+impl<K, T> FrozenSetTrie<K, T> {
+    /// The keys, the offsets of the children, the offsets of the values and the values.
+    #[cfg(feature = "succinct")]
+    pub(crate) fn into_parts(self) -> (Vec<K>, Vec<usize>, Vec<usize>, Vec<T>) {
+        (self.keys, self.children, self.values, self.leaves)
+    }
+
+    /// The number of values in the trie.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Whether the trie holds no values.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Iterates over all values in the trie, in BFS order of their sets.
+    pub fn values(&self) -> std::slice::Iter<'_, T> {
+        self.leaves.iter()
+    }
+
+    /// The children of `node`.
+    fn child_range(&self, node: usize) -> Range<usize> {
+        self.children[node]..self.children[node + 1]
+    }
+
+    /// The keys of the children of `node`, which are sorted.
+    fn child_keys(&self, node: usize) -> &[K] {
+        let Range { start, end } = self.child_range(node);
+        &self.keys[start - 1..end - 1]
+    }
+
+    /// The values of `node`.
+    fn leaves(&self, node: usize) -> &[T] {
+        &self.leaves[self.values[node]..self.values[node + 1]]
+    }
+}
+
+impl<K, T> FrozenSetTrie<K, T>
+where
+    K: Ord,
+{
+    /// The values of the set `keys`, if it is stored.
+    #[must_use]
+    pub fn get(&self, keys: &[K]) -> Option<&[T]> {
+        let mut node = 0;
+        for key in keys {
+            node = self.children[node] + self.child_keys(node).binary_search(key).ok()?;
+        }
+        Some(self.leaves(node)).filter(|leaves| !leaves.is_empty())
+    }
+
+    /// Iterates over all values whose set is a subset of `keys`. See [`SetTrie::subsets`].
+    #[must_use]
+    pub fn subsets<'a, 'b>(&'a self, keys: &'b [K]) -> FrozenSubsets<'a, 'b, K, T> {
+        FrozenSubsets {
+            trie: self,
+            keys,
+            leaves: [].iter(),
+            stack: vec![(0, 0)],
+        }
+    }
+
+    /// Iterates over all values whose set is a superset of `keys`. See [`SetTrie::supersets`].
+    #[must_use]
+    pub fn supersets<'a, 'b>(&'a self, keys: &'b [K]) -> FrozenSuperSets<'a, 'b, K, T> {
+        FrozenSuperSets {
+            trie: self,
+            keys,
+            leaves: [].iter(),
+            stack: vec![(0, 0)],
+        }
+    }
+}
+
+/// Iterator for [`FrozenSetTrie::subsets`].
+#[derive(Debug, Clone)]
+pub struct FrozenSubsets<'a, 'b, K, T> {
+    trie: &'a FrozenSetTrie<K, T>,
+    keys: &'b [K],
+    leaves: std::slice::Iter<'a, T>,
+    // (node, start of the query keys which may still follow the key path of the node).
+    stack: Vec<(usize, usize)>,
+}
+
+impl<'a, K, T> Iterator for FrozenSubsets<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some(item);
+            }
+
+            let (node, start) = self.stack.pop()?;
+            self.leaves = self.trie.leaves(node).iter();
+
+            let rest = &self.keys[start..];
+            let (Some(from), Some(to)) = (rest.first(), rest.last()) else {
+                continue;
+            };
+            let keys = self.trie.child_keys(node);
+            let lower = keys.partition_point(|key| key < from);
+            let upper = keys.partition_point(|key| key <= to);
+            // pushed in reverse, so that the children are visited in order.
+            for idx in (lower..upper).rev() {
+                if let Ok(found) = rest.binary_search(&keys[idx]) {
+                    let child = self.trie.children[node] + idx;
+                    self.stack.push((child, start + found + 1));
+                }
+            }
+        }
+    }
+}
+
+/// Iterator for [`FrozenSetTrie::supersets`].
+#[derive(Debug, Clone)]
+pub struct FrozenSuperSets<'a, 'b, K, T> {
+    trie: &'a FrozenSetTrie<K, T>,
+    keys: &'b [K],
+    leaves: std::slice::Iter<'a, T>,
+    // (node, number of query keys on the key path of the node).
+    stack: Vec<(usize, usize)>,
+}
+
+impl<'a, K, T> Iterator for FrozenSuperSets<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some(item);
+            }
+
+            let (node, matched) = self.stack.pop()?;
+            let keys = self.trie.child_keys(node);
+            let first = self.trie.children[node];
+            let Some(want) = self.keys.get(matched) else {
+                self.leaves = self.trie.leaves(node).iter();
+                self.stack.extend(
+                    self.trie
+                        .child_range(node)
+                        .rev()
+                        .map(|child| (child, matched)),
+                );
+                continue;
+            };
+
+            // as children are sorted, only those up to the next key of the query may still lead
+            // to a superset.
+            let upper = keys.partition_point(|key| key <= want);
+            for idx in (0..upper).rev() {
+                let matched = matched + usize::from(&keys[idx] == want);
+                self.stack.push((first + idx, matched));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SetTrie;
+
+    #[test]
+    fn freeze() {
+        let mut trie = SetTrie::new();
+        trie.insert(vec![1, 2, 3], 'a');
+        trie.insert(vec![1], 'b');
+        trie.insert(vec![2, 3], 'c');
+        trie.insert(vec![], 'd');
+        trie.insert(vec![1, 3], 'e');
+        trie.insert(vec![1, 2, 3], 'f');
+        trie.insert(vec![4], 'g');
+        trie.remove(&[4]);
+
+        let queries: &[&[i32]] = &[&[], &[1], &[3], &[1, 3], &[1, 2, 3], &[2, 3, 4], &[5]];
+        let expected: Vec<_> = queries
+            .iter()
+            .map(|keys| {
+                (
+                    trie.subsets(keys).copied().collect::<Vec<_>>(),
+                    trie.supersets(keys).copied().collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+
+        let frozen = trie.freeze();
+        assert_eq!(frozen.len(), 6);
+        for (keys, expected) in queries.iter().zip(expected) {
+            let actual = (
+                frozen.subsets(keys).copied().collect::<Vec<_>>(),
+                frozen.supersets(keys).copied().collect::<Vec<_>>(),
+            );
+            assert_eq!(actual, expected, "{keys:?}");
+        }
+
+        assert_eq!(frozen.get(&[1, 2, 3]), Some(&['a', 'f'][..]));
+        assert_eq!(frozen.get(&[]), Some(&['d'][..]));
+        assert_eq!(frozen.get(&[2]), None);
+        assert_eq!(frozen.get(&[4]), None);
+    }
+}
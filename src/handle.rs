@@ -0,0 +1,217 @@
+use crate::arena::{Arena, Index};
+use crate::subset::Subset;
+use crate::superset::SuperSet;
+use crate::values::Values;
+use crate::SetTrie;
+
+/// Opaque handle to a single value in a [`HandleSetTrie`], which removes the value in `O(1)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ValueHandle(Index);
+
+/// A [`SetTrie`] which hands out a [`ValueHandle`] for every inserted value, through which the
+/// value can later be removed without walking its key path, or even knowing its set.
+///
+/// The values are kept in an arena outside of the trie, and the trie itself only stores their
+/// handles. Removing a value by its handle only frees its slot in the arena; the handle stays
+/// behind in the trie and is skipped by queries, until [vacuum](Self::vacuum) drops it. Freed
+/// slots are reused by later insertions, but every slot counts the removals of its values, so
+/// stale handles resolve to `None` rather than to a newer value.
+///
+/// ```rust
+/// use set_trie::HandleSetTrie;
+///
+/// let mut trie = HandleSetTrie::new();
+/// let foo = trie.insert(vec![1, 2], "foo");
+/// trie.insert(vec![1, 2], "bar");
+///
+/// assert_eq!(trie.remove(foo), Some("foo"));
+/// assert_eq!(trie.remove(foo), None);
+///
+/// trie.insert(vec![1, 2], "baz");
+/// assert_eq!(trie.get(foo), None);
+/// assert_eq!(trie.subsets(&[1, 2, 3]).collect::<Vec<_>>(), vec![&"bar", &"baz"]);
+/// ```
+#[derive(Debug)]
+pub struct HandleSetTrie<K, T> {
+    trie: SetTrie<K, ValueHandle>,
+    values: Arena<T>,
+}
+
+impl<K, T> Default for HandleSetTrie<K, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, T> HandleSetTrie<K, T> {
+    /// Create a new, empty `HandleSetTrie`.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            trie: SetTrie::new(),
+            values: Arena::new(),
+        }
+    }
+
+    /// The number of values in the trie.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether the trie holds no values.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.values.len() == 0
+    }
+
+    /// The value referenced by `handle`, or `None` if it has been removed.
+    #[must_use]
+    pub fn get(&self, handle: ValueHandle) -> Option<&T> {
+        self.values.get(handle.0)
+    }
+
+    /// Mutably returns the value referenced by `handle`, or `None` if it has been removed.
+    #[must_use]
+    pub fn get_mut(&mut self, handle: ValueHandle) -> Option<&mut T> {
+        self.values.get_mut(handle.0)
+    }
+
+    /// Removes the value referenced by `handle` in `O(1)`, returning it if it was not removed
+    /// before.
+    pub fn remove(&mut self, handle: ValueHandle) -> Option<T> {
+        self.values.remove(handle.0)
+    }
+
+    /// Drops the handles of removed values from the trie, together with the nodes which are left
+    /// without values and children. Returns the number of removed nodes.
+    pub fn vacuum(&mut self) -> usize
+    where
+        K: Ord + Clone,
+    {
+        self.trie.flush_counts();
+        let values = &self.values;
+        self.trie.root.walk_mut(
+            &mut self.trie.sets,
+            &mut self.trie.keys,
+            (),
+            |(), _| Some(()),
+            |_, (), handles| handles.retain(|handle| values.get(handle.0).is_some()),
+        )
+    }
+}
+
+impl<K, T> HandleSetTrie<K, T>
+where
+    K: Ord,
+{
+    /// Inserts the item in the given set, returning the handle of the item. Will create the set if
+    /// needed.
+    pub fn insert(&mut self, keys: impl IntoIterator<Item = K>, item: T) -> ValueHandle
+    where
+        K: Clone,
+    {
+        let handle = ValueHandle(self.values.insert(item));
+        self.trie.insert(keys, handle);
+        handle
+    }
+
+    /// Removes the set `keys`, returning its values if it holds any.
+    pub fn remove_set(&mut self, keys: &[K]) -> Option<Vec<T>>
+    where
+        K: Clone,
+    {
+        let items: Vec<T> = self
+            .trie
+            .remove(keys)?
+            .into_iter()
+            .filter_map(|handle| self.values.remove(handle.0))
+            .collect();
+        Some(items).filter(|items| !items.is_empty())
+    }
+
+    /// Iterates over all values using DFS. See [`SetTrie::values`].
+    #[must_use]
+    pub const fn values(&self) -> Live<'_, T, Values<'_, K, ValueHandle>> {
+        Live::new(&self.values, self.trie.values())
+    }
+
+    /// Iterates over all values stored under subsets of `keys`. See [`SetTrie::subsets`].
+    #[must_use]
+    pub fn subsets<'a, 'b>(&'a self, keys: &'b [K]) -> Live<'a, T, Subset<'a, 'b, K, ValueHandle>> {
+        Live::new(&self.values, self.trie.subsets(keys))
+    }
+
+    /// Iterates over all values stored under supersets of `keys`. See [`SetTrie::supersets`].
+    #[must_use]
+    pub fn supersets<'a, 'b>(
+        &'a self,
+        keys: &'b [K],
+    ) -> Live<'a, T, SuperSet<'a, 'b, K, ValueHandle>> {
+        Live::new(&self.values, self.trie.supersets(keys))
+    }
+}
+
+/// Iterator resolving the handles yielded by a query on a [`HandleSetTrie`] to their values,
+/// skipping removed values.
+#[derive(Debug, Clone)]
+pub struct Live<'a, T, I> {
+    values: &'a Arena<T>,
+    handles: I,
+}
+
+impl<'a, T, I> Live<'a, T, I> {
+    const fn new(values: &'a Arena<T>, handles: I) -> Self {
+        Self { values, handles }
+    }
+}
+
+impl<'a, T, I> Iterator for Live<'a, T, I>
+where
+    I: Iterator<Item = &'a ValueHandle>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let values = self.values;
+        self.handles
+            .by_ref()
+            .find_map(|handle| values.get(handle.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::HandleSetTrie;
+
+    #[test]
+    fn handles() {
+        let mut trie = HandleSetTrie::new();
+        let a = trie.insert(vec![1, 2], 'a');
+        let b = trie.insert(vec![1], 'b');
+        let c = trie.insert(vec![1, 2], 'c');
+        trie.insert(vec![2, 3], 'd');
+        assert_eq!(trie.len(), 4);
+
+        assert_eq!(trie.remove(a), Some('a'));
+        assert_eq!(trie.remove(a), None);
+
+        // the slot of `a` is reused while its stale handle is still in the trie.
+        let f = trie.insert(vec![2], 'f');
+        assert_ne!(f, a);
+        assert_eq!(trie.get(a), None);
+        *trie.get_mut(c).unwrap() = 'e';
+        assert_eq!(trie.supersets(&[1]).collect::<Vec<_>>(), vec![&'b', &'e']);
+
+        assert_eq!(trie.remove(b), Some('b'));
+        assert_eq!(trie.remove_set(&[1]), None);
+        assert_eq!(trie.remove_set(&[2, 3]), Some(vec!['d']));
+        assert_eq!(trie.len(), 2);
+
+        // the node of [2, 3] is dropped, [1] is kept as the parent of [1, 2].
+        assert_eq!(trie.vacuum(), 1);
+        assert_eq!(trie.values().collect::<Vec<_>>(), vec![&'e', &'f']);
+        assert_eq!(trie.get(f), Some(&'f'));
+        assert_eq!(trie.get(c), Some(&'e'));
+    }
+}
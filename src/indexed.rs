@@ -0,0 +1,204 @@
+use crate::arena::{Arena, Index};
+use crate::subset::Subset;
+use crate::superset::SuperSet;
+use crate::values::Values;
+use crate::SetTrie;
+
+/// Opaque handle to the values of a set in an [`IndexedSetTrie`], giving `O(1)` access to them
+/// without walking the key path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EntryId(Index);
+
+/// A [`SetTrie`] which hands out [`EntryId`] handles to the values of its sets.
+///
+/// The values are kept in an arena outside of the trie, and the trie itself only stores the
+/// handle of every set. A handle stays valid until its set is [removed](Self::remove). The slot of
+/// a removed set is reused by later insertions, but every slot counts the removals of its sets, so
+/// stale handles resolve to `None` rather than to the values of a newer set.
+///
+/// ```rust
+/// use set_trie::IndexedSetTrie;
+///
+/// let mut trie = IndexedSetTrie::new();
+/// let id = trie.insert(vec![1, 2], "foo");
+/// assert_eq!(trie.insert(vec![1, 2], "bar"), id);
+///
+/// trie.get_mut(id).unwrap()[1] = "baz";
+/// assert_eq!(trie.get(id), Some(&["foo", "baz"][..]));
+/// assert_eq!(trie.subsets(&[1, 2, 3]).count(), 2);
+/// ```
+#[derive(Debug)]
+pub struct IndexedSetTrie<K, T> {
+    trie: SetTrie<K, EntryId>,
+    values: Arena<Vec<T>>,
+}
+
+impl<K, T> Default for IndexedSetTrie<K, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, T> IndexedSetTrie<K, T> {
+    /// Create a new, empty `IndexedSetTrie`.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            trie: SetTrie::new(),
+            values: Arena::new(),
+        }
+    }
+
+    /// The values of the set referenced by `id`, or `None` if the set has been removed.
+    #[must_use]
+    pub fn get(&self, id: EntryId) -> Option<&[T]> {
+        self.values.get(id.0).map(Vec::as_slice)
+    }
+
+    /// Mutably returns the values of the set referenced by `id`, or `None` if the set has been
+    /// removed. Values are added and removed through the trie, so that no stored set is left
+    /// without values.
+    #[must_use]
+    pub fn get_mut(&mut self, id: EntryId) -> Option<&mut [T]> {
+        self.values.get_mut(id.0).map(Vec::as_mut_slice)
+    }
+
+    /// Iterates over the values of the sets referenced by `ids`, skipping removed sets.
+    pub(crate) fn resolve<I>(&self, ids: I) -> Resolve<'_, T, I> {
+        Resolve::new(&self.values, ids)
+    }
+}
+
+impl<K, T> IndexedSetTrie<K, T>
+where
+    K: Ord,
+{
+    /// The handle of the set `keys`, if it is stored.
+    #[must_use]
+    pub fn id(&self, keys: &[K]) -> Option<EntryId> {
+        self.trie.root.find(keys)?.leaves.first().copied()
+    }
+
+    /// Inserts the item in the given set, returning the handle of the set. Will create the set if
+    /// needed.
+    pub fn insert(&mut self, keys: impl IntoIterator<Item = K>, item: T) -> EntryId
+    where
+        K: Clone,
+    {
+        let values = &mut self.values;
+        let mut entry = self.trie.entry(keys).or_create();
+        let id = entry.items().first().copied().unwrap_or_else(|| {
+            let id = EntryId(values.insert(vec![]));
+            entry.push_value(id);
+            id
+        });
+
+        // the slot of a stored set is always live.
+        if let Some(values) = values.get_mut(id.0) {
+            values.push(item);
+        }
+        id
+    }
+
+    /// Removes the set `keys`, returning its values if it was stored. Its handle no longer
+    /// resolves afterwards.
+    pub fn remove(&mut self, keys: &[K]) -> Option<Vec<T>>
+    where
+        K: Clone,
+    {
+        let id = self.trie.remove(keys)?.pop()?;
+        self.values.remove(id.0)
+    }
+
+    /// Iterates over all values using DFS. See [`SetTrie::values`].
+    #[must_use]
+    pub fn values(&self) -> Resolve<'_, T, Values<'_, K, EntryId>> {
+        Resolve::new(&self.values, self.trie.values())
+    }
+
+    /// Iterates over all values stored under subsets of `keys`. See [`SetTrie::subsets`].
+    #[must_use]
+    pub fn subsets<'a, 'b>(&'a self, keys: &'b [K]) -> Resolve<'a, T, Subset<'a, 'b, K, EntryId>> {
+        Resolve::new(&self.values, self.trie.subsets(keys))
+    }
+
+    /// Iterates over all values stored under supersets of `keys`. See [`SetTrie::supersets`].
+    #[must_use]
+    pub fn supersets<'a, 'b>(
+        &'a self,
+        keys: &'b [K],
+    ) -> Resolve<'a, T, SuperSet<'a, 'b, K, EntryId>> {
+        Resolve::new(&self.values, self.trie.supersets(keys))
+    }
+}
+
+/// Iterator resolving the handles yielded by a query on an [`IndexedSetTrie`] to their values.
+#[derive(Debug, Clone)]
+pub struct Resolve<'a, T, I> {
+    values: &'a Arena<Vec<T>>,
+    ids: I,
+    leaves: std::slice::Iter<'a, T>,
+}
+
+impl<'a, T, I> Resolve<'a, T, I> {
+    pub(crate) fn new(values: &'a Arena<Vec<T>>, ids: I) -> Self {
+        Self {
+            values,
+            ids,
+            leaves: [].iter(),
+        }
+    }
+}
+
+impl<'a, T, I> Iterator for Resolve<'a, T, I>
+where
+    I: Iterator<Item = &'a EntryId>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some(item);
+            }
+            let id = self.ids.next()?;
+            self.leaves = self
+                .values
+                .get(id.0)
+                .map(Vec::as_slice)
+                .unwrap_or_default()
+                .iter();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::IndexedSetTrie;
+
+    #[test]
+    fn indexed() {
+        let mut trie = IndexedSetTrie::new();
+        let a = trie.insert(vec![1, 2], 'a');
+        let b = trie.insert(vec![1], 'b');
+        assert_eq!(trie.insert(vec![1, 2], 'c'), a);
+        assert_eq!(trie.id(&[1]), Some(b));
+        assert_eq!(trie.id(&[2]), None);
+
+        assert_eq!(
+            trie.supersets(&[1]).collect::<Vec<_>>(),
+            vec![&'b', &'a', &'c']
+        );
+        assert_eq!(trie.get(a), Some(&['a', 'c'][..]));
+
+        assert_eq!(trie.remove(&[1, 2]), Some(vec!['a', 'c']));
+        assert_eq!(trie.get(a), None);
+        assert_eq!(trie.values().collect::<Vec<_>>(), vec![&'b']);
+
+        // a removed set gets a fresh handle, even though its slot is reused.
+        let d = trie.insert(vec![1, 2], 'd');
+        assert_ne!(d, a);
+        assert_eq!(trie.get(a), None);
+        assert_eq!(trie.get(d), Some(&['d'][..]));
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{Dictionary, EncodedSetTrie, Entry, EntryBuilder, SetTrie};
+use std::ops::Deref;
+
+/// Maps strings to dense `u32` symbols, handed out in order of first appearance, and back. A
+/// [`Dictionary`] of strings, which interns borrowed strings without allocating for those it
+/// already knows.
+///
+/// ```rust
+/// use set_trie::Interner;
+///
+/// let mut interner = Interner::new();
+/// let rust = interner.intern("rust");
+/// assert_eq!(interner.intern("rust"), rust);
+/// assert_eq!(interner.get("trie"), None);
+/// assert_eq!(interner.resolve(rust), Some("rust"));
+/// ```
+pub type Interner = Dictionary<Box<str>>;
+
+impl Dictionary<Box<str>> {
+    /// The symbol of `key`, interning it if it is new.
+    ///
+    /// # Panics
+    ///
+    /// If more than `u32::MAX` strings are interned.
+    pub fn intern(&mut self, key: &str) -> u32 {
+        self.get(key).unwrap_or_else(|| self.encode(key.into()))
+    }
+
+    /// The string of `symbol`, if it has been handed out.
+    #[must_use]
+    pub fn resolve(&self, symbol: u32) -> Option<&str> {
+        self.decode(symbol).map(AsRef::as_ref)
+    }
+}
+
+/// A [`SetTrie`] with string keys, which are [interned](Interner) to `u32` symbols before
+/// insertion so that queries compare integers rather than strings.
+///
+/// This is an [`EncodedSetTrie`] of strings, which takes its keys as borrowed strings. Queries are available through `Deref` on the symbols returned by [query](Self::query), and
+/// [resolve](Self::resolve) maps symbols in the output back to strings.
+///
+/// ```rust
+/// use set_trie::InternedSetTrie;
+///
+/// let mut trie = InternedSetTrie::new();
+/// trie.insert(["trie", "rust"], 1);
+/// trie.insert(["rust"], 2);
+///
+/// let query = trie.query(["rust", "set", "trie"]);
+/// assert_eq!(trie.subsets(&query).collect::<Vec<_>>(), vec![&1, &2]);
+///
+/// let (first, _) = trie.first().unwrap();
+/// assert_eq!(trie.resolve(first), vec!["trie", "rust"]);
+/// ```
+#[derive(Debug)]
+pub struct InternedSetTrie<T> {
+    trie: EncodedSetTrie<Box<str>, T>,
+}
+
+impl<T> Default for InternedSetTrie<T> {
+    fn default() -> Self {
+        Self {
+            trie: EncodedSetTrie::new(),
+        }
+    }
+}
+
+impl<T> InternedSetTrie<T> {
+    /// Create a new, empty `InternedSetTrie`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The interner mapping the keys of the trie to symbols.
+    #[must_use]
+    pub const fn interner(&self) -> &Interner {
+        self.trie.dictionary()
+    }
+
+    /// The sorted symbols of `keys`, to be passed to the queries of the trie. See
+    /// [`Dictionary::symbols`].
+    pub fn query<'k>(&self, keys: impl IntoIterator<Item = &'k str>) -> Vec<u32> {
+        self.trie.encode(keys)
+    }
+
+    /// Maps the `symbols` of a key path yielded by the trie back to their strings.
+    ///
+    /// # Panics
+    ///
+    /// If a symbol was not handed out by the interner of this trie.
+    pub fn resolve<'a>(&self, symbols: impl IntoIterator<Item = &'a u32>) -> Vec<&str> {
+        self.trie
+            .decode(symbols)
+            .into_iter()
+            .map(AsRef::as_ref)
+            .collect()
+    }
+
+    /// A view into the node of the set `keys`, interning any new keys. See [`SetTrie::entry`].
+    #[must_use]
+    pub fn entry<'k>(
+        &mut self,
+        keys: impl IntoIterator<Item = &'k str>,
+    ) -> EntryBuilder<'_, u32, T, std::vec::IntoIter<u32>> {
+        self.trie.entry_by(keys, Interner::intern)
+    }
+
+    /// Insert the item in the given set, interning any new keys. See [`SetTrie::insert`].
+    pub fn insert<'k>(
+        &mut self,
+        keys: impl IntoIterator<Item = &'k str>,
+        item: T,
+    ) -> Entry<'_, u32, T> {
+        self.entry(keys).and_insert(item)
+    }
+
+    /// Removes the set `keys`, returning its values if it was stored. See [`SetTrie::remove`].
+    pub fn remove<'k>(&mut self, keys: impl IntoIterator<Item = &'k str>) -> Option<Vec<T>> {
+        self.trie.remove(keys)
+    }
+}
+
+impl<T> Deref for InternedSetTrie<T> {
+    type Target = SetTrie<u32, T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.trie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::InternedSetTrie;
+
+    #[test]
+    fn interned_set_trie() {
+        let mut trie = InternedSetTrie::new();
+        trie.insert(["b", "a", "b"], 'x');
+        trie.insert(["c"], 'y');
+        trie.insert(["a", "c"], 'z');
+        assert_eq!(trie.interner().len(), 3);
+
+        assert_eq!(
+            trie.subsets(&trie.query(["a", "b", "d"]))
+                .collect::<Vec<_>>(),
+            vec![&'x']
+        );
+        assert_eq!(
+            trie.supersets(&trie.query(["a"])).collect::<Vec<_>>(),
+            vec![&'x', &'z']
+        );
+        assert_eq!(trie.supersets(&trie.query(["a", "d"])).count(), 0);
+
+        let (last, _) = trie.last().unwrap();
+        assert_eq!(trie.resolve(last), vec!["c"]);
+
+        assert_eq!(trie.remove(["a", "b", "b"]), Some(vec!['x']));
+        assert_eq!(trie.remove(["d"]), None);
+        assert_eq!(trie.values().collect::<Vec<_>>(), vec![&'z', &'y']);
+    }
+}
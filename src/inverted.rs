@@ -0,0 +1,147 @@
+use crate::indexed::Resolve;
+use crate::{EntryId, IndexedSetTrie};
+use std::collections::{btree_set, BTreeMap, BTreeSet};
+use std::ops::Deref;
+
+/// The postings of a key which is not stored.
+static NO_POSTINGS: BTreeSet<EntryId> = BTreeSet::new();
+
+/// An [`IndexedSetTrie`] which also keeps an inverted index from every key to the sets containing
+/// it.
+///
+/// A superset query for a single key explores every branch of which the keys are smaller, which
+/// is most of the trie for keys late in the order. [Sets containing](Self::sets_containing) reads
+/// the postings of the key instead, in time linear in the number of values yielded. Every set is
+/// posted once for each of its keys, so the index takes as many handles as there are keys in all
+/// stored sets.
+///
+/// ```rust
+/// use set_trie::InvertedSetTrie;
+///
+/// let mut employees = InvertedSetTrie::new();
+/// employees.insert(vec!["accounting", "banking"], "Daniels");
+/// employees.insert(vec!["accounting", "banking", "crime"], "Stevens");
+/// employees.insert(vec!["crime"], "Moss");
+///
+/// let crime = employees.sets_containing(&"crime").collect::<Vec<_>>();
+/// assert_eq!(crime, vec![&"Stevens", &"Moss"]);
+/// ```
+#[derive(Debug)]
+pub struct InvertedSetTrie<K, T> {
+    trie: IndexedSetTrie<K, T>,
+    postings: BTreeMap<K, BTreeSet<EntryId>>,
+}
+
+impl<K, T> Default for InvertedSetTrie<K, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, T> InvertedSetTrie<K, T> {
+    /// Create a new, empty `InvertedSetTrie`.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            trie: IndexedSetTrie::new(),
+            postings: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the trie without the inverted index.
+    #[must_use]
+    pub fn into_inner(self) -> IndexedSetTrie<K, T> {
+        self.trie
+    }
+}
+
+impl<K, T> InvertedSetTrie<K, T>
+where
+    K: Ord + Clone,
+{
+    /// Inserts the item in the given set, posting the set under each of its keys. Returns the
+    /// handle of the set, see [`IndexedSetTrie::insert`].
+    pub fn insert(&mut self, keys: impl IntoIterator<Item = K>, item: T) -> EntryId {
+        let keys: Vec<K> = keys.into_iter().collect();
+        let id = self.trie.insert(keys.clone(), item);
+        for key in keys {
+            self.postings.entry(key).or_default().insert(id);
+        }
+        id
+    }
+
+    /// Removes the set `keys`, returning its values if it was stored, and removes it from the
+    /// postings of its keys.
+    pub fn remove(&mut self, keys: &[K]) -> Option<Vec<T>> {
+        let id = self.trie.id(keys)?;
+        for key in keys {
+            if let Some(postings) = self.postings.get_mut(key) {
+                postings.remove(&id);
+                if postings.is_empty() {
+                    self.postings.remove(key);
+                }
+            }
+        }
+        self.trie.remove(keys)
+    }
+
+    /// Iterates over all values stored under sets containing `key`, set by set in the order of
+    /// their handles. Equal to `self.supersets(&[key])` up to order, but only visits the sets
+    /// containing `key`.
+    #[must_use]
+    pub fn sets_containing(&self, key: &K) -> Resolve<'_, T, btree_set::Iter<'_, EntryId>> {
+        let postings = self.postings.get(key).unwrap_or(&NO_POSTINGS);
+        self.trie.resolve(postings.iter())
+    }
+
+    /// The number of sets containing `key`.
+    #[must_use]
+    pub fn count_containing(&self, key: &K) -> usize {
+        self.postings.get(key).map_or(0, BTreeSet::len)
+    }
+}
+
+impl<K, T> Deref for InvertedSetTrie<K, T> {
+    type Target = IndexedSetTrie<K, T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.trie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::InvertedSetTrie;
+
+    #[test]
+    fn inverted() {
+        let mut trie = InvertedSetTrie::new();
+        trie.insert(vec![1, 2, 5], 'a');
+        trie.insert(vec![2, 5], 'b');
+        trie.insert(vec![5], 'c');
+        trie.insert(vec![1, 2, 5], 'd');
+        trie.insert(vec![3], 'e');
+
+        let sorted = |values: Vec<&char>| {
+            let mut values: Vec<char> = values.into_iter().copied().collect();
+            values.sort_unstable();
+            values
+        };
+        for key in 0..7 {
+            assert_eq!(
+                sorted(trie.sets_containing(&key).collect()),
+                sorted(trie.supersets(&[key]).collect())
+            );
+        }
+        assert_eq!(trie.count_containing(&5), 3);
+
+        assert_eq!(trie.remove(&[1, 2, 5]), Some(vec!['a', 'd']));
+        assert_eq!(trie.remove(&[1, 2, 5]), None);
+        assert_eq!(trie.count_containing(&1), 0);
+        assert_eq!(
+            trie.sets_containing(&5).collect::<Vec<_>>(),
+            vec![&'b', &'c']
+        );
+        assert_eq!(trie.into_inner().values().count(), 3);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::{Children, Node};
+use std::iter::Zip;
+use std::slice;
+
+/// Iterator for [`SetTrie::iter`](crate::SetTrie::iter).
+#[derive(Debug, Clone)]
+pub struct Iter<'a, K, T> {
+    path: Vec<&'a K>,
+    leaves: slice::Iter<'a, T>,
+    // the remaining children of every node on the path, the root included.
+    stack: Vec<Children<'a, K, T>>,
+}
+
+impl<'a, K, T> Iter<'a, K, T> {
+    pub(crate) fn new(root: &'a Node<K, T>) -> Self {
+        Iter {
+            path: vec![],
+            leaves: root.leaves.iter(),
+            stack: vec![root.children()],
+        }
+    }
+}
+
+impl<'a, K, T> Iterator for Iter<'a, K, T> {
+    type Item = (Vec<&'a K>, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some((self.path.clone(), item));
+            }
+
+            if let Some((key, child)) = self.stack.last_mut()?.next() {
+                self.path.push(key);
+                self.stack.push(child.children());
+                self.leaves = child.leaves.iter();
+            } else {
+                self.stack.pop();
+                self.path.pop();
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.leaves.len(), None)
+    }
+}
+
+type ChildrenMut<'a, K, T> = Zip<slice::Iter<'a, K>, slice::IterMut<'a, Node<K, T>>>;
+
+/// Iterator for [`SetTrie::iter_mut`](crate::SetTrie::iter_mut).
+#[derive(Debug)]
+pub struct IterMut<'a, K, T> {
+    path: Vec<&'a K>,
+    leaves: slice::IterMut<'a, T>,
+    // the remaining children of every node on the path, the root included.
+    stack: Vec<ChildrenMut<'a, K, T>>,
+}
+
+/// Splits a node into its mutable children and values, keeping the keys immutable.
+fn split_mut<K, T>(node: &mut Node<K, T>) -> (ChildrenMut<'_, K, T>, slice::IterMut<'_, T>) {
+    let Node {
+        keys,
+        children,
+        leaves,
+        ..
+    } = node;
+    (keys.iter().zip(children.iter_mut()), leaves.iter_mut())
+}
+
+impl<'a, K, T> IterMut<'a, K, T> {
+    pub(crate) fn new(root: &'a mut Node<K, T>) -> Self {
+        let (children, leaves) = split_mut(root);
+        IterMut {
+            path: vec![],
+            leaves,
+            stack: vec![children],
+        }
+    }
+}
+
+impl<'a, K, T> Iterator for IterMut<'a, K, T> {
+    type Item = (Vec<&'a K>, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some((self.path.clone(), item));
+            }
+
+            if let Some((key, child)) = self.stack.last_mut()?.next() {
+                let (children, leaves) = split_mut(child);
+                self.path.push(key);
+                self.stack.push(children);
+                self.leaves = leaves;
+            } else {
+                self.stack.pop();
+                self.path.pop();
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.leaves.len(), None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SetTrie;
+
+    #[test]
+    fn iter() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[1, 2, 3], 'a');
+        trie.insert(&[2], 'b');
+        trie.insert(&[], 'c');
+        trie.insert(&[1, 3], 'd');
+        trie.insert(&[1, 2], 'e');
+
+        assert_eq!(
+            trie.iter().collect::<Vec<_>>(),
+            vec![
+                (vec![], &'c'),
+                (vec![&&1, &&2], &'e'),
+                (vec![&&1, &&2, &&3], &'a'),
+                (vec![&&1, &&3], &'d'),
+                (vec![&&2], &'b'),
+            ]
+        );
+
+        for (path, item) in &mut trie {
+            if path.len() == 2 {
+                *item = item.to_ascii_uppercase();
+            }
+        }
+        assert_eq!(
+            (&trie)
+                .into_iter()
+                .map(|(_, item)| *item)
+                .collect::<String>(),
+            "cEaDb"
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use crate::{JsonError, SetTrie};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Separates the keys of a set in the names of the JSON map.
+const SEPARATOR: char = ',';
+
+impl<K, T> SetTrie<K, T> {
+    /// Converts the trie into a JSON map from every stored set to the array of its values. The
+    /// name of a set is its keys joined by commas, and the name of the empty set is empty.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 2], "foo");
+    /// trie.insert(&[1, 2], "bar");
+    /// trie.insert(&[], "baz");
+    ///
+    /// let map = trie.to_json_map().unwrap();
+    /// assert_eq!(
+    ///     serde_json::Value::from(map).to_string(),
+    ///     r#"{"":["baz"],"1,2":["foo","bar"]}"#
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Key`] if the name of a set would not be read back as the same set by
+    /// [from json map](Self::from_json_map): if a key contains a comma, or if the set holds only
+    /// a key which is written as the empty string, as the empty set does. Returns
+    /// [`JsonError::Value`] if a value cannot be serialized.
+    pub fn to_json_map(&self) -> Result<Map<String, Value>, JsonError>
+    where
+        K: Display,
+        T: Serialize,
+    {
+        let mut map = Map::new();
+        for (path, item) in self {
+            let keys: Vec<String> = path.iter().map(ToString::to_string).collect();
+            let name = keys.join(&SEPARATOR.to_string());
+            if keys.iter().any(|key| key.contains(SEPARATOR))
+                || (name.is_empty() && !keys.is_empty())
+            {
+                return Err(JsonError::Key(name));
+            }
+            let item = serde_json::to_value(item).map_err(JsonError::Value)?;
+            if let Value::Array(items) = map.entry(name).or_insert_with(|| Value::Array(vec![])) {
+                items.push(item);
+            }
+        }
+        Ok(map)
+    }
+}
+
+impl<K, T> SetTrie<K, T>
+where
+    K: Ord,
+{
+    /// Builds a trie from a JSON map as produced by [to json map](Self::to_json_map). The keys in
+    /// the name of a set may be in any order and are parsed with [`FromStr`]. An empty name is the
+    /// empty set, while every key in a non-empty name is parsed, including empty ones.
+    ///
+    /// ```rust
+    /// use set_trie::SetTrie;
+    ///
+    /// let json = serde_json::json!({"2,1": ["foo"], "1": ["bar", "baz"]});
+    /// let trie: SetTrie<u8, String> = SetTrie::from_json_map(json.as_object().unwrap().clone())
+    ///     .unwrap();
+    ///
+    /// assert_eq!(trie.supersets(&[1]).count(), 3);
+    /// assert_eq!(trie.subsets(&[2]).count(), 0);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`JsonError::Key`] if a key cannot be parsed, and [`JsonError::Value`] if the
+    /// values of a set are not an array of `T`.
+    pub fn from_json_map(map: Map<String, Value>) -> Result<Self, JsonError>
+    where
+        K: Clone + FromStr,
+        T: DeserializeOwned,
+    {
+        let mut trie = Self::new();
+        for (name, items) in map {
+            let mut keys = if name.is_empty() {
+                vec![]
+            } else {
+                name.split(SEPARATOR)
+                    .map(str::parse)
+                    .collect::<Result<Vec<K>, _>>()
+                    .map_err(|_| JsonError::Key(name.clone()))?
+            };
+            keys.sort();
+            keys.dedup();
+
+            let items: Vec<T> = serde_json::from_value(items).map_err(JsonError::Value)?;
+            trie.insert_many(keys, items);
+        }
+        Ok(trie)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{JsonError, SetTrie};
+
+    #[test]
+    fn json_map() {
+        let mut trie = SetTrie::new();
+        trie.insert(vec![1, 2, 3], 'a');
+        trie.insert(vec![1, 2, 3], 'b');
+        trie.insert(vec![2], 'c');
+        trie.insert(vec![], 'd');
+
+        let map = trie.to_json_map().unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["1,2,3"], serde_json::json!(["a", "b"]));
+
+        let parsed: SetTrie<i32, char> = SetTrie::from_json_map(map).unwrap();
+        assert!(parsed.iter().eq(trie.iter()));
+
+        let invalid = serde_json::json!({"1,x": ['e']});
+        assert!(matches!(
+            SetTrie::<i32, char>::from_json_map(invalid.as_object().unwrap().clone()),
+            Err(JsonError::Key(name)) if name == "1,x"
+        ));
+        let invalid = serde_json::json!({"1,": ['e']});
+        assert!(matches!(
+            SetTrie::<i32, char>::from_json_map(invalid.as_object().unwrap().clone()),
+            Err(JsonError::Key(name)) if name == "1,"
+        ));
+        let invalid = serde_json::json!({"1": 'e'});
+        assert!(matches!(
+            SetTrie::<i32, char>::from_json_map(invalid.as_object().unwrap().clone()),
+            Err(JsonError::Value(_))
+        ));
+    }
+
+    #[test]
+    fn json_map_empty_keys() {
+        let mut trie = SetTrie::new();
+        trie.insert(vec![String::new(), "a".to_string()], 'a');
+        trie.insert(vec![], 'b');
+
+        let map = trie.to_json_map().unwrap();
+        assert_eq!(map[",a"], serde_json::json!(["a"]));
+        let parsed: SetTrie<String, char> = SetTrie::from_json_map(map).unwrap();
+        assert!(parsed.iter().eq(trie.iter()));
+
+        trie.insert(vec![String::new()], 'c');
+        assert!(matches!(trie.to_json_map(), Err(JsonError::Key(name)) if name.is_empty()));
+        let mut trie = SetTrie::new();
+        trie.insert(vec!["a,b".to_string()], 'd');
+        assert!(matches!(trie.to_json_map(), Err(JsonError::Key(name)) if name == "a,b"));
+    }
+}
@@ -19,30 +19,341 @@
 //! assert_eq!(employees.supersets(&[&"accounting"]).collect::<Vec<_>>(), vec![&"Daniels", &"Stevens"]);
 //! ```
 
-use crate::subset::Subset;
-use crate::superset::SuperSet;
-use crate::values::Values;
-use std::iter::FromIterator;
+use crate::bfs::{IterBfs, ValuesBfs};
+use crate::by_len::ByLen;
+use crate::cover::ExactCovers;
+use crate::display::DisplayTree;
+use crate::distance::WithinDistance;
+use crate::drain::Drain;
+use crate::iter::{Iter, IterMut};
+use crate::stats::{KeyCounts, PendingCounts, SetCounts, SubtreeCounts};
+use crate::subset::{
+    MaximalSubsets, Subset, SubsetKeys, SubsetQuery, SubsetsGrouped, SubsetsIn, SubsetsMany,
+};
+use crate::subtrie::{SubTrie, SubTrieMut};
+use crate::superset::{
+    MinimalSuperSets, SuperSet, SuperSetKeys, SuperSetNodes, SuperSetQuery, SuperSetsGrouped,
+    SuperSetsOfAny,
+};
+use crate::values::{Matching, Values, WithPrefix};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::iter::{FromIterator, Zip};
+use std::ops::{Bound, Index, IndexMut, RangeBounds, RangeInclusive};
+use std::{slice, vec};
 
+mod adapter;
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+mod arena;
+mod bfs;
+mod bimap;
+mod budget;
+mod by_len;
+mod complement;
+#[cfg(feature = "concurrent")]
+mod concurrent;
+mod cover;
+mod diff;
+mod display;
+mod distance;
+mod drain;
+#[cfg(feature = "durable")]
+mod durable;
+mod encoded;
 mod entry;
+mod error;
+mod fallible;
+mod frozen;
+mod handle;
+mod indexed;
+mod interned;
+mod inverted;
+mod iter;
+#[cfg(feature = "serde_json")]
+mod json;
+mod map;
+mod multi;
+mod multiset;
+mod observer;
+mod order;
+mod persistent;
+mod priority;
+#[cfg(feature = "rand")]
+mod sample;
+#[cfg(feature = "serde")]
+mod serialize;
+mod sharded;
+mod stats;
+#[cfg(feature = "futures")]
+mod stream;
+mod subscription;
 mod subset;
+mod subtrie;
+#[cfg(feature = "succinct")]
+mod succinct;
 mod superset;
+mod support;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod top_k;
+mod transaction;
 mod values;
+mod view;
+mod walk;
 
+#[cfg(feature = "unicode-normalization")]
+pub use adapter::Nfc;
+pub use adapter::{AdaptedSetTrie, CaseFold, KeyAdapter, Trim};
+pub use bimap::BiSetTrie;
+pub use by_len::LenOrder;
+pub use complement::{SubsetsOfComplement, SuperSetsOfComplement};
+#[cfg(feature = "concurrent")]
+pub use concurrent::ConcurrentSetTrie;
+pub use diff::{Change, OwnedChange, TrieDiff};
+#[cfg(feature = "durable")]
+pub use durable::DurableSetTrie;
+pub use encoded::{Dictionary, EncodedSetTrie};
 pub use entry::{CreatedEntry, Entry, EntryBuilder, ExistingEntry};
+#[cfg(feature = "serde_json")]
+pub use error::JsonError;
+pub use error::{InvariantError, OccupiedError};
+pub use frozen::FrozenSetTrie;
+pub use handle::{HandleSetTrie, ValueHandle};
+pub use indexed::{EntryId, IndexedSetTrie};
+pub use interned::{InternedSetTrie, Interner};
+pub use inverted::InvertedSetTrie;
+pub use multi::{Merged, MultiTrie};
+pub use multiset::Multiset;
+pub use observer::{ObservedSetTrie, Observer};
+pub use order::{KeyOrder, Ordered, OrderedF32, OrderedF64, TotalOrder};
+pub use persistent::PersistentSetTrie;
+pub use priority::Priorities;
+pub use sharded::ShardedSetTrie;
+#[cfg(feature = "metrics")]
+pub use stats::QueryStats;
+pub use stats::TrieStats;
+#[cfg(feature = "futures")]
+pub use stream::QueryStream;
+pub use subscription::{Notification, SubscriptionId, Subscriptions};
+#[cfg(feature = "succinct")]
+pub use succinct::SuccinctSetTrie;
+pub use transaction::Transaction;
+pub use view::MaterializedView;
 
-#[derive(Debug, Default, Eq, PartialEq)]
+/// The children of a node, paired with their keys.
+type Children<'a, K, T> = Zip<slice::Iter<'a, K>, slice::Iter<'a, Node<K, T>>>;
+
+/// The children of a node, paired with their keys, moved out of the node.
+type IntoChildren<K, T> = Zip<vec::IntoIter<K>, vec::IntoIter<Node<K, T>>>;
+
+/// The keys of the children are stored apart from the children themselves, so that searching for
+/// a child only touches the cache lines of the keys. `keys[i]` is the key of `children[i]`.
+#[derive(Default, Eq, PartialEq)]
 struct Node<K, T> {
-    children: Vec<(K, Node<K, T>)>,
+    keys: Vec<K>,
+    children: Vec<Self>,
     leaves: Vec<T>,
+    // the values and sets of this node and its descendants.
+    counts: SubtreeCounts,
 }
 
 impl<K, T> Node<K, T> {
     pub const fn new() -> Self {
         Self {
+            keys: vec![],
             children: vec![],
             leaves: vec![],
+            counts: SubtreeCounts::new(),
+        }
+    }
+
+    /// The counts of this node and its descendants, computed from the values of this node and the
+    /// counts of its children.
+    fn subtree_counts(&self) -> SubtreeCounts {
+        let mut counts = SubtreeCounts::of(self.leaves.len());
+        for child in &self.children {
+            counts.add(child.counts.above());
+        }
+        counts
+    }
+
+    /// Recomputes the counts of this node from its values and the counts of its children.
+    fn recount(&mut self) {
+        self.counts = self.subtree_counts();
+    }
+
+    /// Recomputes the counts of this node and all of its descendants, bottom-up, such as after
+    /// building the trie without keeping them up to date.
+    fn recount_all(&mut self) {
+        // the counts of every node in pre-order, with the index of its parent, so that visiting
+        // them in reverse visits every node after its descendants.
+        let mut nodes: Vec<(&mut SubtreeCounts, Option<usize>)> = vec![];
+        let mut totals = vec![];
+        let mut stack = vec![(self, None)];
+        while let Some((node, parent)) = stack.pop() {
+            let Self {
+                children,
+                leaves,
+                counts,
+                ..
+            } = node;
+            let idx = nodes.len();
+            nodes.push((counts, parent));
+            totals.push(SubtreeCounts::of(leaves.len()));
+            stack.extend(children.iter_mut().map(|child| (child, Some(idx))));
+        }
+        while let (Some((counts, parent)), Some(total)) = (nodes.pop(), totals.pop()) {
+            *counts = total;
+            if let Some(parent) = parent {
+                totals[parent].add(total.above());
+            }
+        }
+    }
+
+    const fn is_empty(&self) -> bool {
+        self.leaves.is_empty() && self.children.is_empty()
+    }
+
+    /// Counts the nodes holding values, this node included, by the length of their key path
+    /// below this node.
+    fn count_sets(&self) -> SetCounts {
+        let mut sets = SetCounts::new();
+        let mut stack = vec![(0, self)];
+        while let Some((len, node)) = stack.pop() {
+            if !node.leaves.is_empty() {
+                sets.add(len);
+            }
+            stack.extend(node.children.iter().map(|child| (len + 1, child)));
+        }
+        sets
+    }
+
+    /// Iterates over the children in order of their keys.
+    fn children(&self) -> Children<'_, K, T> {
+        self.keys.iter().zip(&self.children)
+    }
+
+    /// Moves all children out of the node.
+    fn take_children(&mut self) -> IntoChildren<K, T> {
+        std::mem::take(&mut self.keys)
+            .into_iter()
+            .zip(std::mem::take(&mut self.children))
+    }
+
+    /// The key and child at `idx`.
+    fn child_at(&self, idx: usize) -> (&K, &Self) {
+        (&self.keys[idx], &self.children[idx])
+    }
+
+    /// Inserts the child `key` at `idx`, which must keep the keys sorted.
+    fn insert_child(&mut self, idx: usize, key: K, child: Self) {
+        self.keys.insert(idx, key);
+        self.children.insert(idx, child);
+    }
+
+    /// Appends the child `key`, which must be larger than the keys of the other children.
+    fn push_child(&mut self, key: K, child: Self) {
+        self.keys.push(key);
+        self.children.push(child);
+    }
+
+    /// Removes the child at `idx`.
+    fn remove_child(&mut self, idx: usize) -> (K, Self) {
+        (self.keys.remove(idx), self.children.remove(idx))
+    }
+
+    /// The child indices leading to the lexicographically first stored set.
+    fn first_indices(&self) -> Option<Vec<usize>> {
+        if !self.leaves.is_empty() {
+            return Some(vec![]);
+        }
+
+        let mut indices = vec![];
+        let mut stack = vec![self.children.iter().enumerate()];
+        while let Some(children) = stack.last_mut() {
+            if let Some((idx, child)) = children.next() {
+                indices.push(idx);
+                if !child.leaves.is_empty() {
+                    return Some(indices);
+                }
+                stack.push(child.children.iter().enumerate());
+            } else {
+                stack.pop();
+                indices.pop();
+            }
+        }
+        None
+    }
+
+    /// The child indices leading to the lexicographically last stored set.
+    fn last_indices(&self) -> Option<Vec<usize>> {
+        let mut indices = vec![];
+        let mut stack = vec![(self, self.children.iter().enumerate().rev())];
+        while let Some((node, children)) = stack.last_mut() {
+            if let Some((idx, child)) = children.next() {
+                indices.push(idx);
+                stack.push((child, child.children.iter().enumerate().rev()));
+            } else {
+                // every set below the node is larger than the node itself.
+                if !node.leaves.is_empty() {
+                    return Some(indices);
+                }
+                stack.pop();
+                indices.pop();
+            }
+        }
+        None
+    }
+
+    /// The key path and node reached by following the child `indices`.
+    fn resolve(&self, indices: &[usize]) -> (Vec<&K>, &Self) {
+        let mut path = Vec::with_capacity(indices.len());
+        let mut node = self;
+        for &idx in indices {
+            path.push(&node.keys[idx]);
+            node = &node.children[idx];
+        }
+        (path, node)
+    }
+
+    /// Takes the values of the node reached by following the child `indices`. The nodes along the
+    /// path which are left empty are removed.
+    fn take_at(&mut self, indices: &[usize], key_counts: &mut KeyCounts<K>) -> Vec<T>
+    where
+        K: Ord + Clone,
+    {
+        let diff = SubtreeCounts::of(self.resolve(indices).1.leaves.len()).negate();
+        // the depth of the shallowest node from which on the path leads to nothing but the set.
+        let mut chain = None;
+        let mut node = &mut *self;
+        node.counts.add(diff);
+        for (depth, &idx) in indices.iter().enumerate() {
+            if !node.leaves.is_empty() || node.children.len() > 1 {
+                chain = None;
+            } else if depth > 0 {
+                chain.get_or_insert(depth);
+            }
+            let Self { keys, children, .. } = node;
+            node = &mut children[idx];
+            let before = node.counts;
+            node.counts.add(diff);
+            key_counts.update(&keys[idx], before, node.counts);
+        }
+
+        let leaves = std::mem::take(&mut node.leaves);
+        if !node.is_empty() || indices.is_empty() {
+            return leaves;
+        }
+
+        let cut = chain.unwrap_or(indices.len());
+        let mut node = self;
+        for &idx in &indices[..cut - 1] {
+            node = &mut node.children[idx];
         }
+        node.remove_child(indices[cut - 1]);
+        leaves
     }
 }
 
@@ -51,10 +362,10 @@ impl<K, T> Node<K, T> {
 impl<K, T> Drop for Node<K, T> {
     fn drop(&mut self) {
         let mut stack = Vec::with_capacity(self.children.len());
-        while let Some((_, child)) = self.children.pop() {
+        while let Some(child) = self.children.pop() {
             stack.push(child);
             while let Some(mut current) = stack.pop() {
-                while let Some((_, child)) = current.children.pop() {
+                while let Some(child) = current.children.pop() {
                     stack.push(child)
                 }
             }
@@ -66,24 +377,139 @@ impl<K, T> Node<K, T>
 where
     K: Ord,
 {
-    fn has_descendant(&self, key: &K) -> bool {
-        if self.children.binary_search_by(|(k, _)| k.cmp(key)).is_ok() {
-            return true;
+    /// The index of the child `key`, or the index at which it would be inserted.
+    fn search(&self, key: &K) -> Result<usize, usize> {
+        self.keys.binary_search(key)
+    }
+
+    fn child(&self, key: &K) -> Option<&Self> {
+        self.search(key).ok().map(|idx| &self.children[idx])
+    }
+
+    fn child_mut(&mut self, key: &K) -> Option<&mut Self> {
+        match self.search(key) {
+            Ok(idx) => Some(&mut self.children[idx]),
+            Err(_) => None,
+        }
+    }
+
+    /// Finds or creates the node at the end of the key path `keys`. Children are appended when
+    /// their key is larger than that of the last child, which avoids shifting the children when
+    /// sets are created in lexicographic order.
+    fn or_create_sorted(&mut self, keys: impl IntoIterator<Item = K>) -> &mut Self {
+        let mut node = self;
+        for key in keys {
+            let idx = match node.keys.last() {
+                Some(last) if *last == key => node.keys.len() - 1,
+                Some(last) if *last > key => match node.search(&key) {
+                    Ok(idx) => idx,
+                    Err(idx) => {
+                        node.insert_child(idx, key, Self::new());
+                        idx
+                    }
+                },
+                _ => {
+                    node.push_child(key, Self::new());
+                    node.keys.len() - 1
+                }
+            };
+            node = &mut node.children[idx];
+        }
+        node
+    }
+
+    /// Finds the node at the end of the key path `keys`.
+    fn find(&self, keys: &[K]) -> Option<&Self> {
+        keys.iter().try_fold(self, |node, key| node.child(key))
+    }
+
+    /// Mutably finds the node at the end of the key path `keys`.
+    fn find_mut(&mut self, keys: &[K]) -> Option<&mut Self> {
+        keys.iter().try_fold(self, |node, key| node.child_mut(key))
+    }
+
+    /// The child indices leading to the node at the end of the key path `keys`.
+    fn indices(&self, keys: &[K]) -> Option<Vec<usize>> {
+        let mut indices = Vec::with_capacity(keys.len());
+        let mut node = self;
+        for key in keys {
+            let idx = node.search(key).ok()?;
+            indices.push(idx);
+            node = &node.children[idx];
         }
-        self.children
-            .iter()
-            .take_while(|(k, _)| k < key)
-            .any(|(_, n)| n.has_descendant(key))
+        Some(indices)
     }
 
-    fn between_inclusive(&self, from: &K, to: &K) -> &[(K, Self)] {
-        match (
-            self.children.binary_search_by(|(k, _)| k.cmp(from)),
-            self.children.binary_search_by(|(k, _)| k.cmp(to)),
-        ) {
-            (Ok(from), Ok(to)) | (Err(from), Ok(to)) => &self.children[from..=to],
-            (Ok(from), Err(to)) | (Err(from), Err(to)) => &self.children[from..to],
+    /// Takes the values of the node at the end of the key path `keys`, if it has any, and removes
+    /// them from the counts of the nodes along the path and from `key_counts`.
+    fn remove(&mut self, keys: &[K], key_counts: &mut KeyCounts<K>) -> Option<Vec<T>>
+    where
+        K: Clone,
+    {
+        let removed = self.find(keys)?.leaves.len();
+        if removed == 0 {
+            return None;
         }
+        let diff = SubtreeCounts::of(removed).negate();
+        let mut node = self;
+        node.counts.add(diff);
+        for key in keys {
+            node = node.child_mut(key).expect("the path was found");
+            let before = node.counts;
+            node.counts.add(diff);
+            key_counts.update(key, before, node.counts);
+        }
+        Some(std::mem::take(&mut node.leaves))
+    }
+
+    /// The children of which the key lies within `range`.
+    fn within(&self, range: &impl RangeBounds<K>) -> Children<'_, K, T> {
+        let from = match range.start_bound() {
+            Bound::Included(start) => self.keys.partition_point(|key| key < start),
+            Bound::Excluded(start) => self.keys.partition_point(|key| key <= start),
+            Bound::Unbounded => 0,
+        };
+        let to = match range.end_bound() {
+            Bound::Included(end) => self.keys.partition_point(|key| key <= end),
+            Bound::Excluded(end) => self.keys.partition_point(|key| key < end),
+            Bound::Unbounded => self.keys.len(),
+        };
+        let range = from..to.max(from);
+        self.keys[range.clone()].iter().zip(&self.children[range])
+    }
+
+    fn between_inclusive(&self, from: &K, to: &K) -> Children<'_, K, T> {
+        let from = self.search(from).unwrap_or_else(|idx| idx);
+        let to = self.search(to).map_or_else(|idx| idx, |idx| idx + 1);
+        let range = from..to;
+        self.keys[range.clone()].iter().zip(&self.children[range])
+    }
+}
+
+/// Returns whether every key of the sorted set `a` occurs in the sorted set `b`.
+fn is_subset<K, A, B>(a: &[A], b: &[B]) -> bool
+where
+    K: Ord,
+    A: Borrow<K>,
+    B: Borrow<K>,
+{
+    let mut b = b.iter();
+    a.iter()
+        .all(|k| b.any(|other| other.borrow() == k.borrow()))
+}
+
+/// Converts a range of set lengths into an inclusive range, which is empty if `lengths` is empty.
+fn inclusive_lengths(lengths: &impl RangeBounds<usize>) -> RangeInclusive<usize> {
+    let start = match lengths.start_bound() {
+        Bound::Included(start) => *start,
+        Bound::Excluded(start) => start.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    match lengths.end_bound() {
+        Bound::Included(end) => start..=*end,
+        Bound::Excluded(0) => RangeInclusive::new(1, 0),
+        Bound::Excluded(end) => start..=end - 1,
+        Bound::Unbounded => start..=usize::MAX,
     }
 }
 
@@ -106,145 +532,2355 @@ where
 /// that the caller must ensure that provided keys are in sorted order, lest nonsensical results be
 /// returned.
 ///
+/// The trie counts the nodes of every key below which values are stored, which answers
+/// [contains key](Self::contains_key). As that count owns its keys, the methods which add or
+/// remove sets require keys to be Clone as well.
+///
 /// # Performance
 ///
 /// Subsets and Supersets are lazily evaluated. Note that superset queries are far more expensive
 /// than subset queries, so attempt to structure your problem around subsets.
-#[derive(Debug, Default)]
-pub struct SetTrie<K, T>(Node<K, T>);
+#[derive(Default)]
+pub struct SetTrie<K, T> {
+    root: Node<K, T>,
+    // whether removals prune the nodes they leave empty.
+    prune: bool,
+    // the number of nodes holding values.
+    sets: SetCounts,
+    // the changes to the subtree counts of the nodes last updated through an entry, which are
+    // applied before the shape of the trie changes.
+    pending: PendingCounts,
+    // the number of nodes of every key below which values are stored.
+    keys: KeyCounts<K>,
+}
 
 impl<K, T> SetTrie<K, T> {
     /// Create a new, empty `SetTrie`, without allocating any space for the nodes.
     #[must_use]
     pub const fn new() -> Self {
-        Self(Node::new())
+        Self {
+            root: Node::new(),
+            prune: false,
+            sets: SetCounts::new(),
+            pending: PendingCounts::new(),
+            keys: KeyCounts::new(),
+        }
     }
-}
 
-impl<K, T> SetTrie<K, T>
-where
-    K: Ord,
-{
-    /// A view into a single node in the trie; which must either be created or already exists.
+    /// Create a new, empty `SetTrie` of which [remove](Self::remove) also removes the nodes it
+    /// leaves without values and children, at the cost of recreating them when the set is
+    /// inserted again. Long running processes with churn do not need to [vacuum](Self::vacuum)
+    /// such a trie.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::with_pruning();
+    /// trie.insert(&[1, 2, 3], "foo");
+    /// trie.remove(&[&1, &2, &3]);
+    ///
+    /// assert_eq!(trie.vacuum(), 0);
+    /// ```
     #[must_use]
-    pub fn entry<IK: IntoIterator<Item = K>>(
-        &mut self,
-        keys: IK,
-    ) -> EntryBuilder<K, T, IK::IntoIter> {
-        EntryBuilder::new(self, keys.into_iter())
-    }
-
-    /// Insert the item in the given node. Will create the node if needed.
-    pub fn insert(&mut self, keys: impl IntoIterator<Item = K>, item: T) {
-        self.entry(keys.into_iter()).and_insert(item);
+    pub const fn with_pruning() -> Self {
+        Self {
+            root: Node::new(),
+            prune: true,
+            sets: SetCounts::new(),
+            pending: PendingCounts::new(),
+            keys: KeyCounts::new(),
+        }
     }
 
-    /// Inserts multiple items in the given node. More performant that repeatedly calling insert.
-    pub fn insert_many<IK: IntoIterator<Item = K>, IT: IntoIterator<Item = T>>(
-        &mut self,
-        keys: IK,
-        item: IT,
-    ) {
-        self.entry(keys.into_iter()).and_extend(item);
+    /// Applies the changes to the subtree counts left pending by the last entry, which must be
+    /// done before any change to the shape of the trie.
+    pub(crate) fn flush_counts(&mut self)
+    where
+        K: Ord + Clone,
+    {
+        self.pending.flush(&mut self.root, &mut self.keys);
     }
 
-    /// Iterates over all subsets of `keys` using DFS, meaning that the keys are visited
-    /// in order of the query:
+    /// The number of distinct sets stored in the trie, which is the number of nodes holding at
+    /// least one value. Kept up to date by every update, so no traversal is needed.
     ///
     /// ```rust
     /// let mut trie = set_trie::SetTrie::new();
-    /// trie.insert(&[1], "foo");
+    /// trie.insert(&[1, 2], "foo");
     /// trie.insert(&[1, 2], "bar");
     /// trie.insert(&[1, 2, 3], "baz");
+    /// assert_eq!(trie.set_count(), 2);
     ///
-    /// assert_eq!(trie.subsets(&[&1, &2, &3]).collect::<Vec<_>>(), vec![&"foo", &"bar", &"baz"]);
+    /// trie.remove(&[&1, &2]);
+    /// assert_eq!(trie.set_count(), 1);
     /// ```
     #[must_use]
-    pub fn subsets<'a, 'b>(&'a self, keys: &'b [K]) -> Subset<'a, 'b, K, T> {
-        Subset::new(self, keys)
+    pub const fn set_count(&self) -> usize {
+        self.sets.total()
     }
 
-    /// Iterates over all values in the trie using DFS, meaning that values are visited in order
-    /// of the keys stored in the trie.
+    /// The length of the longest stored set, or 0 if the trie is empty. Superset queries for more
+    /// keys than this return without visiting any node.
     ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 2, 3], "foo");
+    /// trie.insert(&[1], "bar");
+    /// assert_eq!(trie.max_depth(), 3);
+    ///
+    /// trie.remove(&[&1, &2, &3]);
+    /// assert_eq!(trie.max_depth(), 1);
+    /// ```
+    #[must_use]
+    pub const fn max_depth(&self) -> usize {
+        self.sets.max_len()
+    }
+
+    /// The number of stored sets of every cardinality, indexed by the cardinality and ending at
+    /// [max depth](Self::max_depth). Like [set count](Self::set_count), it is kept up to date by
+    /// every update rather than computed by a traversal.
     ///
     /// ```rust
     /// let mut trie = set_trie::SetTrie::new();
-    /// trie.insert(&[1], "foo");
-    /// trie.insert(&[1, 2], "bar");
-    /// trie.insert(&[1, 2, 3], "baz");
+    /// trie.insert(&[1, 2], "foo");
+    /// trie.insert(&[1, 3], "bar");
+    /// trie.insert(&[1, 3], "baz");
+    /// trie.insert(&[4], "qux");
     ///
-    /// assert_eq!(trie.values().collect::<Vec<_>>(), vec![&"foo", &"bar", &"baz"]);
+    /// assert_eq!(trie.size_histogram(), &[0, 1, 2]);
     /// ```
     #[must_use]
-    pub fn values(&self) -> Values<K, T> {
-        Values::new(self)
+    pub fn size_histogram(&self) -> &[usize] {
+        self.sets.by_len()
     }
 
-    /// Iterates over all supersets of `keys` in the trie using DFS, meaning that values are visited
-    /// in order of the query.
+    /// Verifies that the keys of the children of every node are strictly increasing, and that
+    /// the maintained [set counts](Self::size_histogram) and counts of every node match the
+    /// stored sets. Meant for tests and debugging, as it traverses the whole trie.
+    ///
+    /// # Errors
     ///
+    /// Returns the first violated invariant which is found.
     ///
     /// ```rust
     /// let mut trie = set_trie::SetTrie::new();
-    /// trie.insert(&[1], "foo");
+    /// trie.insert(&[1, 2], "foo");
+    /// trie.insert(&[2], "bar");
+    /// trie.remove(&[&2]);
+    ///
+    /// assert_eq!(trie.check_invariants(), Ok(()));
+    /// ```
+    pub fn check_invariants(&self) -> Result<(), InvariantError<K>>
+    where
+        K: Ord + Clone,
+    {
+        let mut path: Vec<&K> = vec![];
+        let mut stack = vec![(0_usize, None, &self.root, self.pending.root())];
+        while let Some((depth, key, node, pending)) = stack.pop() {
+            path.truncate(depth.saturating_sub(1));
+            path.extend(key);
+
+            let owned = || path.iter().map(|&key| key.clone()).collect();
+            if node.keys.len() != node.children.len() {
+                return Err(InvariantError::KeyCount {
+                    path: owned(),
+                    keys: node.keys.len(),
+                    children: node.children.len(),
+                });
+            }
+            if node.keys.windows(2).any(|pair| pair[0] >= pair[1]) {
+                return Err(InvariantError::Unsorted { path: owned() });
+            }
+            let mut counts = SubtreeCounts::of(node.leaves.len());
+            for (idx, child) in node.children.iter().enumerate() {
+                counts.add(pending.child(idx).apply(child.counts).above());
+            }
+            if !pending.apply(node.counts).bounds(counts) {
+                return Err(InvariantError::SubtreeCount { path: owned() });
+            }
+            stack.extend(
+                node.children()
+                    .enumerate()
+                    .rev()
+                    .map(|(idx, (key, child))| (depth + 1, Some(key), child, pending.child(idx))),
+            );
+        }
+
+        if let Some((len, counted, actual)) = self.sets.mismatch(&self.root.count_sets()) {
+            return Err(InvariantError::SetCount {
+                len,
+                counted,
+                actual,
+            });
+        }
+        match self.keys.mismatch(&KeyCounts::count(&self.root)) {
+            Some((key, counted, actual)) => Err(InvariantError::KeyOccurrences {
+                key,
+                counted,
+                actual,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Iterates over all sets and their values in the trie using DFS, meaning that sets are
+    /// visited in lexicographic order.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[2], "baz");
     /// trie.insert(&[1, 2], "bar");
-    /// trie.insert(&[1, 2, 3], "baz");
     ///
-    /// assert_eq!(trie.supersets(&[&1]).collect::<Vec<_>>(), vec![&"foo", &"bar", &"baz"]);
+    /// assert_eq!(
+    ///     trie.iter().collect::<Vec<_>>(),
+    ///     vec![(vec![&&1, &&2], &"bar"), (vec![&&2], &"baz")]
+    /// );
     /// ```
+    #[must_use]
+    pub fn iter(&self) -> Iter<'_, K, T> {
+        Iter::new(&self.root)
+    }
+
+    /// Iterates over all sets and mutable references to their values in lexicographic order.
     ///
-    /// # Remarks
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1], 1);
+    /// trie.insert(&[1, 2], 2);
     ///
-    /// Note that the empty set will provide the same result as values. There is currently no fast
-    /// path in the trie, so if you know that your query contains no keys, use [`SetTrie::values`]
-    /// instead.
+    /// for (path, item) in trie.iter_mut() {
+    ///     *item *= path.len();
+    /// }
+    /// assert_eq!(trie.values().collect::<Vec<_>>(), vec![&1, &4]);
+    /// ```
     #[must_use]
-    pub fn supersets<'a, 'b>(&'a self, keys: &'b [K]) -> SuperSet<'a, 'b, K, T> {
-        SuperSet::new(self, keys)
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, T> {
+        IterMut::new(&mut self.root)
+    }
+
+    /// Renders the trie as an indented tree, one node per line. Unlike the `Debug` output, the
+    /// rendering is iterative and can be limited in depth, making it suitable for large tries.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 2], "foo");
+    /// trie.insert(&[1, 3], "bar");
+    ///
+    /// assert_eq!(
+    ///     trie.display_tree().to_string(),
+    ///     ".\n└── 1\n    ├── 2 [\"foo\"]\n    └── 3 [\"bar\"]\n"
+    /// );
+    /// assert_eq!(trie.display_tree().max_depth(0).to_string(), ".\n└── ...\n");
+    /// ```
+    #[must_use]
+    pub const fn display_tree(&self) -> DisplayTree<'_, K, T> {
+        DisplayTree::new(self)
     }
 }
 
-impl<I, K, T> Extend<(I, T)> for SetTrie<K, T>
+impl<K, T> SetTrie<K, T>
 where
-    I: IntoIterator<Item = K>,
     K: Ord,
 {
-    fn extend<F: IntoIterator<Item = (I, T)>>(&mut self, iter: F) {
-        for (k, t) in iter {
-            self.insert(k, t)
+    /// A view into a single node in the trie; which must either be created or already exists.
+    #[must_use]
+    pub fn entry<IK: IntoIterator<Item = K>>(
+        &mut self,
+        keys: IK,
+    ) -> EntryBuilder<K, T, IK::IntoIter>
+    where
+        K: Clone,
+    {
+        EntryBuilder::new(self, keys.into_iter())
+    }
+
+    /// Insert the item in the given node. Will create the node if needed. The returned entry
+    /// reports whether the node was created, and gives access to the inserted item.
+    ///
+    /// ```rust
+    /// use set_trie::{Entry, SetTrie};
+    ///
+    /// let mut trie = SetTrie::new();
+    /// assert!(matches!(trie.insert(&[1, 2], 1), Entry::Created(_)));
+    ///
+    /// *trie.insert(&[1, 2], 2).into_last_mut().unwrap() += 1;
+    /// assert_eq!(trie.values().collect::<Vec<_>>(), vec![&1, &3]);
+    /// ```
+    pub fn insert(&mut self, keys: impl IntoIterator<Item = K>, item: T) -> Entry<'_, K, T>
+    where
+        K: Clone,
+    {
+        self.entry(keys).and_insert(item)
+    }
+
+    /// Insert the item under the keys of `set`, which are sorted by construction.
+    ///
+    /// ```rust
+    /// use std::collections::BTreeSet;
+    ///
+    /// let mut trie = set_trie::SetTrie::new();
+    /// let set: BTreeSet<_> = vec![3, 1, 2].into_iter().collect();
+    /// trie.insert_set(&set, "foo");
+    ///
+    /// assert_eq!(trie.supersets(&[1, 3]).collect::<Vec<_>>(), vec![&"foo"]);
+    /// ```
+    pub fn insert_set(&mut self, set: &BTreeSet<K>, item: T) -> Entry<'_, K, T>
+    where
+        K: Clone,
+    {
+        self.insert(set.iter().cloned(), item)
+    }
+
+    /// Insert the item under the keys of `set`, sorting them first.
+    ///
+    /// ```rust
+    /// use std::collections::HashSet;
+    ///
+    /// let mut trie = set_trie::SetTrie::new();
+    /// let set: HashSet<_> = vec![3, 1, 2].into_iter().collect();
+    /// trie.insert_hashset(set, "foo");
+    ///
+    /// assert_eq!(trie.supersets(&[1, 3]).collect::<Vec<_>>(), vec![&"foo"]);
+    /// ```
+    pub fn insert_hashset<S>(&mut self, set: HashSet<K, S>, item: T) -> Entry<'_, K, T>
+    where
+        K: Clone,
+    {
+        let mut keys: Vec<K> = set.into_iter().collect();
+        keys.sort_unstable();
+        self.insert(keys, item)
+    }
+
+    /// Inserts the item only if the given set does not hold any value yet, which keeps at most one
+    /// value per set.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OccupiedError`] holding the item if the set already holds a value.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// assert!(trie.try_insert(&[1, 2], "foo").is_ok());
+    /// assert_eq!(trie.try_insert(&[1, 2], "bar").unwrap_err().value, "bar");
+    /// ```
+    pub fn try_insert(
+        &mut self,
+        keys: impl IntoIterator<Item = K>,
+        item: T,
+    ) -> Result<(), OccupiedError<T>>
+    where
+        K: Clone,
+    {
+        let mut entry = self.entry(keys).or_create();
+        if !entry.items().is_empty() {
+            return Err(OccupiedError { value: item });
         }
+        entry.push_value(item);
+        Ok(())
     }
-}
 
-impl<I, K, T> FromIterator<(I, T)> for SetTrie<K, T>
-where
-    I: IntoIterator<Item = K>,
-    K: Ord,
-{
-    fn from_iter<F: IntoIterator<Item = (I, T)>>(iter: F) -> Self {
+    /// Inserts multiple items in the given node. More performant that repeatedly calling insert.
+    pub fn insert_many<IK: IntoIterator<Item = K>, IT: IntoIterator<Item = T>>(
+        &mut self,
+        keys: IK,
+        item: IT,
+    ) where
+        K: Clone,
+    {
+        self.entry(keys.into_iter()).and_extend(item);
+    }
+
+    /// Builds a trie from sets which are sorted lexicographically. New nodes are appended rather
+    /// than inserted, making this considerably faster than [insert](Self::insert) for bulk loads.
+    /// Unsorted input still produces the correct trie, only slower.
+    ///
+    /// ```rust
+    /// let trie = set_trie::SetTrie::from_sorted_iter(vec![
+    ///     (vec![1], "foo"),
+    ///     (vec![1, 2], "bar"),
+    ///     (vec![2], "baz"),
+    /// ]);
+    ///
+    /// assert_eq!(trie.subsets(&[1, 2]).collect::<Vec<_>>(), vec![&"foo", &"bar", &"baz"]);
+    /// ```
+    pub fn from_sorted_iter<IK, I>(iter: I) -> Self
+    where
+        K: Clone,
+        IK: IntoIterator<Item = K>,
+        I: IntoIterator<Item = (IK, T)>,
+    {
         let mut trie = Self::new();
-        trie.extend(iter);
+        for (keys, item) in iter {
+            let mut len = 0;
+            let node = trie
+                .root
+                .or_create_sorted(keys.into_iter().inspect(|_| len += 1));
+            trie.sets
+                .update(len, &mut node.leaves, |leaves| leaves.push(item));
+        }
+        trie.root.recount_all();
+        trie.keys = KeyCounts::count(&trie.root);
         trie
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Insert the item in the given node, unless the node already holds an equal item. Gives the
+    /// values of every set the semantics of a set, so repeated inserts are idempotent. Returns
+    /// whether the item was inserted.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// assert!(trie.insert_unique_value(&[1, 2], "foo"));
+    /// assert!(!trie.insert_unique_value(&[1, 2], "foo"));
+    ///
+    /// assert_eq!(trie.values().collect::<Vec<_>>(), vec![&"foo"]);
+    /// ```
+    pub fn insert_unique_value(&mut self, keys: impl IntoIterator<Item = K>, item: T) -> bool
+    where
+        K: Clone,
+        T: PartialEq,
+    {
+        let mut entry = self.entry(keys).or_create();
+        if entry.items().contains(&item) {
+            return false;
+        }
+        entry.push_value(item);
+        true
+    }
 
-    mod doctests {
-        include!(concat!(env!("OUT_DIR"), "/skeptic-tests.rs"));
+    /// Insert the item in the given node, unless the set is already stored, in which case the item
+    /// is combined into the last value of the set by `merge` instead. Keeps a single value per set
+    /// when used throughout, such as a counter. Returns the stored value.
+    ///
+    /// ```rust
+    /// let mut counts = set_trie::SetTrie::new();
+    /// for tags in [vec!["a", "b"], vec!["b"], vec!["a", "b"]] {
+    ///     counts.insert_or_merge(tags, 1, |count, new| *count += new);
+    /// }
+    ///
+    /// assert_eq!(counts.supersets(&["b"]).collect::<Vec<_>>(), vec![&2, &1]);
+    /// ```
+    pub fn insert_or_merge(
+        &mut self,
+        keys: impl IntoIterator<Item = K>,
+        item: T,
+        merge: impl FnOnce(&mut T, T),
+    ) -> &mut T
+    where
+        K: Clone,
+    {
+        let (node, path, sets, pending, key_counts) = self.entry(keys).or_create().into_parts();
+        if let Some(last) = node.leaves.len().checked_sub(1) {
+            merge(&mut node.leaves[last], item);
+        } else {
+            sets.add(path.len());
+            let before = node.counts;
+            node.counts.add(SubtreeCounts::of(1));
+            if let Some(key) = path.last() {
+                key_counts.update(key, before, node.counts);
+            }
+            pending.add_above(path.len(), SubtreeCounts::of(1));
+            pending.lengthen(path.len());
+            node.leaves.push(item);
+        }
+        let last = node.leaves.len() - 1;
+        &mut node.leaves[last]
     }
 
-    #[test]
-    fn insert() {
-        let mut trie = SetTrie::new();
-        trie.insert(&[1], "c");
-        trie.insert(&[1, 2], "c");
-        trie.insert(&[1, 2, 3], "a");
-        trie.insert(&[1, 2, 3], "b");
-        assert_eq!(trie.entry(&[1, 2, 3]).items(), Some(&vec!["a", "b"]))
+    /// Removes the set `keys` from the trie, returning its values if it was stored. Unless the
+    /// trie was created [with pruning](Self::with_pruning), the nodes along the path are kept in
+    /// place, so reinserting the same set later is cheap; use [vacuum](Self::vacuum) to reclaim
+    /// them.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 2], "foo");
+    ///
+    /// assert_eq!(trie.remove(&[&1, &2]), Some(vec!["foo"]));
+    /// assert_eq!(trie.remove(&[&1, &2]), None);
+    /// ```
+    pub fn remove(&mut self, keys: &[K]) -> Option<Vec<T>>
+    where
+        K: Clone,
+    {
+        self.flush_counts();
+        if !self.prune {
+            let removed = self.root.remove(keys, &mut self.keys)?;
+            self.sets.remove(keys.len());
+            return Some(removed);
+        }
+
+        let indices = self.root.indices(keys)?;
+        if self.root.resolve(&indices).1.leaves.is_empty() {
+            return None;
+        }
+        self.sets.remove(indices.len());
+        Some(self.root.take_at(&indices, &mut self.keys))
+    }
+
+    /// Removes all nodes which hold neither values nor children, such as those left behind by
+    /// [remove](Self::remove) or [drain values](Entry::drain_values). Returns the number of
+    /// removed nodes.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 2, 3], "foo");
+    /// trie.insert(&[1], "bar");
+    /// trie.remove(&[&1, &2, &3]);
+    ///
+    /// assert_eq!(trie.vacuum(), 2);
+    /// assert_eq!(trie.vacuum(), 0);
+    /// ```
+    pub fn vacuum(&mut self) -> usize
+    where
+        K: Clone,
+    {
+        self.flush_counts();
+        self.root.walk_mut(
+            &mut self.sets,
+            &mut self.keys,
+            (),
+            |(), _| Some(()),
+            |_, (), _| {},
+        )
+    }
+
+    /// The number of nodes and the memory they use, see [`TrieStats`].
+    #[must_use]
+    pub fn stats(&self) -> TrieStats {
+        let mut stats = TrieStats::default();
+        let mut stack = vec![(0, &self.root)];
+        while let Some((depth, node)) = stack.pop() {
+            stats.nodes += 1;
+            stats.children_len += node.children.len();
+            stats.children_capacity += node.children.capacity();
+            stats.leaves_len += node.leaves.len();
+            stats.leaves_capacity += node.leaves.capacity();
+            stats.max_depth = stats.max_depth.max(depth);
+            stack.extend(node.children.iter().map(|child| (depth + 1, child)));
+        }
+        stats
+    }
+
+    /// Shrinks the capacity of every node as much as possible, releasing the memory reported by
+    /// [`stats`](Self::stats) as unused.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// for i in 0..10 {
+    ///     trie.insert(vec![i], i);
+    /// }
+    /// for i in 1..10 {
+    ///     trie.remove(&[i]);
+    /// }
+    ///
+    /// trie.shrink_to_fit();
+    /// let stats = trie.stats();
+    /// assert_eq!(stats.children_capacity, stats.children_len);
+    /// assert_eq!(stats.leaves_capacity, stats.leaves_len);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        let mut stack = vec![&mut self.root];
+        while let Some(node) = stack.pop() {
+            node.keys.shrink_to_fit();
+            node.children.shrink_to_fit();
+            node.leaves.shrink_to_fit();
+            stack.extend(node.children.iter_mut());
+        }
+    }
+
+    /// A mutable view into all sets which start with `prefix`, creating the prefix if needed.
+    /// Operations on the view do not need to traverse the prefix again.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    ///
+    /// let mut accounting = trie.subtrie_mut(vec!["accounting"]);
+    /// accounting.insert(vec!["banking"], "Daniels");
+    /// accounting.insert(vec!["crime"], "Stevens");
+    ///
+    /// assert_eq!(
+    ///     trie.supersets(&["accounting"]).collect::<Vec<_>>(),
+    ///     vec![&"Daniels", &"Stevens"]
+    /// );
+    /// ```
+    pub fn subtrie_mut(&mut self, prefix: impl IntoIterator<Item = K>) -> SubTrieMut<'_, K, T>
+    where
+        K: Clone,
+    {
+        let (node, path, sets, pending, keys) = self.entry(prefix).or_create().into_parts();
+        SubTrieMut::new(node, path, sets, pending, keys)
+    }
+
+    /// Iterates over the values of all sets which start with `prefix` using DFS. Unlike
+    /// [supersets](Self::supersets), the keys of `prefix` must be the smallest keys of a set.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 2], "foo");
+    /// trie.insert(&[1, 2, 3], "bar");
+    /// trie.insert(&[0, 1, 2], "baz");
+    ///
+    /// assert_eq!(trie.with_prefix(&[&1, &2]).collect::<Vec<_>>(), vec![&"foo", &"bar"]);
+    /// assert_eq!(trie.with_prefix(&[&2]).count(), 0);
+    /// ```
+    #[must_use]
+    pub fn with_prefix(&self, prefix: &[K]) -> WithPrefix<'_, K, T> {
+        WithPrefix::new(self.root.find(prefix))
+    }
+
+    /// Whether any set holding values contains `key`. The trie counts the nodes of every key
+    /// below which values are stored, so this is a lookup in that count.
+    ///
+    /// # Performance
+    ///
+    /// Takes logarithmic time in the number of distinct keys, plus the depth of the set last
+    /// changed through an [entry](Self::entry), of which the counts of the nodes above it are
+    /// yet to be applied. Unlike [`InvertedSetTrie::count_containing`], the number of sets
+    /// containing `key` is not known.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 3], "foo");
+    /// trie.insert(&[2], "bar");
+    ///
+    /// assert!(trie.contains_key(&&3));
+    /// assert!(!trie.contains_key(&&4));
+    /// ```
+    #[must_use]
+    pub fn contains_key(&self, key: &K) -> bool {
+        let mut count = self.keys.get(key);
+        // the nodes of the pending path are counted by the values they kept before the changes.
+        let mut node = &self.root;
+        let mut pending = self.pending.root();
+        for &idx in self.pending.path() {
+            let label = &node.keys[idx];
+            node = &node.children[idx];
+            pending = pending.child(idx);
+            match label.cmp(key) {
+                Ordering::Less => {}
+                Ordering::Equal => {
+                    count -= usize::from(node.counts.values > 0);
+                    count += usize::from(pending.apply(node.counts).values > 0);
+                    break;
+                }
+                Ordering::Greater => break,
+            }
+        }
+        count > 0
+    }
+
+    /// Iterates over the values stored under exactly the set `keys`, excluding its subsets and
+    /// supersets.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1], "foo");
+    /// trie.insert(&[1, 2], "bar");
+    /// trie.insert(&[1, 2], "baz");
+    /// trie.insert(&[1, 2, 3], "qux");
+    ///
+    /// assert_eq!(trie.matching(&[&1, &2]).collect::<Vec<_>>(), vec![&"bar", &"baz"]);
+    /// assert_eq!(trie.matching(&[&2]).count(), 0);
+    /// ```
+    #[must_use]
+    pub fn matching(&self, keys: &[K]) -> Matching<'_, T> {
+        Matching::new(self.root.find(keys).map(|node| node.leaves.as_slice()))
+    }
+
+    /// A read-only view into all sets which start with `prefix`, or `None` if no such set exists.
+    /// Queries on the view only visit the nodes below the prefix.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&["accounting", "banking"], "Daniels");
+    /// trie.insert(&["accounting", "crime"], "Stevens");
+    /// trie.insert(&["banking", "crime"], "Smith");
+    ///
+    /// let accounting = trie.subtrie(&[&"accounting"]).unwrap();
+    /// assert_eq!(accounting.values().collect::<Vec<_>>(), vec![&"Daniels", &"Stevens"]);
+    /// assert_eq!(accounting.supersets(&[&"crime"]).collect::<Vec<_>>(), vec![&"Stevens"]);
+    /// ```
+    #[must_use]
+    pub fn subtrie(&self, prefix: &[K]) -> Option<SubTrie<'_, K, T>> {
+        self.root.find(prefix).map(SubTrie::new)
+    }
+
+    /// Moves all sets of which the first key is greater than or equal to `key` into a new trie,
+    /// leaving the other sets in place. Entire branches below the root are moved, so no sets need
+    /// to be reinserted. The empty set is never moved.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 3], "foo");
+    /// trie.insert(&[2], "bar");
+    /// trie.insert(&[3], "baz");
+    ///
+    /// let other = trie.split_off(&&2);
+    /// assert_eq!(trie.values().collect::<Vec<_>>(), vec![&"foo"]);
+    /// assert_eq!(other.values().collect::<Vec<_>>(), vec![&"bar", &"baz"]);
+    /// ```
+    #[must_use]
+    pub fn split_off(&mut self, key: &K) -> Self
+    where
+        K: Clone,
+    {
+        self.flush_counts();
+        let idx = self.root.keys.partition_point(|k| k < key);
+        let mut other = Self {
+            root: Node::new(),
+            prune: self.prune,
+            sets: SetCounts::new(),
+            pending: PendingCounts::new(),
+            keys: KeyCounts::new(),
+        };
+        other.root.keys = self.root.keys.split_off(idx);
+        other.root.children = self.root.children.split_off(idx);
+        other.root.recount();
+        self.root.recount();
+        other.sets = other.root.count_sets();
+        self.sets.remove_all(&other.sets);
+        other.keys = KeyCounts::count(&other.root);
+        self.keys = KeyCounts::count(&self.root);
+        other
+    }
+
+    /// Iterates over all subsets of `keys` using DFS, meaning that the keys are visited
+    /// in order of the query:
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1], "foo");
+    /// trie.insert(&[1, 2], "bar");
+    /// trie.insert(&[1, 2, 3], "baz");
+    ///
+    /// assert_eq!(trie.subsets(&[&1, &2, &3]).collect::<Vec<_>>(), vec![&"foo", &"bar", &"baz"]);
+    /// ```
+    #[must_use]
+    pub fn subsets<'a, 'b>(&'a self, keys: &'b [K]) -> Subset<'a, 'b, K, T> {
+        Subset::new(&self.root, keys)
+    }
+
+    /// Creates a [`SubsetQuery`], which runs subset queries like [`SetTrie::subsets`] while reusing
+    /// its buffers from one query to the next, for running many queries in a tight loop.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 2], "foo");
+    /// trie.insert(&[2], "bar");
+    ///
+    /// let mut query = trie.subset_query();
+    /// assert_eq!(query.run(&[&1, &2]).collect::<Vec<_>>(), vec![&"foo", &"bar"]);
+    /// assert_eq!(query.run(&[&2]).collect::<Vec<_>>(), vec![&"bar"]);
+    /// ```
+    #[must_use]
+    pub const fn subset_query(&self) -> SubsetQuery<'_, K, T> {
+        SubsetQuery::new(&self.root)
+    }
+
+    /// Iterates over all subsets of `keys` with a length within `lengths`, in the same order as
+    /// [`SetTrie::subsets`]. Branches which are too deep to satisfy the constraint are not
+    /// traversed.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1], "foo");
+    /// trie.insert(&[1, 2], "bar");
+    /// trie.insert(&[1, 2, 3], "baz");
+    ///
+    /// assert_eq!(trie.subsets_with_len(&[&1, &2, &3], 2..).collect::<Vec<_>>(), vec![&"bar", &"baz"]);
+    /// assert_eq!(trie.subsets_with_len(&[&1, &2, &3], ..=1).collect::<Vec<_>>(), vec![&"foo"]);
+    /// ```
+    #[must_use]
+    pub fn subsets_with_len<'a, 'b>(
+        &'a self,
+        keys: &'b [K],
+        lengths: impl RangeBounds<usize>,
+    ) -> Subset<'a, 'b, K, T> {
+        Subset::with_len(&self.root, keys, inclusive_lengths(&lengths))
+    }
+
+    /// Iterates over the subsets of `keys` ordered by the size of the stored set, smallest or
+    /// largest first. The query keys left after a node, and the length of the longest set below
+    /// it which every node keeps, bound the size of the sets below it, so sets are yielded
+    /// without collecting all subsets first.
+    ///
+    /// ```rust
+    /// use set_trie::{LenOrder, SetTrie};
+    ///
+    /// let mut trie = SetTrie::new();
+    /// trie.insert(&[1, 2], "foo");
+    /// trie.insert(&[1], "bar");
+    /// trie.insert(&[2, 3], "baz");
+    ///
+    /// let most_specific = trie.subsets_by_len(&[&1, &2, &3], LenOrder::Descending);
+    /// assert_eq!(most_specific.take(2).collect::<Vec<_>>(), vec![&"foo", &"baz"]);
+    /// ```
+    #[must_use]
+    pub fn subsets_by_len<'a, 'b>(&'a self, keys: &'b [K], order: LenOrder) -> ByLen<'a, 'b, K, T> {
+        ByLen::subsets(&self.root, self.pending.root(), keys, order)
+    }
+
+    /// Iterates over the subsets of `keys` once per stored set rather than once per value,
+    /// yielding the key path of every set together with its values. Sets are visited in the same
+    /// order as [`SetTrie::subsets`].
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1], "foo");
+    /// trie.insert(&[1], "bar");
+    /// trie.insert(&[1, 2], "baz");
+    /// trie.insert(&[3], "qux");
+    ///
+    /// assert_eq!(
+    ///     trie.subsets_grouped(&[&1, &2]).collect::<Vec<_>>(),
+    ///     vec![(vec![&&1], &["foo", "bar"][..]), (vec![&&1, &&2], &["baz"][..])]
+    /// );
+    /// ```
+    #[must_use]
+    pub const fn subsets_grouped<'a, 'b>(&'a self, keys: &'b [K]) -> SubsetsGrouped<'a, 'b, K, T> {
+        SubsetsGrouped::new(&self.root, keys)
+    }
+
+    /// Iterates over the key paths of the stored subsets of `keys`, without their values. Sets
+    /// are visited in the same order as [`SetTrie::subsets`], and the values of a set are never
+    /// iterated, however many it holds.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1], "foo");
+    /// trie.insert(&[1], "bar");
+    /// trie.insert(&[1, 2], "baz");
+    /// trie.insert(&[3], "qux");
+    ///
+    /// assert_eq!(
+    ///     trie.subset_keys(&[&1, &2]).collect::<Vec<_>>(),
+    ///     vec![vec![&&1], vec![&&1, &&2]]
+    /// );
+    /// ```
+    #[must_use]
+    pub const fn subset_keys<'a, 'b>(&'a self, keys: &'b [K]) -> SubsetKeys<'a, 'b, K, T> {
+        SubsetKeys::new(&self.root, keys)
+    }
+
+    /// Iterates over all values stored under sets of which every key lies within `range`, in the
+    /// same order as [`SetTrie::subsets`]. Keys are checked against the bounds of the range, so
+    /// that a large contiguous query never needs to be collected into a slice.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 2], "foo");
+    /// trie.insert(&[2, 5], "bar");
+    /// trie.insert(&[3], "baz");
+    ///
+    /// assert_eq!(trie.subsets_in(&1..&4).collect::<Vec<_>>(), vec![&"foo", &"baz"]);
+    /// ```
+    #[must_use]
+    pub fn subsets_in<R>(&self, range: R) -> SubsetsIn<'_, K, T, R>
+    where
+        R: RangeBounds<K>,
+    {
+        SubsetsIn::new(&self.root, range)
+    }
+
+    /// The largest stored subset of `keys` together with its values, like the longest match of a
+    /// routing table. Among equally large subsets, the first in lexicographic order is returned.
+    /// Branches which cannot hold a larger subset than the best one found so far are skipped, and
+    /// the search stops as soon as no larger subset can be stored.
+    ///
+    /// ```rust
+    /// let mut rules = set_trie::SetTrie::new();
+    /// rules.insert(&["GET"], "read");
+    /// rules.insert(&["/admin", "GET"], "admin");
+    /// rules.insert(&["/admin", "POST"], "admin write");
+    ///
+    /// let (keys, values) = rules.most_specific_subset(&[&"/admin", &"GET", &"json"]).unwrap();
+    /// assert_eq!(keys, vec![&&"/admin", &&"GET"]);
+    /// assert_eq!(values, &["admin"]);
+    /// ```
+    #[must_use]
+    pub fn most_specific_subset(&self, keys: &[K]) -> Option<(Vec<&K>, &[T])> {
+        self.root.most_specific_subset(keys, self.max_depth())
+    }
+
+    /// Creates a new trie holding copies of the subsets of `keys` and their values. Matching
+    /// branches are copied as they are, rather than re-inserting every set.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&["acme", "eu"], "eu-1");
+    /// trie.insert(&["acme", "us"], "us-1");
+    /// trie.insert(&["acme"], "global");
+    ///
+    /// let shard = trie.clone_subsets(&[&"acme", &"eu"]);
+    /// assert_eq!(shard.values().collect::<Vec<_>>(), vec![&"global", &"eu-1"]);
+    /// ```
+    #[must_use]
+    pub fn clone_subsets(&self, keys: &[K]) -> Self
+    where
+        K: Clone,
+        T: Clone,
+    {
+        let mut sets = SetCounts::new();
+        let root = self.root.clone_subsets(keys, &mut sets);
+        Self {
+            keys: KeyCounts::count(&root),
+            root,
+            prune: self.prune,
+            sets,
+            pending: PendingCounts::new(),
+        }
+    }
+
+    /// The value stored under a subset of `keys` with the highest `priority`. Among values of the
+    /// same priority, the first in the order of [`SetTrie::subsets`] is returned.
+    ///
+    /// # Performance
+    ///
+    /// The trie holds no bounds on priorities given by an arbitrary closure, so every subset is
+    /// visited and prioritized, without pruning. Callers on a hot path should build
+    /// [priorities](Self::priorities) once and reuse it for every query while the trie is not
+    /// modified: its queries skip every branch which cannot hold a value of a higher priority.
+    ///
+    /// ```rust
+    /// let mut rules = set_trie::SetTrie::new();
+    /// rules.insert(&["GET"], ("read", 1));
+    /// rules.insert(&["/admin"], ("deny", 10));
+    /// rules.insert(&["/admin", "GET"], ("admin", 5));
+    ///
+    /// let best = rules.best_subset(&[&"/admin", &"GET"], |(_, priority)| *priority);
+    /// assert_eq!(best, Some(&("deny", 10)));
+    /// ```
+    pub fn best_subset<P, F>(&self, keys: &[K], mut priority: F) -> Option<&T>
+    where
+        P: Ord,
+        F: FnMut(&T) -> P,
+    {
+        best_by(self.subsets(keys), |value| priority(value)).map(|(_, value)| value)
+    }
+
+    /// Iterates over the maximal subsets of `keys`: the subsets of `keys` which are not contained
+    /// in another subset of `keys` stored in the trie. Values are visited in the same order as
+    /// [`SetTrie::subsets`].
+    ///
+    /// The subsets are found in a single traversal, as [`SetTrie::subsets`] does. Every stored
+    /// subset without a larger one below it is compared with the maximal subsets found before it,
+    /// which are kept until the iterator is dropped.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1], "foo");
+    /// trie.insert(&[1, 2], "bar");
+    /// trie.insert(&[1, 2, 4], "baz");
+    /// trie.insert(&[3], "qux");
+    ///
+    /// assert_eq!(trie.maximal_subsets(&[&1, &2, &3]).collect::<Vec<_>>(), vec![&"bar", &"qux"]);
+    /// ```
+    #[must_use]
+    pub fn maximal_subsets<'a, 'b>(&'a self, keys: &'b [K]) -> MaximalSubsets<'a, 'b, K, T> {
+        MaximalSubsets::new(self, keys)
+    }
+
+    /// Answers many subset queries in a single traversal of the trie. Every value which is stored
+    /// under a subset of at least one of the `queries` is yielded once, together with the indices
+    /// of the queries it satisfies. Each query must be sorted.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1], "foo");
+    /// trie.insert(&[1, 2], "bar");
+    /// trie.insert(&[3], "baz");
+    ///
+    /// assert_eq!(
+    ///     trie.subsets_many(&[&[&1, &2], &[&1, &3]]).collect::<Vec<_>>(),
+    ///     vec![(&"foo", vec![0, 1]), (&"bar", vec![0]), (&"baz", vec![1])]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn subsets_many<'a, 'b>(&'a self, queries: &'b [&'b [K]]) -> SubsetsMany<'a, 'b, K, T> {
+        SubsetsMany::new(&self.root, queries)
+    }
+
+    /// Returns the `k` values stored under subsets of `keys` which score highest according to
+    /// `score`, best first. Values with equal scores are returned in the order of
+    /// [`SetTrie::subsets`]. Only `k` values are held in memory during the traversal.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1], 3);
+    /// trie.insert(&[1, 2], 7);
+    /// trie.insert(&[2], 5);
+    ///
+    /// assert_eq!(trie.subsets_top_k(&[&1, &2], 2, |v| *v), vec![&7, &5]);
+    /// ```
+    #[must_use]
+    pub fn subsets_top_k<S: Ord>(
+        &self,
+        keys: &[K],
+        k: usize,
+        score: impl FnMut(&T) -> S,
+    ) -> Vec<&T> {
+        top_k::top_k(self.subsets(keys), k, score)
+    }
+
+    /// Removes all values stored under subsets of `keys`, returning them in the same order as
+    /// [`SetTrie::subsets`]. Nodes which are left without values and children are removed.
+    ///
+    /// The values are removed in a single traversal when this method is called, so dropping the
+    /// returned iterator early does not keep them in the trie.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1], "foo");
+    /// trie.insert(&[1, 2], "bar");
+    /// trie.insert(&[1, 3], "baz");
+    ///
+    /// assert_eq!(trie.drain_subsets(&[&1, &2]).collect::<Vec<_>>(), vec!["foo", "bar"]);
+    /// assert_eq!(trie.values().collect::<Vec<_>>(), vec![&"baz"]);
+    /// ```
+    pub fn drain_subsets(&mut self, keys: &[K]) -> Drain<T>
+    where
+        K: Clone,
+    {
+        let mut drained = vec![];
+        self.remove_subsets(keys, |_, item| drained.push(item));
+        Drain::new(drained)
+    }
+
+    /// Removes all values stored under subsets of `keys` in a single traversal, passing each of
+    /// them to `removed` together with its set, in the same order as [`SetTrie::subsets`]. Nodes
+    /// which are left without values and children are removed. Returns the number of removed
+    /// values.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1], "foo");
+    /// trie.insert(&[1, 2], "bar");
+    /// trie.insert(&[1, 3], "baz");
+    ///
+    /// assert_eq!(trie.remove_subsets(&[&1, &2], |_, _| {}), 2);
+    /// assert_eq!(trie.values().collect::<Vec<_>>(), vec![&"baz"]);
+    /// ```
+    pub fn remove_subsets<F>(&mut self, keys: &[K], mut removed: F) -> usize
+    where
+        K: Clone,
+        F: FnMut(&[K], T),
+    {
+        let mut count = 0;
+        self.flush_counts();
+        self.root.walk_mut(
+            &mut self.sets,
+            &mut self.keys,
+            0,
+            |start, key| {
+                keys[*start..]
+                    .binary_search(key)
+                    .ok()
+                    .map(|idx| start + idx + 1)
+            },
+            |path, _, leaves| {
+                count += leaves.len();
+                for item in leaves.drain(..) {
+                    removed(path, item);
+                }
+            },
+        );
+        count
+    }
+
+    /// Removes all values for which `predicate` returns `true`, returning them together with the
+    /// set they were stored under, in DFS order. The predicate may mutate the values it inspects,
+    /// regardless of whether they are removed. Nodes which are left without values and children
+    /// are removed.
+    ///
+    /// Like [`SetTrie::drain_subsets`], the values are removed in a single traversal when this
+    /// method is called.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1], 1);
+    /// trie.insert(&[1, 2], 2);
+    /// trie.insert(&[2], 3);
+    ///
+    /// let odd = trie.extract_if(|_, value| *value % 2 == 1).collect::<Vec<_>>();
+    /// assert_eq!(odd, vec![(vec![&1], 1), (vec![&2], 3)]);
+    /// assert_eq!(trie.values().collect::<Vec<_>>(), vec![&2]);
+    /// ```
+    pub fn extract_if<F>(&mut self, mut predicate: F) -> Drain<(Vec<K>, T)>
+    where
+        F: FnMut(&[K], &mut T) -> bool,
+        K: Clone,
+    {
+        let mut extracted = vec![];
+        self.flush_counts();
+        self.root.walk_mut(
+            &mut self.sets,
+            &mut self.keys,
+            (),
+            |(), _| Some(()),
+            |path, (), leaves| {
+                let mut idx = 0;
+                while idx < leaves.len() {
+                    if predicate(path, &mut leaves[idx]) {
+                        extracted.push((path.to_vec(), leaves.remove(idx)));
+                    } else {
+                        idx += 1;
+                    }
+                }
+            },
+        );
+        Drain::new(extracted)
+    }
+
+    /// Removes every occurrence of `item` from the trie, scanning all values. Returns the sets
+    /// `item` was removed from together with the number of removed occurrences, in DFS order.
+    /// Nodes which are left without values and children are removed.
+    ///
+    /// [`BiSetTrie::remove_by_value`] only visits the sets which hold the value.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1], "doc-1");
+    /// trie.insert(&[1, 2], "doc-1");
+    /// trie.insert(&[1, 2], "doc-1");
+    /// trie.insert(&[2], "doc-2");
+    ///
+    /// assert_eq!(
+    ///     trie.remove_by_value(&"doc-1"),
+    ///     vec![(vec![&1], 1), (vec![&1, &2], 2)]
+    /// );
+    /// assert_eq!(trie.values().collect::<Vec<_>>(), vec![&"doc-2"]);
+    /// ```
+    pub fn remove_by_value(&mut self, item: &T) -> Vec<(Vec<K>, usize)>
+    where
+        K: Clone,
+        T: PartialEq,
+    {
+        let mut removed = vec![];
+        self.flush_counts();
+        self.root.walk_mut(
+            &mut self.sets,
+            &mut self.keys,
+            (),
+            |(), _| Some(()),
+            |path, (), leaves| {
+                let len = leaves.len();
+                leaves.retain(|v| v != item);
+                if leaves.len() < len {
+                    removed.push((path.to_vec(), len - leaves.len()));
+                }
+            },
+        );
+        removed
+    }
+
+    /// Iterates over all values in the trie using DFS, meaning that values are visited in order
+    /// of the keys stored in the trie.
+    ///
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1], "foo");
+    /// trie.insert(&[1, 2], "bar");
+    /// trie.insert(&[1, 2, 3], "baz");
+    ///
+    /// assert_eq!(trie.values().collect::<Vec<_>>(), vec![&"foo", &"bar", &"baz"]);
+    /// ```
+    #[must_use]
+    pub const fn values(&self) -> Values<'_, K, T> {
+        Values::new(&self.root)
+    }
+
+    /// Iterates over all values in the trie using BFS, meaning that values of smaller sets are
+    /// visited before those of larger sets.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 2], "bar");
+    /// trie.insert(&[2], "baz");
+    /// trie.insert(&[1], "foo");
+    ///
+    /// assert_eq!(trie.values_bfs().collect::<Vec<_>>(), vec![&"foo", &"baz", &"bar"]);
+    /// ```
+    #[must_use]
+    pub fn values_bfs(&self) -> ValuesBfs<'_, K, T> {
+        ValuesBfs::new(&self.root)
+    }
+
+    /// Iterates over all sets and their values in the trie using BFS, meaning that smaller sets
+    /// are visited before larger sets.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 2], "bar");
+    /// trie.insert(&[1], "foo");
+    ///
+    /// assert_eq!(
+    ///     trie.iter_bfs().collect::<Vec<_>>(),
+    ///     vec![(vec![&&1], &"foo"), (vec![&&1, &&2], &"bar")]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn iter_bfs(&self) -> IterBfs<'_, K, T> {
+        IterBfs::new(&self.root)
+    }
+
+    /// The lexicographically first stored set together with its values. Takes `O(depth)` as long
+    /// as the trie holds no nodes left empty by [remove](Self::remove).
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 2], "bar");
+    /// trie.insert(&[2], "baz");
+    /// trie.insert(&[1, 2, 3], "foo");
+    ///
+    /// assert_eq!(trie.first(), Some((vec![&&1, &&2], &["bar"][..])));
+    /// ```
+    #[must_use]
+    pub fn first(&self) -> Option<(Vec<&K>, &[T])> {
+        let (path, node) = self.root.resolve(&self.root.first_indices()?);
+        Some((path, &node.leaves))
+    }
+
+    /// The lexicographically last stored set together with its values. Takes `O(depth)` as long
+    /// as the trie holds no nodes left empty by [remove](Self::remove).
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 2], "bar");
+    /// trie.insert(&[2], "baz");
+    /// trie.insert(&[2, 3], "foo");
+    ///
+    /// assert_eq!(trie.last(), Some((vec![&&2, &&3], &["foo"][..])));
+    /// ```
+    #[must_use]
+    pub fn last(&self) -> Option<(Vec<&K>, &[T])> {
+        let (path, node) = self.root.resolve(&self.root.last_indices()?);
+        Some((path, &node.leaves))
+    }
+
+    /// Removes the lexicographically first stored set, returning its keys and values. Nodes which
+    /// are left empty are removed as well, so the trie can be consumed as a queue.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(vec![1, 2], "bar");
+    /// trie.insert(vec![2], "baz");
+    /// trie.insert(vec![1], "foo");
+    ///
+    /// assert_eq!(trie.pop_first(), Some((vec![1], vec!["foo"])));
+    /// assert_eq!(trie.pop_first(), Some((vec![1, 2], vec!["bar"])));
+    /// assert_eq!(trie.pop_first(), Some((vec![2], vec!["baz"])));
+    /// assert_eq!(trie.pop_first(), None);
+    /// ```
+    pub fn pop_first(&mut self) -> Option<(Vec<K>, Vec<T>)>
+    where
+        K: Clone,
+    {
+        self.flush_counts();
+        let indices = self.root.first_indices()?;
+        let keys = self.root.resolve(&indices).0.into_iter().cloned().collect();
+        self.sets.remove(indices.len());
+        Some((keys, self.root.take_at(&indices, &mut self.keys)))
+    }
+
+    /// Removes the lexicographically last stored set, returning its keys and values. Nodes which
+    /// are left empty are removed as well, so the trie can be consumed as a queue.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(vec![1, 2], "bar");
+    /// trie.insert(vec![2], "baz");
+    /// trie.insert(vec![1], "foo");
+    ///
+    /// assert_eq!(trie.pop_last(), Some((vec![2], vec!["baz"])));
+    /// assert_eq!(trie.pop_last(), Some((vec![1, 2], vec!["bar"])));
+    /// ```
+    pub fn pop_last(&mut self) -> Option<(Vec<K>, Vec<T>)>
+    where
+        K: Clone,
+    {
+        self.flush_counts();
+        let indices = self.root.last_indices()?;
+        let keys = self.root.resolve(&indices).0.into_iter().cloned().collect();
+        self.sets.remove(indices.len());
+        Some((keys, self.root.take_at(&indices, &mut self.keys)))
+    }
+
+    /// Iterates over all supersets of `keys` in the trie using DFS, meaning that values are visited
+    /// in order of the query.
+    ///
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1], "foo");
+    /// trie.insert(&[1, 2], "bar");
+    /// trie.insert(&[1, 2, 3], "baz");
+    ///
+    /// assert_eq!(trie.supersets(&[&1]).collect::<Vec<_>>(), vec![&"foo", &"bar", &"baz"]);
+    /// ```
+    ///
+    /// # Remarks
+    ///
+    /// Note that the empty set will provide the same result as values, and is just as fast.
+    #[must_use]
+    pub fn supersets<'a, 'b>(&'a self, keys: &'b [K]) -> SuperSet<'a, 'b, K, T> {
+        if keys.len() > self.max_depth() {
+            return SuperSet::empty();
+        }
+        SuperSet::new(&self.root, keys)
+    }
+
+    /// Creates a [`SuperSetQuery`], which runs superset queries like [`SetTrie::supersets`] while
+    /// reusing its buffers from one query to the next, for running many queries in a tight loop.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 2], "foo");
+    /// trie.insert(&[2, 3], "bar");
+    ///
+    /// let mut query = trie.superset_query();
+    /// assert_eq!(query.run(&[&1]).collect::<Vec<_>>(), vec![&"foo"]);
+    /// assert_eq!(query.run(&[&2]).collect::<Vec<_>>(), vec![&"foo", &"bar"]);
+    /// ```
+    #[must_use]
+    pub const fn superset_query(&self) -> SuperSetQuery<'_, K, T> {
+        SuperSetQuery::new(&self.root, self.max_depth())
+    }
+
+    /// Iterates over all supersets of `keys` with a length within `lengths`, in the same order as
+    /// [`SetTrie::supersets`]. Branches which are too deep to satisfy the constraint are not
+    /// traversed.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1], "foo");
+    /// trie.insert(&[1, 2], "bar");
+    /// trie.insert(&[1, 2, 3], "baz");
+    ///
+    /// assert_eq!(trie.supersets_with_len(&[&1], 2..=2).collect::<Vec<_>>(), vec![&"bar"]);
+    /// ```
+    #[must_use]
+    pub fn supersets_with_len<'a, 'b>(
+        &'a self,
+        keys: &'b [K],
+        lengths: impl RangeBounds<usize>,
+    ) -> SuperSet<'a, 'b, K, T> {
+        let lengths = inclusive_lengths(&lengths);
+        if keys.len().max(*lengths.start()) > self.max_depth() {
+            return SuperSet::empty();
+        }
+        SuperSet::with_len(&self.root, keys, lengths)
+    }
+
+    /// Iterates over all supersets of a query consisting of `keys` and `wildcards` elements which
+    /// each match any single key. A set matches if it contains `keys` and at least `wildcards`
+    /// other keys, in the same order as [`SetTrie::supersets`].
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&["admin", "read"], "foo");
+    /// trie.insert(&["admin", "read", "write"], "bar");
+    /// trie.insert(&["admin", "delete", "read"], "baz");
+    ///
+    /// // {read, *, admin}
+    /// assert_eq!(
+    ///     trie.supersets_with_wildcards(&[&"admin", &"read"], 1).collect::<Vec<_>>(),
+    ///     vec![&"baz", &"bar"]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn supersets_with_wildcards<'a, 'b>(
+        &'a self,
+        keys: &'b [K],
+        wildcards: usize,
+    ) -> SuperSet<'a, 'b, K, T> {
+        self.supersets_with_len(keys, keys.len().saturating_add(wildcards)..)
+    }
+
+    /// Iterates over all sets of which the symmetric difference with `keys` holds at most `max`
+    /// keys, using DFS. Subtrees are skipped as soon as their path differs from the query in more
+    /// than `max` keys, making this suitable for finding near-duplicate sets.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&["rust", "trie"], "foo");
+    /// trie.insert(&["rust", "set", "trie"], "bar");
+    /// trie.insert(&["go", "set", "trie"], "baz");
+    ///
+    /// assert_eq!(
+    ///     trie.within_distance(&[&"rust", &"set", &"trie"], 1).collect::<Vec<_>>(),
+    ///     vec![&"bar", &"foo"]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn within_distance<'a, 'b>(
+        &'a self,
+        keys: &'b [K],
+        max: usize,
+    ) -> WithinDistance<'a, 'b, K, T> {
+        WithinDistance::new(&self.root, keys, max)
+    }
+
+    /// Iterates over the exact covers of `keys`: the combinations of disjoint stored sets of
+    /// which the union equals `keys`. Every cover lists its sets in lexicographic order, together
+    /// with their values. The empty set is never part of a cover.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&["a", "b"], 1);
+    /// trie.insert(&["a"], 2);
+    /// trie.insert(&["b", "c"], 3);
+    /// trie.insert(&["c"], 4);
+    ///
+    /// let covers: Vec<Vec<i32>> = trie
+    ///     .exact_covers(&[&"a", &"b", &"c"])
+    ///     .map(|cover| cover.into_iter().flat_map(|(_, values)| values).copied().collect())
+    ///     .collect();
+    /// assert_eq!(covers, vec![vec![2, 3], vec![1, 4]]);
+    /// ```
+    #[must_use]
+    pub fn exact_covers<'a, 'b>(&'a self, keys: &'b [K]) -> ExactCovers<'a, 'b, K, T> {
+        ExactCovers::new(&self.root, keys)
+    }
+
+    /// Greedily selects stored sets covering `keys`, each time picking the set which covers the
+    /// most keys that are not covered yet. Selection stops when every key is covered, or when no
+    /// set covers any of the remaining keys. Returns the selected sets and their values in order
+    /// of selection.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 2], "foo");
+    /// trie.insert(&[2, 3, 4], "bar");
+    /// trie.insert(&[1, 5], "baz");
+    ///
+    /// let chosen: Vec<_> = trie
+    ///     .cover_greedy(&[&1, &2, &3, &4])
+    ///     .into_iter()
+    ///     .map(|(_, values)| values[0])
+    ///     .collect();
+    /// assert_eq!(chosen, vec!["bar", "foo"]);
+    /// ```
+    #[must_use]
+    pub fn cover_greedy(&self, keys: &[K]) -> Vec<(Vec<&K>, &[T])> {
+        cover::greedy(&self.root, keys)
+    }
+
+    /// Iterates over the supersets of `keys` ordered by the size of the stored set, smallest or
+    /// largest first. Ascending, the query keys not yet on the path bound the size of the sets
+    /// below a node. Descending, every node keeps a bound on the length of the longest set below
+    /// it, so sets are yielded without collecting all supersets first. Removing sets leaves the
+    /// bounds as they are until the trie is [vacuumed](Self::vacuum), which may delay the first
+    /// superset.
+    ///
+    /// ```rust
+    /// use set_trie::{LenOrder, SetTrie};
+    ///
+    /// let mut trie = SetTrie::new();
+    /// trie.insert(&[1, 2, 3], "foo");
+    /// trie.insert(&[2], "bar");
+    /// trie.insert(&[2, 3], "baz");
+    ///
+    /// let most_general = trie.supersets_by_len(&[&2], LenOrder::Ascending);
+    /// assert_eq!(most_general.collect::<Vec<_>>(), vec![&"bar", &"baz", &"foo"]);
+    /// ```
+    #[must_use]
+    pub fn supersets_by_len<'a, 'b>(
+        &'a self,
+        keys: &'b [K],
+        order: LenOrder,
+    ) -> ByLen<'a, 'b, K, T> {
+        ByLen::supersets(&self.root, self.pending.root(), keys, order)
+    }
+
+    /// Iterates over the supersets of `keys` once per stored set rather than once per value,
+    /// yielding the key path of every set together with its values. Sets are visited in the same
+    /// order as [`SetTrie::supersets`].
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 2], "foo");
+    /// trie.insert(&[1, 2], "bar");
+    /// trie.insert(&[2, 3], "baz");
+    /// trie.insert(&[3], "qux");
+    ///
+    /// assert_eq!(
+    ///     trie.supersets_grouped(&[&2]).collect::<Vec<_>>(),
+    ///     vec![(vec![&&1, &&2], &["foo", "bar"][..]), (vec![&&2, &&3], &["baz"][..])]
+    /// );
+    /// ```
+    #[must_use]
+    pub const fn supersets_grouped<'a, 'b>(
+        &'a self,
+        keys: &'b [K],
+    ) -> SuperSetsGrouped<'a, 'b, K, T> {
+        SuperSetsGrouped::new(&self.root, keys)
+    }
+
+    /// Iterates over the key paths of the stored supersets of `keys`, without their values. Sets
+    /// are visited in the same order as [`SetTrie::supersets`], and the values of a set are never
+    /// iterated, however many it holds.
+    ///
+    /// ```rust
+    /// let mut permissions = set_trie::SetTrie::new();
+    /// permissions.insert(&["read", "write"], "editor");
+    /// permissions.insert(&["admin", "read", "write"], "owner");
+    /// permissions.insert(&["read"], "viewer");
+    ///
+    /// assert_eq!(
+    ///     permissions.superset_keys(&[&"read", &"write"]).collect::<Vec<_>>(),
+    ///     vec![vec![&&"admin", &&"read", &&"write"], vec![&&"read", &&"write"]]
+    /// );
+    /// ```
+    #[must_use]
+    pub const fn superset_keys<'a, 'b>(&'a self, keys: &'b [K]) -> SuperSetKeys<'a, 'b, K, T> {
+        SuperSetKeys::new(&self.root, keys, self.max_depth())
+    }
+
+    /// The smallest stored superset of `keys` together with its values, such as the cheapest
+    /// bundle containing all requested features. Among equally small supersets, the first in
+    /// lexicographic order is returned. Once a superset is found, only branches which may hold a
+    /// smaller one are traversed, and the search stops as soon as a superset of the smallest
+    /// stored length is found.
+    ///
+    /// ```rust
+    /// let mut bundles = set_trie::SetTrie::new();
+    /// bundles.insert(&["backup", "sso", "support"], "enterprise");
+    /// bundles.insert(&["backup", "sso"], "business");
+    /// bundles.insert(&["backup"], "basic");
+    ///
+    /// let (features, plans) = bundles.least_specific_superset(&[&"sso"]).unwrap();
+    /// assert_eq!(features, vec![&&"backup", &&"sso"]);
+    /// assert_eq!(plans, &["business"]);
+    /// ```
+    #[must_use]
+    pub fn least_specific_superset(&self, keys: &[K]) -> Option<(Vec<&K>, &[T])> {
+        // no superset can be shorter than the shortest stored set of at least the query's length.
+        let min_len =
+            (keys.len()..self.sets.by_len().len()).find(|&len| self.sets.by_len()[len] > 0)?;
+        SuperSetNodes::new(&self.root, keys, min_len..=self.max_depth()).smallest()
+    }
+
+    /// Creates a new trie holding copies of the supersets of `keys` and their values. Matching
+    /// branches are copied as they are, rather than re-inserting every set.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&["acme", "eu"], "eu-1");
+    /// trie.insert(&["acme", "eu", "prod"], "eu-2");
+    /// trie.insert(&["acme", "us"], "us-1");
+    ///
+    /// let eu = trie.clone_supersets(&[&"eu"]);
+    /// assert_eq!(eu.values().collect::<Vec<_>>(), vec![&"eu-1", &"eu-2"]);
+    /// ```
+    #[must_use]
+    pub fn clone_supersets(&self, keys: &[K]) -> Self
+    where
+        K: Clone,
+        T: Clone,
+    {
+        let mut sets = SetCounts::new();
+        let root = self.root.clone_supersets(keys, self.max_depth(), &mut sets);
+        Self {
+            keys: KeyCounts::count(&root),
+            root,
+            prune: self.prune,
+            sets,
+            pending: PendingCounts::new(),
+        }
+    }
+
+    /// The value stored under a superset of `keys` with the highest `priority`. Among values of
+    /// the same priority, the first in the order of [`SetTrie::supersets`] is returned.
+    ///
+    /// # Performance
+    ///
+    /// The trie holds no bounds on priorities given by an arbitrary closure, so every superset is
+    /// visited and prioritized, without pruning. Callers on a hot path should build
+    /// [priorities](Self::priorities) once and reuse it for every query while the trie is not
+    /// modified: its queries skip every branch which cannot hold a value of a higher priority.
+    ///
+    /// ```rust
+    /// let mut bundles = set_trie::SetTrie::new();
+    /// bundles.insert(&["backup", "sso"], ("business", 3));
+    /// bundles.insert(&["backup", "sso", "support"], ("enterprise", 2));
+    /// bundles.insert(&["backup"], ("basic", 9));
+    ///
+    /// let best = bundles.best_superset(&[&"sso"], |(_, priority)| *priority);
+    /// assert_eq!(best, Some(&("business", 3)));
+    /// ```
+    pub fn best_superset<P, F>(&self, keys: &[K], mut priority: F) -> Option<&T>
+    where
+        P: Ord,
+        F: FnMut(&T) -> P,
+    {
+        best_by(self.supersets(keys), |value| priority(value)).map(|(_, value)| value)
+    }
+
+    /// Iterates over the minimal supersets of `keys`: the supersets of `keys` which do not contain
+    /// another superset of `keys` stored in the trie. Sets are visited in descending
+    /// lexicographic order, and the values of a set in the order in which they were inserted.
+    ///
+    /// # Performance
+    ///
+    /// The supersets of `keys` are traversed once, without descending into stored sets, and every
+    /// stored superset is compared with the minimal supersets found before it.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 2], "foo");
+    /// trie.insert(&[1, 2, 3], "bar");
+    /// trie.insert(&[1, 3], "baz");
+    /// trie.insert(&[2, 3], "qux");
+    ///
+    /// assert_eq!(trie.minimal_supersets(&[&1]).collect::<Vec<_>>(), vec![&"baz", &"foo"]);
+    /// assert_eq!(trie.minimal_supersets(&[&3]).collect::<Vec<_>>(), vec![&"qux", &"baz"]);
+    /// ```
+    #[must_use]
+    pub fn minimal_supersets<'a, 'b>(&'a self, keys: &'b [K]) -> MinimalSuperSets<'a, 'b, K, T> {
+        MinimalSuperSets::new(self, keys)
+    }
+
+    /// Iterates over all values stored under a superset of at least one of the `queries`, in a
+    /// single traversal of the trie. Every value is yielded once, no matter how many queries it
+    /// satisfies. Each query must be sorted.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 2], "foo");
+    /// trie.insert(&[1, 3], "bar");
+    /// trie.insert(&[2, 3], "baz");
+    ///
+    /// assert_eq!(
+    ///     trie.supersets_of_any(&[&[&1, &2], &[&2]]).collect::<Vec<_>>(),
+    ///     vec![&"foo", &"baz"]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn supersets_of_any<'a, 'b>(
+        &'a self,
+        queries: &'b [&'b [K]],
+    ) -> SuperSetsOfAny<'a, 'b, K, T> {
+        SuperSetsOfAny::new(&self.root, queries)
+    }
+
+    /// Removes all values stored under supersets of `keys`, returning them in the same order as
+    /// [`SetTrie::supersets`]. Nodes which are left without values and children are removed.
+    ///
+    /// The values are removed in a single traversal when this method is called, so dropping the
+    /// returned iterator early does not keep them in the trie.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1], "foo");
+    /// trie.insert(&[1, 2], "bar");
+    /// trie.insert(&[2, 3], "baz");
+    ///
+    /// assert_eq!(trie.drain_supersets(&[&2]).collect::<Vec<_>>(), vec!["bar", "baz"]);
+    /// assert_eq!(trie.values().collect::<Vec<_>>(), vec![&"foo"]);
+    /// ```
+    pub fn drain_supersets(&mut self, keys: &[K]) -> Drain<T>
+    where
+        K: Clone,
+    {
+        let mut drained = vec![];
+        self.remove_supersets(keys, |_, item| drained.push(item));
+        Drain::new(drained)
+    }
+
+    /// Removes all values stored under supersets of `keys` in a single traversal, passing each of
+    /// them to `removed` together with its set, in the same order as [`SetTrie::supersets`].
+    /// Nodes which are left without values and children are removed. Returns the number of
+    /// removed values.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1], "foo");
+    /// trie.insert(&[1, 2], "bar");
+    /// trie.insert(&[2, 3], "baz");
+    ///
+    /// let mut sets = vec![];
+    /// assert_eq!(trie.remove_supersets(&[&2], |keys, _| sets.push(keys.to_vec())), 2);
+    /// assert_eq!(sets, vec![vec![&1, &2], vec![&2, &3]]);
+    /// assert_eq!(trie.values().collect::<Vec<_>>(), vec![&"foo"]);
+    /// ```
+    pub fn remove_supersets<F>(&mut self, keys: &[K], mut removed: F) -> usize
+    where
+        K: Clone,
+        F: FnMut(&[K], T),
+    {
+        let mut count = 0;
+        if keys.len() > self.max_depth() {
+            return count;
+        }
+        self.flush_counts();
+        self.root.walk_mut(
+            &mut self.sets,
+            &mut self.keys,
+            0,
+            |matched, key| match keys.get(*matched).map(|want| key.cmp(want)) {
+                None | Some(Ordering::Less) => Some(*matched),
+                Some(Ordering::Equal) => Some(matched + 1),
+                // the next key of the query can no longer be encountered.
+                Some(Ordering::Greater) => None,
+            },
+            |path, matched, leaves| {
+                if *matched == keys.len() {
+                    count += leaves.len();
+                    for item in leaves.drain(..) {
+                        removed(path, item);
+                    }
+                }
+            },
+        );
+        count
+    }
+
+    /// Returns the `k` values stored under supersets of `keys` which score highest according to
+    /// `score`, best first. Values with equal scores are returned in the order of
+    /// [`SetTrie::supersets`]. Only `k` values are held in memory during the traversal.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1], 3);
+    /// trie.insert(&[1, 2], 7);
+    /// trie.insert(&[2], 5);
+    ///
+    /// assert_eq!(trie.supersets_top_k(&[&1], 1, |v| *v), vec![&7]);
+    /// ```
+    #[must_use]
+    pub fn supersets_top_k<S: Ord>(
+        &self,
+        keys: &[K],
+        k: usize,
+        score: impl FnMut(&T) -> S,
+    ) -> Vec<&T> {
+        top_k::top_k(self.supersets(keys), k, score)
+    }
+}
+
+impl<K, T> From<BTreeMap<BTreeSet<K>, T>> for SetTrie<K, T>
+where
+    K: Ord + Clone,
+{
+    /// Builds the trie from a map of sets, which is sorted lexicographically by construction.
+    fn from(map: BTreeMap<BTreeSet<K>, T>) -> Self {
+        Self::from_sorted_iter(map)
+    }
+}
+
+impl<K, T> From<BTreeMap<Vec<K>, Vec<T>>> for SetTrie<K, T>
+where
+    K: Ord + Clone,
+{
+    /// Builds the trie from a map of sets to their values, of which every set must be sorted.
+    /// The map itself is sorted lexicographically by construction.
+    fn from(map: BTreeMap<Vec<K>, Vec<T>>) -> Self {
+        let mut trie = Self::new();
+        for (keys, items) in map {
+            let len = keys.len();
+            let node = trie.root.or_create_sorted(keys);
+            trie.sets
+                .update(len, &mut node.leaves, |leaves| leaves.extend(items));
+        }
+        trie.root.recount_all();
+        trie.keys = KeyCounts::count(&trie.root);
+        trie
+    }
+}
+
+impl<K, T> From<SetTrie<K, T>> for BTreeMap<Vec<K>, Vec<T>>
+where
+    K: Ord + Clone,
+{
+    /// Collects every stored set together with its values, which is the canonical form of the
+    /// trie: two tries store the same sets and values if their maps are equal.
+    ///
+    /// ```rust
+    /// use std::collections::BTreeMap;
+    ///
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(vec![1, 2], "foo");
+    /// trie.insert(vec![1, 2], "bar");
+    /// trie.insert(vec![3], "baz");
+    ///
+    /// let map = BTreeMap::from(trie);
+    /// assert_eq!(map[&vec![1, 2]], vec!["foo", "bar"]);
+    /// assert_eq!(set_trie::SetTrie::from(map).values().count(), 3);
+    /// ```
+    fn from(trie: SetTrie<K, T>) -> Self {
+        let mut root = trie.root;
+        let mut map = Self::new();
+        if !root.leaves.is_empty() {
+            map.insert(vec![], std::mem::take(&mut root.leaves));
+        }
+
+        let mut path = vec![];
+        let mut stack = vec![root.take_children()];
+        while let Some(children) = stack.last_mut() {
+            let Some((key, mut child)) = children.next() else {
+                stack.pop();
+                path.pop();
+                continue;
+            };
+            path.push(key);
+            if !child.leaves.is_empty() {
+                map.insert(path.clone(), std::mem::take(&mut child.leaves));
+            }
+            stack.push(child.take_children());
+        }
+        map
+    }
+}
+
+impl<K, T> Index<&[K]> for SetTrie<K, T>
+where
+    K: Ord,
+{
+    type Output = [T];
+
+    /// Returns the values of the set `keys`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the set is not stored in the trie.
+    fn index(&self, keys: &[K]) -> &Self::Output {
+        match self.root.find(keys) {
+            Some(node) if !node.leaves.is_empty() => &node.leaves,
+            _ => panic!("set is not stored in the trie"),
+        }
+    }
+}
+
+impl<K, T> IndexMut<&[K]> for SetTrie<K, T>
+where
+    K: Ord,
+{
+    /// Mutably returns the values of the set `keys`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the set is not stored in the trie.
+    fn index_mut(&mut self, keys: &[K]) -> &mut Self::Output {
+        match self.root.find_mut(keys) {
+            Some(node) if !node.leaves.is_empty() => &mut node.leaves,
+            _ => panic!("set is not stored in the trie"),
+        }
+    }
+}
+
+impl<I, K, T> Extend<(I, T)> for SetTrie<K, T>
+where
+    I: IntoIterator<Item = K>,
+    K: Ord + Clone,
+{
+    fn extend<F: IntoIterator<Item = (I, T)>>(&mut self, iter: F) {
+        for (k, t) in iter {
+            self.insert(k, t);
+        }
+    }
+}
+
+impl<'a, K, T> IntoIterator for &'a SetTrie<K, T> {
+    type Item = (Vec<&'a K>, &'a T);
+    type IntoIter = Iter<'a, K, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, T> IntoIterator for &'a mut SetTrie<K, T> {
+    type Item = (Vec<&'a K>, &'a mut T);
+    type IntoIter = IterMut<'a, K, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<I, K, T> FromIterator<(I, T)> for SetTrie<K, T>
+where
+    I: IntoIterator<Item = K>,
+    K: Ord + Clone,
+{
+    fn from_iter<F: IntoIterator<Item = (I, T)>>(iter: F) -> Self {
+        let mut trie = Self::new();
+        trie.extend(iter);
+        trie
+    }
+}
+
+/// The first of `values` with the highest priority, together with its priority.
+fn best_by<V, P, F>(values: impl Iterator<Item = V>, mut priority: F) -> Option<(P, V)>
+where
+    P: Ord,
+    F: FnMut(&V) -> P,
+{
+    let mut best: Option<(P, V)> = None;
+    for value in values {
+        let p = priority(&value);
+        if best.as_ref().is_none_or(|(max, _)| p > *max) {
+            best = Some((p, value));
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod doctests {
+        include!(concat!(env!("OUT_DIR"), "/skeptic-tests.rs"));
+    }
+
+    #[test]
+    fn insert() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[1], "c");
+        trie.insert(&[1, 2], "c");
+        trie.insert(&[1, 2, 3], "a");
+        trie.insert(&[1, 2, 3], "b");
+        assert_eq!(trie.entry(&[1, 2, 3]).items(), Some(&["a", "b"][..]));
+    }
+
+    #[test]
+    fn insert_unique_value() {
+        let mut trie = SetTrie::new();
+        assert!(trie.insert_unique_value(&[1, 2], 'a'));
+        assert!(trie.insert_unique_value(&[1, 2], 'b'));
+        assert!(!trie.insert_unique_value(&[1, 2], 'a'));
+        assert!(trie.insert_unique_value(&[1], 'a'));
+
+        assert_eq!(trie.values().collect::<Vec<_>>(), vec![&'a', &'a', &'b']);
+    }
+
+    #[test]
+    fn insert_or_merge() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[1], vec!['a']);
+        trie.insert(&[1], vec!['b']);
+
+        let merged = trie.insert_or_merge(&[1], vec!['c'], Vec::extend);
+        assert_eq!(merged, &vec!['b', 'c']);
+        trie.insert_or_merge(&[1, 2], vec!['d'], |_, _| unreachable!());
+
+        assert_eq!(
+            trie.values().collect::<Vec<_>>(),
+            vec![&vec!['a'], &vec!['b', 'c'], &vec!['d']]
+        );
+    }
+
+    #[test]
+    fn from_sorted_iter() {
+        let sets: Vec<(Vec<i32>, char)> = vec![
+            (vec![], 'a'),
+            (vec![1], 'b'),
+            (vec![1, 2], 'c'),
+            (vec![1, 2], 'd'),
+            (vec![1, 3], 'e'),
+            (vec![2, 4], 'f'),
+            // out of order, still inserted at the right position.
+            (vec![0, 4], 'g'),
+            (vec![1], 'h'),
+        ];
+        let sorted = SetTrie::from_sorted_iter(sets.clone());
+        let inserted: SetTrie<_, _> = sets.into_iter().collect();
+
+        assert_eq!(
+            sorted.values().collect::<Vec<_>>(),
+            inserted.values().collect::<Vec<_>>()
+        );
+        assert_eq!(sorted.supersets(&[4]).collect::<Vec<_>>(), vec![&'g', &'f']);
+    }
+
+    #[test]
+    fn try_insert() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[1, 2, 3], 'a');
+
+        assert_eq!(trie.try_insert(&[1, 2], 'b'), Ok(()));
+        assert_eq!(
+            trie.try_insert(&[1, 2], 'c'),
+            Err(OccupiedError { value: 'c' })
+        );
+        assert_eq!(
+            trie.try_insert(&[1, 2, 3], 'd').unwrap_err().to_string(),
+            "set already holds a value"
+        );
+        assert_eq!(trie.values().collect::<Vec<_>>(), vec![&'b', &'a']);
+    }
+
+    #[test]
+    fn index() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[1, 2], 'a');
+        trie.insert(&[1, 2], 'b');
+        trie.insert(&[1, 2, 3], 'c');
+
+        assert_eq!(trie[&[&1, &2]], ['a', 'b']);
+        trie[&[&1, &2, &3]][0] = 'd';
+        assert_eq!(trie[&[&1, &2, &3]], ['d']);
+    }
+
+    #[test]
+    #[should_panic(expected = "set is not stored in the trie")]
+    fn index_missing() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[1, 2], 'a');
+        let _ = &trie[&[&1]];
+    }
+
+    #[test]
+    fn entry_path() {
+        let mut trie = SetTrie::new();
+        trie.insert(vec![1, 2], 'a');
+
+        assert_eq!(trie.entry(vec![1, 2]).find().unwrap().path(), vec![&1, &2]);
+        let entry = trie.entry(vec![1]).or_insert('b');
+        assert_eq!(entry.path(), vec![&1]);
+        assert_eq!(entry.entry(vec![3, 4]).or_create().path(), vec![&1, &3, &4]);
+        assert!(trie.entry(vec![]).or_create().path().is_empty());
+    }
+
+    #[test]
+    fn from_btree_map() {
+        let mut map = BTreeMap::new();
+        map.insert(vec![2, 3].into_iter().collect::<BTreeSet<_>>(), 'a');
+        map.insert(vec![1].into_iter().collect(), 'b');
+        map.insert(vec![1, 3].into_iter().collect(), 'c');
+        map.insert(BTreeSet::new(), 'd');
+
+        let trie = SetTrie::from(map);
+        assert_eq!(
+            trie.values().collect::<Vec<_>>(),
+            vec![&'d', &'b', &'c', &'a']
+        );
+        assert_eq!(trie.supersets(&[3]).collect::<Vec<_>>(), vec![&'c', &'a']);
+    }
+
+    #[test]
+    fn first_last() {
+        let mut trie = SetTrie::new();
+        assert_eq!(trie.first(), None);
+        assert_eq!(trie.last(), None);
+
+        trie.insert(&[], 'a');
+        trie.insert(&[1, 2], 'b');
+        trie.insert(&[1, 3], 'c');
+        trie.insert(&[2, 4, 5], 'd');
+        trie.insert(&[2], 'e');
+        assert_eq!(trie.first(), Some((vec![], &['a'][..])));
+        assert_eq!(trie.last(), Some((vec![&&2, &&4, &&5], &['d'][..])));
+
+        // empty nodes left behind by remove are skipped.
+        trie.remove(&[]);
+        trie.remove(&[&1, &2]);
+        trie.remove(&[&2, &4, &5]);
+        assert_eq!(trie.first(), Some((vec![&&1, &&3], &['c'][..])));
+        assert_eq!(trie.last(), Some((vec![&&2], &['e'][..])));
+    }
+
+    #[test]
+    fn vacuum() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[1, 2], 'a');
+        trie.insert(&[1, 2, 3, 4], 'b');
+        trie.insert(&[1, 3], 'c');
+        trie.insert(&[2], 'd');
+        assert_eq!(trie.vacuum(), 0);
+
+        trie.remove(&[&1, &2, &3, &4]);
+        trie.remove(&[&2]);
+        trie.entry(vec![&1, &3])
+            .or_create()
+            .drain_values()
+            .for_each(drop);
+        assert_eq!(trie.vacuum(), 4);
+        assert_eq!(
+            trie.display_tree().to_string(),
+            ".\n└── 1\n    └── 2 ['a']\n"
+        );
+    }
+
+    #[test]
+    fn with_pruning() {
+        let mut trie = SetTrie::with_pruning();
+        trie.insert(&[1, 2], 'a');
+        trie.insert(&[1, 2, 3, 4], 'b');
+        trie.insert(&[2], 'c');
+
+        assert_eq!(trie.remove(&[&1, &2, &3]), None);
+        assert_eq!(trie.remove(&[&1, &2, &3, &4]), Some(vec!['b']));
+        assert_eq!(trie.remove(&[&1, &2]), Some(vec!['a']));
+        assert_eq!(trie.remove(&[&1, &2]), None);
+        assert_eq!(trie.display_tree().to_string(), ".\n└── 2 ['c']\n");
+
+        let other = trie.split_off(&&2);
+        assert!(other.prune);
+    }
+
+    #[test]
+    fn pop_first_last() {
+        let mut trie = SetTrie::new();
+        trie.insert(vec![], 'a');
+        trie.insert(vec![1, 2, 3], 'b');
+        trie.insert(vec![1, 2, 3], 'c');
+        trie.insert(vec![1, 4], 'd');
+        trie.insert(vec![2], 'e');
+        trie.insert(vec![2, 5, 6], 'f');
+
+        assert_eq!(trie.pop_first(), Some((vec![], vec!['a'])));
+        assert_eq!(trie.pop_last(), Some((vec![2, 5, 6], vec!['f'])));
+        // the chain leading to the popped set is removed, but not the stored set above it.
+        assert_eq!(trie.root.child(&2).map(|n| n.children.len()), Some(0));
+
+        assert_eq!(trie.pop_first(), Some((vec![1, 2, 3], vec!['b', 'c'])));
+        assert_eq!(trie.root.child(&1).map(|n| n.children.len()), Some(1));
+        assert_eq!(trie.pop_last(), Some((vec![2], vec!['e'])));
+        assert_eq!(trie.pop_last(), Some((vec![1, 4], vec!['d'])));
+        assert_eq!(trie.pop_last(), None);
+        assert!(trie.root.is_empty());
+    }
+
+    #[test]
+    fn split_off() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[], 'a');
+        trie.insert(&[1, 2], 'b');
+        trie.insert(&[2, 3], 'c');
+        trie.insert(&[4], 'd');
+
+        let mut other = trie.split_off(&&3);
+        assert_eq!(other.values().collect::<Vec<_>>(), vec![&'d']);
+
+        let last = other.split_off(&&5);
+        assert_eq!(last.values().count(), 0);
+        assert_eq!(other.values().collect::<Vec<_>>(), vec![&'d']);
+
+        let rest = trie.split_off(&&0);
+        assert_eq!(trie.values().collect::<Vec<_>>(), vec![&'a']);
+        assert_eq!(rest.values().collect::<Vec<_>>(), vec![&'b', &'c']);
+    }
+
+    #[test]
+    fn set_count() {
+        let mut trie = SetTrie::new();
+        trie.insert(vec![], 'a');
+        trie.insert(vec![1, 2], 'b');
+        trie.insert(vec![1, 2], 'c');
+        trie.insert(vec![1, 3], 'd');
+        trie.insert(vec![2], 'e');
+        trie.entry(vec![4]).or_create();
+        assert_eq!(trie.set_count(), 4);
+        assert_eq!(trie.size_histogram(), &[1, 1, 2]);
+
+        let mut entry = trie.entry(vec![1, 2]).or_create();
+        assert_eq!(entry.drain_values().count(), 2);
+        assert_eq!(trie.remove(&[1, 3]), Some(vec!['d']));
+        assert_eq!(trie.set_count(), 2);
+
+        let other = trie.split_off(&2);
+        assert_eq!((trie.set_count(), other.set_count()), (1, 1));
+        assert_eq!(
+            (trie.size_histogram(), other.size_histogram()),
+            (&[1][..], &[0, 1][..])
+        );
+
+        trie.extend(vec![(vec![1], 'f'), (vec![1, 2], 'g')]);
+        assert_eq!(trie.extract_if(|_, v| *v != 'g').count(), 2);
+        assert_eq!(trie.pop_first(), Some((vec![1, 2], vec!['g'])));
+        assert_eq!(trie.set_count(), 0);
+
+        trie.extend(vec![(vec![1], 'h'), (vec![1, 2], 'i'), (vec![1, 3], 'j')]);
+        let mut removed = vec![];
+        assert_eq!(
+            trie.remove_supersets(&[2], |k, v| removed.push((k.to_vec(), v))),
+            1
+        );
+        assert_eq!(
+            trie.remove_subsets(&[1, 3], |k, v| removed.push((k.to_vec(), v))),
+            2
+        );
+        assert_eq!(
+            removed,
+            vec![(vec![1, 2], 'i'), (vec![1], 'h'), (vec![1, 3], 'j')]
+        );
+        assert_eq!(trie.set_count(), 0);
+        assert_eq!(trie.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn best_match() {
+        let mut trie = SetTrie::new();
+        trie.insert(vec![1], ('a', 2));
+        trie.insert(vec![1, 2], ('b', 3));
+        trie.insert(vec![2], ('c', 3));
+        trie.insert(vec![1, 2, 3], ('d', 1));
+
+        let priority = |(_, p): &(char, u8)| *p;
+        assert_eq!(trie.best_subset(&[1, 2], priority), Some(&('b', 3)));
+        assert_eq!(trie.best_subset(&[3], priority), None);
+        assert_eq!(trie.best_superset(&[1, 2], priority), Some(&('b', 3)));
+        assert_eq!(trie.best_superset(&[3], priority), Some(&('d', 1)));
+        assert_eq!(trie.best_superset(&[4], priority), None);
+    }
+
+    #[test]
+    fn btree_map() {
+        let mut trie = SetTrie::new();
+        trie.insert(vec![], 'a');
+        trie.insert(vec![1, 2], 'b');
+        trie.insert(vec![1, 2], 'c');
+        trie.insert(vec![2], 'd');
+        trie.entry(vec![1, 3]).or_create();
+
+        let map = BTreeMap::from(trie);
+        let expected = vec![
+            (vec![], vec!['a']),
+            (vec![1, 2], vec!['b', 'c']),
+            (vec![2], vec!['d']),
+        ];
+        assert_eq!(map.clone().into_iter().collect::<Vec<_>>(), expected);
+
+        let trie = SetTrie::from(map);
+        assert_eq!(trie.set_count(), 3);
+        assert_eq!(trie.check_invariants(), Ok(()));
+        assert_eq!(
+            BTreeMap::from(trie).into_iter().collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn contains_key() {
+        let mut trie = SetTrie::with_pruning();
+        trie.insert(vec![1, 2, 4], 'a');
+        trie.insert(vec![3], 'b');
+        trie.entry(vec![1, 5]).or_create();
+
+        for key in 0..7 {
+            assert_eq!(
+                trie.contains_key(&key),
+                trie.supersets(&[key]).next().is_some()
+            );
+        }
+        assert!(!trie.contains_key(&5));
+
+        trie.remove(&[1, 2, 4]);
+        assert!(!trie.contains_key(&1));
+        assert!(trie.contains_key(&3));
+
+        trie.entry(vec![1])
+            .or_create()
+            .entry(vec![6])
+            .or_insert('c');
+        assert!(trie.contains_key(&1));
+        assert!(trie.contains_key(&6));
+    }
+
+    #[test]
+    fn contains_key_is_counted() {
+        fn check(trie: &SetTrie<u32, char>) {
+            for key in 0..8 {
+                assert_eq!(
+                    trie.contains_key(&key),
+                    trie.supersets(&[key]).next().is_some(),
+                    "key {key}"
+                );
+            }
+        }
+
+        let mut trie = SetTrie::new();
+        let mut entry = trie.entry(vec![1]).or_create();
+        for key in 2..5 {
+            entry = entry.entry(vec![key]).or_insert('a');
+        }
+        check(&trie);
+        assert_eq!(trie.check_invariants(), Ok(()));
+
+        let mut entry = trie.entry(vec![1, 2, 3]).or_create();
+        entry.drain_values().for_each(drop);
+        check(&trie);
+        trie.insert(vec![5, 6], 'b');
+        trie.insert(vec![7], 'c');
+        check(&trie);
+
+        let mut subtrie = trie.subtrie_mut(vec![1, 2]);
+        subtrie.remove(&[3, 4]);
+        check(&trie);
+        assert_eq!(trie.check_invariants(), Ok(()));
+
+        trie.extract_if(|keys, _| keys.contains(&6)).for_each(drop);
+        check(&trie);
+        assert!(trie.pop_last().is_some());
+        check(&trie);
+        trie.remove(&[1, 2]);
+        trie.vacuum();
+        check(&trie);
+        assert!(trie.values().next().is_none());
+        assert_eq!(trie.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn check_invariants() {
+        let mut trie = SetTrie::new();
+        trie.insert(vec![1, 2], 'a');
+        trie.insert(vec![1, 3], 'b');
+        trie.insert(vec![2], 'c');
+        assert_eq!(trie.check_invariants(), Ok(()));
+
+        trie.root.children[0].keys.swap(0, 1);
+        assert_eq!(
+            trie.check_invariants(),
+            Err(InvariantError::Unsorted { path: vec![1] })
+        );
+        trie.root.children[0].keys.swap(0, 1);
+
+        trie.root.children[1].leaves.clear();
+        assert_eq!(
+            trie.check_invariants(),
+            Err(InvariantError::SubtreeCount { path: vec![2] })
+        );
+        trie.flush_counts();
+        trie.root.recount_all();
+        assert_eq!(
+            trie.check_invariants(),
+            Err(InvariantError::SetCount {
+                len: 1,
+                counted: 1,
+                actual: 0
+            })
+        );
+    }
+
+    #[test]
+    fn subtree_counts() {
+        let mut trie = SetTrie::new();
+        let mut entry = trie.entry(vec![1]).or_insert('a');
+        for key in 2..6 {
+            entry = entry.entry(vec![key]).or_insert('b');
+        }
+        entry.push_value('c');
+        assert_eq!(entry.value_count(), 2);
+        assert_eq!(trie.check_invariants(), Ok(()));
+
+        let mut prefix = trie.entry(vec![1]).or_create();
+        assert_eq!(prefix.value_count(), 6);
+        assert_eq!(prefix.descendant_set_count(), 4);
+        prefix.push_value('d');
+
+        let mut subtrie = trie.subtrie_mut(vec![1, 2]);
+        subtrie.insert(vec![3, 7], 'e');
+        subtrie.insert(vec![4], 'f');
+        assert_eq!(subtrie.remove(&[3, 4, 5]), Some(vec!['b', 'c']));
+        subtrie.insert(vec![3, 4, 6], 'g');
+        assert_eq!(trie.check_invariants(), Ok(()));
+
+        trie.flush_counts();
+        assert_eq!(trie.check_invariants(), Ok(()));
+        let root = trie.entry(vec![]).or_create();
+        assert_eq!(root.value_count(), 8);
+        assert_eq!(root.descendant_set_count(), 7);
     }
 
     /// Due to the recursive structure; the default Drop implementation actually causes a stack
@@ -0,0 +1,189 @@
+use crate::stats::{KeyCounts, PendingCounts, SetCounts};
+use crate::{IntoChildren, Node, SetTrie};
+use std::mem;
+
+/// A node of which the values are being mapped. Its children are moved out and mapped one by one.
+struct Frame<K, T, U> {
+    key: Option<K>,
+    leaves: Vec<U>,
+    children: IntoChildren<K, T>,
+    done: Node<K, U>,
+}
+
+impl<K, T, U> Frame<K, T, U> {
+    fn new(key: Option<K>, node: &mut Node<K, T>, f: &mut impl FnMut(T) -> U) -> Self {
+        let children = node.take_children();
+        let mut done = Node::new();
+        // every value is mapped to exactly one value, in the same node.
+        done.counts = node.counts;
+        done.keys.reserve_exact(children.len());
+        done.children.reserve_exact(children.len());
+        Self {
+            key,
+            leaves: mem::take(&mut node.leaves).into_iter().map(f).collect(),
+            done,
+            children,
+        }
+    }
+}
+
+impl<K, T> SetTrie<K, T> {
+    /// Transforms every value of the trie, keeping the structure of the trie as is. Values are
+    /// transformed in the same order as they are visited by [values](Self::values).
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 2], "foo");
+    /// trie.insert(&[1], "barbaz");
+    ///
+    /// let lengths = trie.map(str::len);
+    /// assert_eq!(lengths.supersets(&[&1]).collect::<Vec<_>>(), vec![&6, &3]);
+    /// ```
+    #[must_use]
+    pub fn map<U>(mut self, mut f: impl FnMut(T) -> U) -> SetTrie<K, U> {
+        let mut root = Frame::new(None, &mut self.root, &mut f);
+        let mut stack = vec![];
+
+        loop {
+            let frame = stack.last_mut().unwrap_or(&mut root);
+            if let Some((key, mut child)) = frame.children.next() {
+                stack.push(Frame::new(Some(key), &mut child, &mut f));
+                continue;
+            }
+
+            let Some(mut frame) = stack.pop() else {
+                break;
+            };
+            frame.done.leaves = frame.leaves;
+            if let Some(key) = frame.key {
+                let parent = &mut stack.last_mut().unwrap_or(&mut root).done;
+                parent.push_child(key, frame.done);
+            }
+        }
+
+        root.done.leaves = root.leaves;
+        SetTrie {
+            root: root.done,
+            prune: self.prune,
+            sets: self.sets,
+            // the shape of the trie is kept, and so are the paths of the pending changes.
+            pending: self.pending,
+            keys: self.keys,
+        }
+    }
+
+    /// Maps every key of the trie through `f`, rebuilding the trie under the new keys. Since `f`
+    /// need not preserve the order of the keys, every set is sorted again; keys of a set which map
+    /// to equal keys are merged, as are sets which map to equal sets. `f` is called once per node
+    /// rather than once per set.
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    ///
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(vec!["banking", "crime"], "Stevens");
+    /// trie.insert(vec!["accounting", "banking"], "Daniels");
+    ///
+    /// let mut symbols = HashMap::new();
+    /// let interned = trie.map_keys(|key| {
+    ///     let next = symbols.len() as u32;
+    ///     *symbols.entry(key).or_insert(next)
+    /// });
+    ///
+    /// let banking = symbols["banking"];
+    /// assert_eq!(interned.supersets(&[banking]).count(), 2);
+    /// ```
+    #[must_use]
+    pub fn map_keys<L>(mut self, mut f: impl FnMut(K) -> L) -> SetTrie<L, T>
+    where
+        L: Ord + Clone,
+    {
+        let mut trie = SetTrie {
+            root: Node::new(),
+            prune: self.prune,
+            sets: SetCounts::new(),
+            pending: PendingCounts::new(),
+            keys: KeyCounts::new(),
+        };
+        if !self.root.leaves.is_empty() {
+            trie.insert_many(vec![], mem::take(&mut self.root.leaves));
+        }
+
+        let mut path = vec![];
+        let mut stack = vec![self.root.take_children()];
+        while let Some(children) = stack.last_mut() {
+            let Some((key, mut child)) = children.next() else {
+                stack.pop();
+                path.pop();
+                continue;
+            };
+
+            path.push(f(key));
+            if !child.leaves.is_empty() {
+                let mut keys = path.clone();
+                keys.sort();
+                keys.dedup();
+                trie.insert_many(keys, mem::take(&mut child.leaves));
+            }
+            stack.push(child.take_children());
+        }
+        trie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SetTrie;
+
+    #[test]
+    fn map() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[], 1);
+        trie.insert(&[1, 2], 2);
+        trie.insert(&[1, 2], 3);
+        trie.insert(&[2, 3], 4);
+
+        let mut seen = vec![];
+        let trie = trie.map(|v| {
+            seen.push(v);
+            v * 10
+        });
+        assert_eq!(seen, vec![1, 2, 3, 4]);
+        assert_eq!(
+            trie.subsets(&[&1, &2]).collect::<Vec<_>>(),
+            vec![&10, &20, &30]
+        );
+        assert_eq!(trie.supersets(&[&3]).collect::<Vec<_>>(), vec![&40]);
+    }
+
+    #[test]
+    fn map_keys() {
+        let mut trie = SetTrie::new();
+        trie.insert(vec![], 'a');
+        trie.insert(vec![1, 2], 'b');
+        trie.insert(vec![1, 3], 'c');
+        trie.insert(vec![2, 3], 'd');
+        trie.insert(vec![4], 'e');
+
+        // reverses the order, and merges 2, 3 and 4.
+        let mut calls = 0;
+        let trie = trie.map_keys(|k| {
+            calls += 1;
+            10 - k.min(2)
+        });
+        assert_eq!(calls, 6);
+
+        assert_eq!(trie.matching(&[]).collect::<Vec<_>>(), vec![&'a']);
+        assert_eq!(trie.matching(&[8]).collect::<Vec<_>>(), vec![&'d', &'e']);
+        assert_eq!(trie.matching(&[8, 9]).collect::<Vec<_>>(), vec![&'b', &'c']);
+        assert_eq!(trie.values().count(), 5);
+    }
+
+    #[test]
+    fn map_stack_overflow() {
+        let mut trie = SetTrie::new();
+        trie.insert(0..1_000_000, 'a');
+        let trie = trie.map(|c| c.to_ascii_uppercase());
+        assert_eq!(trie.values().collect::<Vec<_>>(), vec![&'A']);
+    }
+}
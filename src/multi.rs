@@ -0,0 +1,200 @@
+use crate::subset::{Subset, SubsetsGrouped};
+use crate::superset::{SuperSet, SuperSetsGrouped};
+use crate::values::Values;
+use crate::SetTrie;
+use std::iter::Flatten;
+use std::vec;
+
+/// Runs queries over several tries, such as the shards of a partitioned index, as if they were a
+/// single trie.
+///
+/// Plain queries visit the tries one after the other. The sorted variants merge the sets of all
+/// tries into lexicographic order, reporting for every set the index of the trie it came from.
+///
+/// ```rust
+/// use set_trie::{MultiTrie, SetTrie};
+///
+/// let mut eu = SetTrie::new();
+/// eu.insert(&[1, 3], "eu-1");
+/// let mut us = SetTrie::new();
+/// us.insert(&[1], "us-1");
+/// us.insert(&[3], "us-2");
+///
+/// let shards = MultiTrie::new(vec![&eu, &us]);
+/// assert_eq!(
+///     shards.subsets(&[&1, &3]).collect::<Vec<_>>(),
+///     vec![&"eu-1", &"us-1", &"us-2"]
+/// );
+/// assert_eq!(
+///     shards.subsets_sorted(&[&1, &3]).map(|(shard, _, _)| shard).collect::<Vec<_>>(),
+///     vec![1, 0, 1]
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultiTrie<'a, K, T> {
+    tries: Vec<&'a SetTrie<K, T>>,
+}
+
+impl<'a, K, T> MultiTrie<'a, K, T> {
+    /// Create a new `MultiTrie` over `tries`.
+    pub fn new(tries: impl IntoIterator<Item = &'a SetTrie<K, T>>) -> Self {
+        Self {
+            tries: tries.into_iter().collect(),
+        }
+    }
+
+    /// Adds a trie, which is queried after the ones added before it.
+    pub fn push(&mut self, trie: &'a SetTrie<K, T>) {
+        self.tries.push(trie);
+    }
+
+    /// The queried tries, in order.
+    #[must_use]
+    pub fn tries(&self) -> &[&'a SetTrie<K, T>] {
+        &self.tries
+    }
+}
+
+impl<'a, K, T> MultiTrie<'a, K, T>
+where
+    K: Ord,
+{
+    /// Iterates over all values of the tries, trie by trie. See [`SetTrie::values`].
+    // the queries are collected, so that the iterator does not borrow the `MultiTrie`.
+    #[allow(clippy::needless_collect)]
+    pub fn values(&self) -> Flatten<vec::IntoIter<Values<'a, K, T>>> {
+        let queries: Vec<_> = self.tries.iter().map(|trie| trie.values()).collect();
+        queries.into_iter().flatten()
+    }
+
+    /// Iterates over all values stored under subsets of `keys` in any of the tries, trie by trie.
+    /// See [`SetTrie::subsets`].
+    // the queries are collected, so that the iterator does not borrow the `MultiTrie`.
+    #[allow(clippy::needless_collect)]
+    pub fn subsets<'b>(&self, keys: &'b [K]) -> Flatten<vec::IntoIter<Subset<'a, 'b, K, T>>> {
+        let queries: Vec<_> = self.tries.iter().map(|trie| trie.subsets(keys)).collect();
+        queries.into_iter().flatten()
+    }
+
+    /// Iterates over all values stored under supersets of `keys` in any of the tries, trie by
+    /// trie. See [`SetTrie::supersets`].
+    // the queries are collected, so that the iterator does not borrow the `MultiTrie`.
+    #[allow(clippy::needless_collect)]
+    pub fn supersets<'b>(&self, keys: &'b [K]) -> Flatten<vec::IntoIter<SuperSet<'a, 'b, K, T>>> {
+        let queries: Vec<_> = self.tries.iter().map(|trie| trie.supersets(keys)).collect();
+        queries.into_iter().flatten()
+    }
+
+    /// Iterates over the subsets of `keys` stored in any of the tries in lexicographic order,
+    /// together with the index of their trie and their values. A set stored in several tries is
+    /// yielded once for each, in the order of the tries.
+    #[must_use]
+    pub fn subsets_sorted<'b>(
+        &self,
+        keys: &'b [K],
+    ) -> Merged<'a, K, T, SubsetsGrouped<'a, 'b, K, T>> {
+        Merged::new(self.tries.iter().map(|trie| trie.subsets_grouped(keys)))
+    }
+
+    /// Iterates over the supersets of `keys` stored in any of the tries in lexicographic order,
+    /// together with the index of their trie and their values. A set stored in several tries is
+    /// yielded once for each, in the order of the tries.
+    #[must_use]
+    pub fn supersets_sorted<'b>(
+        &self,
+        keys: &'b [K],
+    ) -> Merged<'a, K, T, SuperSetsGrouped<'a, 'b, K, T>> {
+        Merged::new(self.tries.iter().map(|trie| trie.supersets_grouped(keys)))
+    }
+}
+
+/// Iterator merging the sets yielded by a query on every trie of a [`MultiTrie`] into
+/// lexicographic order.
+#[derive(Debug, Clone)]
+pub struct Merged<'a, K, T, I> {
+    // the next set of every query, which is `None` once the query is exhausted.
+    heads: Vec<Option<(Vec<&'a K>, &'a [T])>>,
+    queries: Vec<I>,
+}
+
+impl<'a, K, T, I> Merged<'a, K, T, I>
+where
+    I: Iterator<Item = (Vec<&'a K>, &'a [T])>,
+{
+    fn new(queries: impl Iterator<Item = I>) -> Self {
+        let mut queries: Vec<I> = queries.collect();
+        let heads = queries.iter_mut().map(Iterator::next).collect();
+        Self { heads, queries }
+    }
+}
+
+impl<'a, K, T, I> Iterator for Merged<'a, K, T, I>
+where
+    K: Ord,
+    I: Iterator<Item = (Vec<&'a K>, &'a [T])>,
+{
+    type Item = (usize, Vec<&'a K>, &'a [T]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut first: Option<(usize, &Vec<&K>)> = None;
+        for (idx, head) in self.heads.iter().enumerate() {
+            if let Some((keys, _)) = head {
+                // the earliest trie wins ties, so sets stored in several tries keep their order.
+                if first.is_none_or(|(_, min)| keys < min) {
+                    first = Some((idx, keys));
+                }
+            }
+        }
+        let (idx, _) = first?;
+        let next = self.queries[idx].next();
+        let (keys, values) = std::mem::replace(&mut self.heads[idx], next)?;
+        Some((idx, keys, values))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MultiTrie, SetTrie};
+
+    #[test]
+    fn multi_trie() {
+        let mut a = SetTrie::new();
+        a.insert(vec![1], 'a');
+        a.insert(vec![1, 2], 'b');
+        a.insert(vec![3], 'c');
+        let mut b = SetTrie::new();
+        b.insert(vec![], 'd');
+        b.insert(vec![1, 2], 'e');
+        b.insert(vec![2], 'f');
+
+        let mut shards = MultiTrie::new(vec![&a]);
+        shards.push(&b);
+        assert_eq!(shards.tries().len(), 2);
+
+        assert_eq!(
+            shards.subsets(&[1, 2]).collect::<Vec<_>>(),
+            vec![&'a', &'b', &'d', &'e', &'f']
+        );
+        assert_eq!(
+            shards.supersets(&[2]).collect::<Vec<_>>(),
+            vec![&'b', &'e', &'f']
+        );
+        assert_eq!(
+            shards.subsets_sorted(&[1, 2]).collect::<Vec<_>>(),
+            vec![
+                (1, vec![], &['d'][..]),
+                (0, vec![&1], &['a'][..]),
+                (0, vec![&1, &2], &['b'][..]),
+                (1, vec![&1, &2], &['e'][..]),
+                (1, vec![&2], &['f'][..]),
+            ]
+        );
+        assert_eq!(
+            shards
+                .supersets_sorted(&[1])
+                .map(|(shard, _, values)| (shard, values))
+                .collect::<Vec<_>>(),
+            vec![(0, &['a'][..]), (0, &['b'][..]), (1, &['e'][..])]
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use crate::subset::Subset;
+use crate::superset::SuperSet;
+use crate::SetTrie;
+use std::iter::FromIterator;
+
+/// A multiset of keys, used to query a [`SetTrie`] of multisets.
+///
+/// A multiset is stored as the set of `(key, i)` pairs for every `i` below the multiplicity of
+/// `key`, so multiset inclusion is plain set inclusion of the encoded pairs.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Multiset<K>(Vec<(K, usize)>);
+
+impl<K> Multiset<K>
+where
+    K: Ord + Clone,
+{
+    /// Creates a multiset from `(key, multiplicity)` pairs. Keys may be repeated, in which case
+    /// their multiplicities are summed.
+    ///
+    /// ```rust
+    /// use set_trie::Multiset;
+    ///
+    /// assert_eq!(Multiset::new(vec![('O', 1), ('H', 1), ('O', 1)]), Multiset::new(vec![('O', 2), ('H', 1)]));
+    /// ```
+    pub fn new(counts: impl IntoIterator<Item = (K, usize)>) -> Self {
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut encoded: Vec<(K, usize)> = vec![];
+        for (key, count) in counts {
+            let from = match encoded.last() {
+                Some((last, i)) if *last == key => i + 1,
+                _ => 0,
+            };
+            encoded.extend((from..from + count).map(|i| (key.clone(), i)));
+        }
+        Self(encoded)
+    }
+}
+
+impl<K> Multiset<K> {
+    /// The encoded `(key, i)` pairs of the multiset, in sorted order.
+    #[must_use]
+    pub fn as_slice(&self) -> &[(K, usize)] {
+        &self.0
+    }
+}
+
+impl<K> FromIterator<K> for Multiset<K>
+where
+    K: Ord + Clone,
+{
+    /// Creates a multiset in which every key occurs as many times as it is yielded.
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        Self::new(iter.into_iter().map(|k| (k, 1)))
+    }
+}
+
+/// A [`SetTrie`] keyed by `(key, i)` pairs stores [multisets](Multiset).
+impl<K, T> SetTrie<(K, usize), T>
+where
+    K: Ord + Clone,
+{
+    /// Inserts the item in the node of the multiset given by `(key, multiplicity)` pairs.
+    ///
+    /// ```rust
+    /// use set_trie::{Multiset, SetTrie};
+    ///
+    /// let mut formulas = SetTrie::new();
+    /// formulas.insert_multiset(vec![("H", 2), ("O", 1)], "water");
+    /// formulas.insert_multiset(vec![("H", 2), ("O", 2)], "hydrogen peroxide");
+    ///
+    /// let query = Multiset::new(vec![("H", 2), ("O", 1), ("Na", 1)]);
+    /// assert_eq!(formulas.sub_multisets(&query).collect::<Vec<_>>(), vec![&"water"]);
+    /// ```
+    pub fn insert_multiset(&mut self, counts: impl IntoIterator<Item = (K, usize)>, item: T) {
+        self.insert(Multiset::new(counts).0, item);
+    }
+
+    /// Iterates over all values of which the multiset is contained in `keys`, taking
+    /// multiplicities into account.
+    #[must_use]
+    pub fn sub_multisets<'a, 'b>(&'a self, keys: &'b Multiset<K>) -> Subset<'a, 'b, (K, usize), T> {
+        self.subsets(&keys.0)
+    }
+
+    /// Iterates over all values of which the multiset contains `keys`, taking multiplicities into
+    /// account.
+    ///
+    /// ```rust
+    /// use set_trie::{Multiset, SetTrie};
+    ///
+    /// let mut formulas = SetTrie::new();
+    /// formulas.insert_multiset(vec![("H", 2), ("O", 1)], "water");
+    /// formulas.insert_multiset(vec![("H", 2), ("O", 2)], "hydrogen peroxide");
+    ///
+    /// let query: Multiset<_> = vec!["O", "O"].into_iter().collect();
+    /// assert_eq!(formulas.super_multisets(&query).collect::<Vec<_>>(), vec![&"hydrogen peroxide"]);
+    /// ```
+    #[must_use]
+    pub fn super_multisets<'a, 'b>(
+        &'a self,
+        keys: &'b Multiset<K>,
+    ) -> SuperSet<'a, 'b, (K, usize), T> {
+        self.supersets(&keys.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Multiset, SetTrie};
+
+    #[test]
+    fn multiset_encoding() {
+        let m = Multiset::new(vec![(2, 1), (1, 2), (2, 1), (3, 0)]);
+        assert_eq!(m.as_slice(), &[(1, 0), (1, 1), (2, 0), (2, 1)]);
+        assert_eq!(m, vec![2, 1, 1, 2].into_iter().collect());
+    }
+
+    #[test]
+    fn multiset_queries() {
+        let mut trie = SetTrie::new();
+        trie.insert_multiset(vec![(1, 1)], 'a');
+        trie.insert_multiset(vec![(1, 2)], 'b');
+        trie.insert_multiset(vec![(1, 1), (2, 1)], 'c');
+        trie.insert_multiset(vec![(1, 3), (2, 1)], 'd');
+
+        let q = Multiset::new(vec![(1, 2), (2, 1)]);
+        assert_eq!(
+            trie.sub_multisets(&q).collect::<Vec<_>>(),
+            vec![&'a', &'b', &'c']
+        );
+        assert_eq!(trie.super_multisets(&q).collect::<Vec<_>>(), vec![&'d']);
+        assert_eq!(
+            trie.super_multisets(&Multiset::new(vec![(1, 1)]))
+                .collect::<Vec<_>>(),
+            vec![&'a', &'b', &'d', &'c']
+        );
+    }
+}
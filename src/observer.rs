@@ -0,0 +1,322 @@
+use crate::{Drain, SetTrie};
+use std::ops::Deref;
+
+/// Callbacks invoked by an [`ObservedSetTrie`] on every mutation, which can be used to keep
+/// secondary indexes and caches in sync with the trie. Both methods default to doing nothing.
+pub trait Observer<K, T> {
+    /// Called after `value` has been inserted under the set `keys`.
+    fn on_insert(&mut self, keys: &[&K], value: &T) {
+        let _ = (keys, value);
+    }
+
+    /// Called after `value` has been removed from the set `keys`. Other values of the set may
+    /// remain, such as after [`ObservedSetTrie::extract_if`].
+    fn on_remove(&mut self, keys: &[&K], value: &T) {
+        let _ = (keys, value);
+    }
+}
+
+/// A [`SetTrie`] which reports every insertion and removal to an [`Observer`]. Queries are
+/// available through `Deref`, mutations only through the wrapper so that none are missed.
+///
+/// The wrapper offers the mutations which insert or remove whole values. Those which change
+/// values in place or hand out mutable access to the trie, such as entries and mutable
+/// iteration, cannot be reported and are only available on the trie returned by
+/// [into parts](Self::into_parts).
+///
+/// ```rust
+/// use set_trie::{ObservedSetTrie, Observer};
+///
+/// #[derive(Default)]
+/// struct Counter(usize);
+///
+/// impl<K, T> Observer<K, T> for Counter {
+///     fn on_insert(&mut self, _: &[&K], _: &T) {
+///         self.0 += 1;
+///     }
+///
+///     fn on_remove(&mut self, _: &[&K], _: &T) {
+///         self.0 -= 1;
+///     }
+/// }
+///
+/// let mut trie = ObservedSetTrie::new(Counter::default());
+/// trie.insert(vec![1, 2], "foo");
+/// trie.insert_many(vec![1], vec!["bar", "baz"]);
+/// trie.remove(&[1, 2]);
+///
+/// assert_eq!(trie.observer().0, 2);
+/// assert_eq!(trie.subsets(&[1, 2]).count(), 2);
+/// ```
+#[derive(Debug, Default)]
+pub struct ObservedSetTrie<K, T, O> {
+    trie: SetTrie<K, T>,
+    observer: O,
+}
+
+impl<K, T, O> ObservedSetTrie<K, T, O> {
+    /// Create a new, empty trie reporting to `observer`.
+    #[must_use]
+    pub const fn new(observer: O) -> Self {
+        Self {
+            trie: SetTrie::new(),
+            observer,
+        }
+    }
+
+    /// Wraps an existing trie, reporting its mutations from now on to `observer`.
+    #[must_use]
+    pub const fn from_parts(trie: SetTrie<K, T>, observer: O) -> Self {
+        Self { trie, observer }
+    }
+
+    /// The observer of the trie.
+    #[must_use]
+    pub const fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    /// Mutably returns the observer of the trie.
+    #[must_use]
+    pub const fn observer_mut(&mut self) -> &mut O {
+        &mut self.observer
+    }
+
+    /// Returns the trie and its observer.
+    pub fn into_parts(self) -> (SetTrie<K, T>, O) {
+        (self.trie, self.observer)
+    }
+}
+
+impl<K, T, O> ObservedSetTrie<K, T, O>
+where
+    K: Ord,
+    O: Observer<K, T>,
+{
+    /// Insert the item in the given node and reports it. Will create the node if needed.
+    pub fn insert(&mut self, keys: impl IntoIterator<Item = K>, item: T)
+    where
+        K: Clone,
+    {
+        self.insert_many(keys, Some(item));
+    }
+
+    /// Inserts multiple items in the given node and reports each of them.
+    pub fn insert_many<IK: IntoIterator<Item = K>, IT: IntoIterator<Item = T>>(
+        &mut self,
+        keys: IK,
+        items: IT,
+    ) where
+        K: Clone,
+    {
+        let mut entry = self.trie.entry(keys).or_create();
+        let from = entry.items().len();
+        entry.update_leaves(|leaves| leaves.extend(items));
+        let path = entry.path();
+        for item in &entry.items()[from..] {
+            self.observer.on_insert(&path, item);
+        }
+    }
+
+    /// Removes the set `keys`, reporting and returning its values if it was stored. See
+    /// [`SetTrie::remove`].
+    pub fn remove(&mut self, keys: &[K]) -> Option<Vec<T>>
+    where
+        K: Clone,
+    {
+        let values = self.trie.remove(keys)?;
+        let path: Vec<&K> = keys.iter().collect();
+        for value in &values {
+            self.observer.on_remove(&path, value);
+        }
+        Some(values)
+    }
+
+    /// Removes the values stored under subsets of `keys`, reporting each of them. See
+    /// [`SetTrie::remove_subsets`].
+    pub fn remove_subsets<F>(&mut self, keys: &[K], mut removed: F) -> usize
+    where
+        K: Clone,
+        F: FnMut(&[K], T),
+    {
+        let observer = &mut self.observer;
+        self.trie.remove_subsets(keys, |path, value| {
+            observer.on_remove(&path.iter().collect::<Vec<_>>(), &value);
+            removed(path, value);
+        })
+    }
+
+    /// Removes the values stored under supersets of `keys`, reporting each of them. See
+    /// [`SetTrie::remove_supersets`].
+    pub fn remove_supersets<F>(&mut self, keys: &[K], mut removed: F) -> usize
+    where
+        K: Clone,
+        F: FnMut(&[K], T),
+    {
+        let observer = &mut self.observer;
+        self.trie.remove_supersets(keys, |path, value| {
+            observer.on_remove(&path.iter().collect::<Vec<_>>(), &value);
+            removed(path, value);
+        })
+    }
+
+    /// Removes the values for which `predicate` returns `true`, reporting each of them. See
+    /// [`SetTrie::extract_if`].
+    pub fn extract_if<F>(&mut self, predicate: F) -> Drain<(Vec<K>, T)>
+    where
+        F: FnMut(&[K], &mut T) -> bool,
+        K: Clone,
+    {
+        let extracted: Vec<_> = self.trie.extract_if(predicate).collect();
+        for (keys, value) in &extracted {
+            self.observer
+                .on_remove(&keys.iter().collect::<Vec<_>>(), value);
+        }
+        Drain::new(extracted)
+    }
+
+    /// Removes every occurrence of `item`, reporting each of them. See
+    /// [`SetTrie::remove_by_value`].
+    pub fn remove_by_value(&mut self, item: &T) -> Vec<(Vec<K>, usize)>
+    where
+        K: Clone,
+        T: PartialEq,
+    {
+        let removed = self.trie.remove_by_value(item);
+        for (keys, count) in &removed {
+            let path: Vec<&K> = keys.iter().collect();
+            for _ in 0..*count {
+                self.observer.on_remove(&path, item);
+            }
+        }
+        removed
+    }
+
+    /// Removes the lexicographically first stored set, reporting its values. See
+    /// [`SetTrie::pop_first`].
+    pub fn pop_first(&mut self) -> Option<(Vec<K>, Vec<T>)>
+    where
+        K: Clone,
+    {
+        let popped = self.trie.pop_first()?;
+        self.report_removed(&popped);
+        Some(popped)
+    }
+
+    /// Removes the lexicographically last stored set, reporting its values. See
+    /// [`SetTrie::pop_last`].
+    pub fn pop_last(&mut self) -> Option<(Vec<K>, Vec<T>)>
+    where
+        K: Clone,
+    {
+        let popped = self.trie.pop_last()?;
+        self.report_removed(&popped);
+        Some(popped)
+    }
+
+    /// Removes the nodes which hold neither values nor children. No values are removed, so
+    /// nothing is reported. See [`SetTrie::vacuum`].
+    pub fn vacuum(&mut self) -> usize
+    where
+        K: Clone,
+    {
+        self.trie.vacuum()
+    }
+
+    fn report_removed(&mut self, (keys, values): &(Vec<K>, Vec<T>)) {
+        let path: Vec<&K> = keys.iter().collect();
+        for value in values {
+            self.observer.on_remove(&path, value);
+        }
+    }
+}
+
+impl<K, T, O> Deref for ObservedSetTrie<K, T, O> {
+    type Target = SetTrie<K, T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.trie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ObservedSetTrie, Observer};
+
+    #[derive(Default)]
+    struct Log(Vec<(Vec<i32>, char, bool)>);
+
+    impl Observer<i32, char> for Log {
+        fn on_insert(&mut self, keys: &[&i32], value: &char) {
+            self.0
+                .push((keys.iter().copied().copied().collect(), *value, true));
+        }
+
+        fn on_remove(&mut self, keys: &[&i32], value: &char) {
+            self.0
+                .push((keys.iter().copied().copied().collect(), *value, false));
+        }
+    }
+
+    #[test]
+    fn observer() {
+        let mut trie = ObservedSetTrie::new(Log::default());
+        trie.insert(vec![1, 2], 'a');
+        trie.insert_many(vec![1, 2], vec!['b', 'c']);
+        trie.insert(vec![3], 'd');
+        assert_eq!(trie.remove(&[1, 2]), Some(vec!['a', 'b', 'c']));
+        assert_eq!(trie.remove(&[1]), None);
+
+        let (trie, log) = trie.into_parts();
+        assert_eq!(trie.values().collect::<Vec<_>>(), vec![&'d']);
+        assert_eq!(
+            log.0,
+            vec![
+                (vec![1, 2], 'a', true),
+                (vec![1, 2], 'b', true),
+                (vec![1, 2], 'c', true),
+                (vec![3], 'd', true),
+                (vec![1, 2], 'a', false),
+                (vec![1, 2], 'b', false),
+                (vec![1, 2], 'c', false),
+            ]
+        );
+    }
+
+    #[test]
+    fn observed_removals() {
+        let mut trie = ObservedSetTrie::new(Log::default());
+        trie.insert_many(vec![1], vec!['a', 'b']);
+        trie.insert_many(vec![1, 2], vec!['c', 'd']);
+        trie.insert(vec![2], 'e');
+        trie.insert(vec![3], 'c');
+        trie.insert(vec![4], 'f');
+        trie.observer_mut().0.clear();
+
+        let extracted: Vec<_> = trie.extract_if(|_, value| *value == 'b').collect();
+        assert_eq!(extracted, vec![(vec![1], 'b')]);
+        assert_eq!(
+            trie.remove_by_value(&'c'),
+            vec![(vec![1, 2], 1), (vec![3], 1)]
+        );
+        assert_eq!(trie.remove_supersets(&[2], |_, _| {}), 2);
+        assert_eq!(trie.remove_subsets(&[1], |_, _| {}), 1);
+        assert_eq!(trie.pop_last(), Some((vec![4], vec!['f'])));
+        assert_eq!(trie.pop_first(), None);
+        assert_eq!(trie.vacuum(), 0);
+        assert_eq!(trie.values().next(), None);
+
+        assert_eq!(
+            trie.observer().0,
+            vec![
+                (vec![1], 'b', false),
+                (vec![1, 2], 'c', false),
+                (vec![3], 'c', false),
+                (vec![1, 2], 'd', false),
+                (vec![2], 'e', false),
+                (vec![1], 'a', false),
+                (vec![4], 'f', false),
+            ]
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Debug, Formatter};
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+/// An ordering of keys, used by [`Ordered`] in place of the natural order of `K`.
+///
+/// The order in which keys are stored determines which keys are checked first by queries;
+/// ordering keys by selectivity can make superset queries considerably faster.
+pub trait KeyOrder<K: ?Sized> {
+    /// Compares two keys.
+    fn cmp(a: &K, b: &K) -> Ordering;
+}
+
+/// Key which is ordered by `O` instead of by its natural order, allowing a
+/// [`SetTrie`](crate::SetTrie) to use a custom key order:
+///
+/// ```rust
+/// use set_trie::{KeyOrder, Ordered, SetTrie};
+/// use std::cmp::Ordering;
+///
+/// // rare tags first
+/// struct ByRarity;
+///
+/// impl KeyOrder<&str> for ByRarity {
+///     fn cmp(a: &&str, b: &&str) -> Ordering {
+///         let rank = |tag: &str| ["archived", "urgent", "email"].iter().position(|t| *t == tag);
+///         rank(a).cmp(&rank(b)).then_with(|| a.cmp(b))
+///     }
+/// }
+///
+/// let mut trie = SetTrie::new();
+/// trie.insert(Ordered::<_, ByRarity>::sorted(vec!["email", "urgent"]), 1);
+/// trie.insert(Ordered::<_, ByRarity>::sorted(vec!["email"]), 2);
+///
+/// let query = Ordered::<_, ByRarity>::sorted(vec!["urgent"]);
+/// assert_eq!(trie.supersets(&query).collect::<Vec<_>>(), vec![&1]);
+/// ```
+pub struct Ordered<K, O> {
+    key: K,
+    order: PhantomData<fn() -> O>,
+}
+
+impl<K, O> Ordered<K, O> {
+    /// Wraps the key.
+    pub const fn new(key: K) -> Self {
+        Self {
+            key,
+            order: PhantomData,
+        }
+    }
+
+    /// Returns the wrapped key.
+    pub fn into_inner(self) -> K {
+        self.key
+    }
+}
+
+impl<K, O> Ordered<K, O>
+where
+    O: KeyOrder<K>,
+{
+    /// Wraps all keys, sorted by `O` and deduplicated, ready to be inserted or used as a query.
+    pub fn sorted(keys: impl IntoIterator<Item = K>) -> Vec<Self> {
+        let mut keys: Vec<Self> = keys.into_iter().map(Self::new).collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+}
+
+impl<K, O> Deref for Ordered<K, O> {
+    type Target = K;
+
+    fn deref(&self) -> &Self::Target {
+        &self.key
+    }
+}
+
+impl<K: Clone, O> Clone for Ordered<K, O> {
+    fn clone(&self) -> Self {
+        Self::new(self.key.clone())
+    }
+}
+
+impl<K: Copy, O> Copy for Ordered<K, O> {}
+
+impl<K: Debug, O> Debug for Ordered<K, O> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.key.fmt(f)
+    }
+}
+
+impl<K, O: KeyOrder<K>> PartialEq for Ordered<K, O> {
+    fn eq(&self, other: &Self) -> bool {
+        O::cmp(&self.key, &other.key) == Ordering::Equal
+    }
+}
+
+impl<K, O: KeyOrder<K>> Eq for Ordered<K, O> {}
+
+impl<K, O: KeyOrder<K>> PartialOrd for Ordered<K, O> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K, O: KeyOrder<K>> Ord for Ordered<K, O> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        O::cmp(&self.key, &other.key)
+    }
+}
+
+/// Orders floating point keys by the IEEE 754 `totalOrder` predicate, see [`f64::total_cmp`].
+///
+/// Every float is equal only to itself, bit for bit: `-0.0` sorts before and differs from `0.0`,
+/// and NaNs are kept rather than rejected, with negative NaNs sorting before `-inf` and positive
+/// NaNs after `inf`. NaNs with different payloads are different keys, so canonicalize NaNs before
+/// inserting them if they should match each other.
+///
+/// ```rust
+/// use set_trie::{OrderedF64, SetTrie};
+///
+/// let mut alerts = SetTrie::new();
+/// alerts.insert(OrderedF64::sorted(vec![0.5, 0.9]), "warn");
+/// alerts.insert(OrderedF64::sorted(vec![0.9, f64::NAN]), "broken");
+///
+/// let query = OrderedF64::sorted(vec![0.9]);
+/// assert_eq!(alerts.supersets(&query).collect::<Vec<_>>(), vec![&"warn", &"broken"]);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TotalOrder;
+
+impl KeyOrder<f64> for TotalOrder {
+    fn cmp(a: &f64, b: &f64) -> Ordering {
+        a.total_cmp(b)
+    }
+}
+
+impl KeyOrder<f32> for TotalOrder {
+    fn cmp(a: &f32, b: &f32) -> Ordering {
+        a.total_cmp(b)
+    }
+}
+
+/// An `f64` key in [total order](TotalOrder).
+pub type OrderedF64 = Ordered<f64, TotalOrder>;
+
+/// An `f32` key in [total order](TotalOrder).
+pub type OrderedF32 = Ordered<f32, TotalOrder>;
+
+#[cfg(test)]
+mod tests {
+    use crate::{KeyOrder, Ordered, OrderedF32, OrderedF64, SetTrie};
+    use std::cmp::Ordering;
+
+    struct Descending;
+
+    impl KeyOrder<i32> for Descending {
+        fn cmp(a: &i32, b: &i32) -> Ordering {
+            b.cmp(a)
+        }
+    }
+
+    type Desc = Ordered<i32, Descending>;
+
+    #[test]
+    fn ordered() {
+        let mut trie = SetTrie::new();
+        trie.insert(Desc::sorted(vec![1, 2, 3]), 'a');
+        trie.insert(Desc::sorted(vec![3]), 'b');
+        trie.insert(Desc::sorted(vec![1]), 'c');
+
+        assert_eq!(
+            trie.display_tree().to_string(),
+            ".\n├── 3 ['b']\n│   └── 2\n│       └── 1 ['a']\n└── 1 ['c']\n"
+        );
+        assert_eq!(
+            trie.subsets(&Desc::sorted(vec![1, 3])).collect::<Vec<_>>(),
+            vec![&'b', &'c']
+        );
+        assert_eq!(
+            trie.supersets(&Desc::sorted(vec![1])).collect::<Vec<_>>(),
+            vec![&'a', &'c']
+        );
+        assert_eq!(*Desc::new(4), 4);
+    }
+
+    #[test]
+    fn total_order() {
+        let mut trie = SetTrie::new();
+        trie.insert(OrderedF64::sorted(vec![f64::NAN, 1.0, -0.0]), 'a');
+        trie.insert(OrderedF64::sorted(vec![0.0, 1.0]), 'b');
+        trie.insert(OrderedF64::sorted(vec![-f64::NAN]), 'c');
+
+        assert_eq!(
+            trie.supersets(&OrderedF64::sorted(vec![1.0]))
+                .collect::<Vec<_>>(),
+            vec![&'a', &'b']
+        );
+        assert_eq!(
+            trie.subsets(&OrderedF64::sorted(vec![0.0, 1.0, f64::NAN]))
+                .collect::<Vec<_>>(),
+            vec![&'b']
+        );
+        assert_eq!(
+            trie.supersets(&OrderedF64::sorted(vec![f64::NAN]))
+                .collect::<Vec<_>>(),
+            vec![&'a']
+        );
+        trie.insert(OrderedF64::sorted(vec![0.9, 0.9]), 'd');
+        assert_eq!(OrderedF64::sorted(vec![0.9, 0.9]).len(), 1);
+        assert_eq!(
+            trie.subsets(&OrderedF64::sorted(vec![0.9]))
+                .collect::<Vec<_>>(),
+            vec![&'d']
+        );
+
+        let (first, _) = trie.first().unwrap();
+        assert!(first[0].is_nan() && first[0].is_sign_negative());
+
+        assert!(OrderedF32::new(-0.0) < OrderedF32::new(0.0));
+    }
+}
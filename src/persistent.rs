@@ -0,0 +1,558 @@
+use crate::SetTrie;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+struct PersistentNode<K, T> {
+    children: Vec<(K, Arc<Self>)>,
+    leaves: Vec<T>,
+}
+
+impl<K, T> PersistentNode<K, T> {
+    const fn new() -> Self {
+        Self {
+            children: vec![],
+            leaves: vec![],
+        }
+    }
+}
+
+impl<K, T> Drop for PersistentNode<K, T> {
+    fn drop(&mut self) {
+        // only the nodes which are not shared with another version are dropped here, the others
+        // merely lose a reference.
+        let mut stack: Vec<Self> = vec![];
+        while let Some((_, child)) = self.children.pop() {
+            if let Ok(child) = Arc::try_unwrap(child) {
+                stack.push(child);
+            }
+            while let Some(mut current) = stack.pop() {
+                while let Some((_, child)) = current.children.pop() {
+                    if let Ok(child) = Arc::try_unwrap(child) {
+                        stack.push(child);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<K, T> PersistentNode<K, T>
+where
+    K: Ord,
+{
+    fn child(&self, key: &K) -> Option<&Self> {
+        self.children
+            .binary_search_by(|(k, _)| k.cmp(key))
+            .ok()
+            .map(|idx| &*self.children[idx].1)
+    }
+}
+
+impl<K, T> PersistentNode<K, T>
+where
+    K: Hash + Eq,
+    T: Hash + Eq,
+{
+    /// Hash of the node, in which the children are identified by their address. Since children
+    /// are canonicalized before their parent, equal subtrees hash equally.
+    fn shallow_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.leaves.hash(&mut hasher);
+        for (key, child) in &self.children {
+            key.hash(&mut hasher);
+            Arc::as_ptr(child).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    fn shallow_eq(&self, other: &Self) -> bool {
+        self.leaves == other.leaves
+            && self.children.len() == other.children.len()
+            && self
+                .children
+                .iter()
+                .zip(&other.children)
+                .all(|((k1, c1), (k2, c2))| k1 == k2 && Arc::ptr_eq(c1, c2))
+    }
+}
+
+/// A node of a [`SetTrie`] which is being minimized, of which the children are moved out and
+/// canonicalized one by one.
+struct Frame<K, T> {
+    key: Option<K>,
+    leaves: Vec<T>,
+    children: crate::IntoChildren<K, T>,
+    done: Vec<(K, Arc<PersistentNode<K, T>>)>,
+}
+
+impl<K, T> Frame<K, T> {
+    fn new(key: Option<K>, node: &mut crate::Node<K, T>) -> Self {
+        Self {
+            key,
+            leaves: mem::take(&mut node.leaves),
+            children: node.take_children(),
+            done: vec![],
+        }
+    }
+}
+
+/// Nodes by their [shallow hash](PersistentNode::shallow_hash).
+type Table<K, T> = HashMap<u64, Vec<Arc<PersistentNode<K, T>>>>;
+
+/// Returns the node equal to `node` from the table, inserting it if there is none.
+fn canonicalize<K, T>(
+    table: &mut Table<K, T>,
+    node: PersistentNode<K, T>,
+) -> Arc<PersistentNode<K, T>>
+where
+    K: Hash + Eq,
+    T: Hash + Eq,
+{
+    let candidates = table.entry(node.shallow_hash()).or_default();
+    let existing = candidates.iter().find(|c| c.shallow_eq(&node)).cloned();
+    existing.unwrap_or_else(|| {
+        let node = Arc::new(node);
+        candidates.push(Arc::clone(&node));
+        node
+    })
+}
+
+impl<K, T> SetTrie<K, T>
+where
+    K: Hash + Eq,
+    T: Hash + Eq,
+{
+    /// Converts the trie into a [`PersistentSetTrie`] in which structurally identical subtrees are
+    /// merged into a single shared node, like a DAWG. Corpora of sets with long common tails take
+    /// up considerably less memory this way.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(vec![1, 5, 6], "foo");
+    /// trie.insert(vec![2, 5, 6], "foo");
+    ///
+    /// let minimized = trie.minimize();
+    /// assert_eq!(minimized.supersets(&[5, 6]).collect::<Vec<_>>(), vec![&"foo", &"foo"]);
+    /// ```
+    #[must_use]
+    pub fn minimize(mut self) -> PersistentSetTrie<K, T> {
+        let mut table = Table::new();
+        let mut root = Frame::new(None, &mut self.root);
+        let mut stack = vec![];
+
+        loop {
+            let frame = stack.last_mut().unwrap_or(&mut root);
+            if let Some((key, mut child)) = frame.children.next() {
+                stack.push(Frame::new(Some(key), &mut child));
+                continue;
+            }
+
+            let Some(frame) = stack.pop() else {
+                break;
+            };
+            let node = PersistentNode {
+                children: frame.done,
+                leaves: frame.leaves,
+            };
+            let node = canonicalize(&mut table, node);
+            if let Some(key) = frame.key {
+                stack.last_mut().unwrap_or(&mut root).done.push((key, node));
+            }
+        }
+
+        let node = PersistentNode {
+            children: root.done,
+            leaves: root.leaves,
+        };
+        PersistentSetTrie(canonicalize(&mut table, node))
+    }
+}
+
+/// A persistent variant of [`SetTrie`](crate::SetTrie).
+///
+/// Inserting returns a new version of the trie which shares all unchanged nodes with the previous
+/// version, so both versions remain usable. Cloning is `O(1)`, which makes it cheap to hand out
+/// consistent snapshots to readers while a writer keeps inserting.
+///
+/// ```rust
+/// use set_trie::PersistentSetTrie;
+///
+/// let v1 = PersistentSetTrie::new().insert(vec![1, 2], "foo");
+/// let v2 = v1.insert(vec![1, 3], "bar");
+///
+/// assert_eq!(v1.supersets(&[1]).collect::<Vec<_>>(), vec![&"foo"]);
+/// assert_eq!(v2.supersets(&[1]).collect::<Vec<_>>(), vec![&"foo", &"bar"]);
+/// ```
+#[derive(Debug)]
+pub struct PersistentSetTrie<K, T>(Arc<PersistentNode<K, T>>);
+
+impl<K, T> Clone for PersistentSetTrie<K, T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<K, T> Default for PersistentSetTrie<K, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, T> PersistentSetTrie<K, T> {
+    /// Create a new, empty `PersistentSetTrie`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self(Arc::new(PersistentNode::new()))
+    }
+
+    /// An immutable view of the current version of the trie, which is unaffected by later
+    /// [in place](Self::insert_mut) modifications. Takes `O(1)`, as all nodes are shared until they
+    /// are modified.
+    ///
+    /// ```rust
+    /// use set_trie::PersistentSetTrie;
+    ///
+    /// let mut trie = PersistentSetTrie::new();
+    /// trie.insert_mut(vec![1, 2], "foo");
+    ///
+    /// let snapshot = trie.snapshot();
+    /// let reader = std::thread::spawn(move || snapshot.supersets(&[1]).count());
+    ///
+    /// trie.insert_mut(vec![1, 3], "bar");
+    /// assert_eq!(trie.remove_mut(&[1, 2]), Some(vec!["foo"]));
+    /// assert_eq!(trie.supersets(&[1]).collect::<Vec<_>>(), vec![&"bar"]);
+    /// assert_eq!(reader.join().unwrap(), 1);
+    /// ```
+    #[must_use]
+    pub fn snapshot(&self) -> Self {
+        self.clone()
+    }
+
+    /// Iterates over all values in the trie using DFS.
+    #[must_use]
+    pub fn values(&self) -> PersistentValues<'_, K, T> {
+        PersistentValues {
+            leaves: [].iter(),
+            next: vec![&self.0],
+        }
+    }
+}
+
+impl<K, T> PersistentSetTrie<K, T>
+where
+    K: Ord + Clone,
+    T: Clone,
+{
+    /// Returns a new version of the trie with the item inserted in the given node. Only the nodes
+    /// along the key path are copied; the rest is shared with `self`.
+    #[must_use]
+    pub fn insert(&self, keys: impl IntoIterator<Item = K>, item: T) -> Self {
+        let keys: Vec<K> = keys.into_iter().collect();
+
+        // copies of the nodes along the key path, excluding the final node.
+        let mut path = Vec::with_capacity(keys.len());
+        let mut current = Some(&*self.0);
+        for key in &keys {
+            path.push(current.map_or_else(PersistentNode::new, Clone::clone));
+            current = current.and_then(|node| node.child(key));
+        }
+
+        let mut node = current.map_or_else(PersistentNode::new, Clone::clone);
+        node.leaves.push(item);
+
+        for (mut parent, key) in path.into_iter().zip(keys).rev() {
+            match parent.children.binary_search_by(|(k, _)| k.cmp(&key)) {
+                Ok(idx) => parent.children[idx].1 = Arc::new(node),
+                Err(idx) => parent.children.insert(idx, (key, Arc::new(node))),
+            }
+            node = parent;
+        }
+        Self(Arc::new(node))
+    }
+
+    /// Inserts the item in the given set in place. Nodes along the key path which are shared with
+    /// a [snapshot](Self::snapshot) are copied, the others are modified directly.
+    pub fn insert_mut(&mut self, keys: impl IntoIterator<Item = K>, item: T) {
+        let mut node = Arc::make_mut(&mut self.0);
+        for key in keys {
+            let idx = match node.children.binary_search_by(|(k, _)| k.cmp(&key)) {
+                Ok(idx) => idx,
+                Err(idx) => {
+                    let child = Arc::new(PersistentNode::new());
+                    node.children.insert(idx, (key, child));
+                    idx
+                }
+            };
+            node = Arc::make_mut(&mut node.children[idx].1);
+        }
+        node.leaves.push(item);
+    }
+
+    /// Removes the set `keys` in place, returning its values if it was stored. Nodes which are
+    /// left without values and children are removed, and nodes shared with a
+    /// [snapshot](Self::snapshot) are copied rather than modified.
+    pub fn remove_mut(&mut self, keys: &[K]) -> Option<Vec<T>> {
+        // the indices of the key path, and the depth of the last node on it which has to be kept
+        // after removing the set.
+        let mut indices = Vec::with_capacity(keys.len());
+        let mut keep = 0;
+        let mut node = &*self.0;
+        for (depth, key) in keys.iter().enumerate() {
+            if !node.leaves.is_empty() || node.children.len() > 1 {
+                keep = depth;
+            }
+            let idx = node.children.binary_search_by(|(k, _)| k.cmp(key)).ok()?;
+            indices.push(idx);
+            node = &node.children[idx].1;
+        }
+        if node.leaves.is_empty() {
+            return None;
+        }
+        let prune = node.children.is_empty() && !keys.is_empty();
+
+        let mut node = Arc::make_mut(&mut self.0);
+        for &idx in &indices {
+            node = Arc::make_mut(&mut node.children[idx].1);
+        }
+        let leaves = mem::take(&mut node.leaves);
+
+        if prune {
+            let mut node = Arc::make_mut(&mut self.0);
+            for &idx in &indices[..keep] {
+                node = Arc::make_mut(&mut node.children[idx].1);
+            }
+            node.children.remove(indices[keep]);
+        }
+        Some(leaves)
+    }
+}
+
+impl<K, T> PersistentSetTrie<K, T>
+where
+    K: Ord,
+{
+    /// Iterates over all subsets of `keys` using DFS. See [`SetTrie::subsets`](crate::SetTrie::subsets).
+    #[must_use]
+    pub fn subsets<'a, 'b>(&'a self, keys: &'b [K]) -> PersistentSubset<'a, 'b, K, T> {
+        PersistentSubset {
+            leaves: [].iter(),
+            next: vec![&self.0],
+            keys,
+        }
+    }
+
+    /// Iterates over all supersets of `keys` using DFS. See
+    /// [`SetTrie::supersets`](crate::SetTrie::supersets).
+    #[must_use]
+    pub fn supersets<'a, 'b>(&'a self, keys: &'b [K]) -> PersistentSuperSet<'a, 'b, K, T> {
+        PersistentSuperSet {
+            leaves: [].iter(),
+            next: vec![(0, &self.0)],
+            keys,
+        }
+    }
+}
+
+/// Iterator for [`PersistentSetTrie::values`].
+#[derive(Debug, Clone)]
+pub struct PersistentValues<'a, K, T> {
+    leaves: std::slice::Iter<'a, T>,
+    next: Vec<&'a PersistentNode<K, T>>,
+}
+
+impl<'a, K, T> Iterator for PersistentValues<'a, K, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some(item);
+            }
+            let node = self.next.pop()?;
+            self.next
+                .extend(node.children.iter().rev().map(|(_, n)| &**n));
+            self.leaves = node.leaves.iter();
+        }
+    }
+}
+
+/// Iterator for [`PersistentSetTrie::subsets`].
+#[derive(Debug, Clone)]
+pub struct PersistentSubset<'a, 'b, K, T> {
+    leaves: std::slice::Iter<'a, T>,
+    next: Vec<&'a PersistentNode<K, T>>,
+    keys: &'b [K],
+}
+
+impl<'a, K, T> Iterator for PersistentSubset<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some(item);
+            }
+            let node = self.next.pop()?;
+            let keys = self.keys;
+            self.next.extend(
+                node.children
+                    .iter()
+                    .rev()
+                    .filter(|(k, _)| keys.binary_search(k).is_ok())
+                    .map(|(_, n)| &**n),
+            );
+            self.leaves = node.leaves.iter();
+        }
+    }
+}
+
+/// Iterator for [`PersistentSetTrie::supersets`].
+#[derive(Debug, Clone)]
+pub struct PersistentSuperSet<'a, 'b, K, T> {
+    leaves: std::slice::Iter<'a, T>,
+    // every node is paired with the number of query keys on its key path.
+    next: Vec<(usize, &'a PersistentNode<K, T>)>,
+    keys: &'b [K],
+}
+
+impl<'a, K, T> Iterator for PersistentSuperSet<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some(item);
+            }
+            let (matched, node) = self.next.pop()?;
+            match self.keys.get(matched) {
+                None => {
+                    self.next
+                        .extend(node.children.iter().rev().map(|(_, n)| (matched, &**n)));
+                    self.leaves = node.leaves.iter();
+                }
+                Some(want) => {
+                    // children with a larger key than the next wanted key can never match.
+                    let end = node.children.partition_point(|(k, _)| k <= want);
+                    self.next
+                        .extend(node.children[..end].iter().rev().map(|(k, n)| {
+                            if k == want {
+                                (matched + 1, &**n)
+                            } else {
+                                (matched, &**n)
+                            }
+                        }));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PersistentSetTrie;
+    use crate::SetTrie;
+    use std::sync::Arc;
+
+    #[test]
+    fn persistent() {
+        let v0 = PersistentSetTrie::new();
+        let v1 = v0.insert(vec![1, 2], 'a');
+        let v2 = v1.insert(vec![1, 3], 'b');
+        let v3 = v2.insert(vec![2, 3], 'c').insert(vec![1, 2], 'd');
+
+        assert_eq!(v0.values().count(), 0);
+        assert_eq!(v1.values().collect::<Vec<_>>(), vec![&'a']);
+        assert_eq!(v2.values().collect::<Vec<_>>(), vec![&'a', &'b']);
+        assert_eq!(
+            v3.values().collect::<Vec<_>>(),
+            vec![&'a', &'d', &'b', &'c']
+        );
+
+        assert_eq!(
+            v3.subsets(&[1, 2, 3]).collect::<Vec<_>>(),
+            vec![&'a', &'d', &'b', &'c']
+        );
+        assert_eq!(v3.subsets(&[1, 3]).collect::<Vec<_>>(), vec![&'b']);
+        assert_eq!(v3.supersets(&[3]).collect::<Vec<_>>(), vec![&'b', &'c']);
+        assert_eq!(v2.supersets(&[2]).collect::<Vec<_>>(), vec![&'a']);
+
+        // the subtree under 1 is untouched by inserting [2, 3].
+        let v4 = v2.insert(vec![2, 3], 'c');
+        assert!(Arc::ptr_eq(&v2.0.children[0].1, &v4.0.children[0].1));
+    }
+
+    #[test]
+    fn snapshot() {
+        let mut trie = PersistentSetTrie::new();
+        trie.insert_mut(vec![1, 2], 'a');
+        trie.insert_mut(vec![1, 2, 3], 'b');
+        trie.insert_mut(vec![2], 'c');
+        let snapshot = trie.snapshot();
+
+        trie.insert_mut(vec![1, 2], 'd');
+        assert_eq!(trie.remove_mut(&[2]), Some(vec!['c']));
+        assert_eq!(trie.remove_mut(&[1, 2, 3]), Some(vec!['b']));
+        assert_eq!(trie.remove_mut(&[1]), None);
+        assert_eq!(trie.remove_mut(&[1, 2, 3]), None);
+        assert_eq!(trie.0.children.len(), 1);
+        assert!(trie.0.children[0].1.children[0].1.children.is_empty());
+
+        assert_eq!(trie.values().collect::<Vec<_>>(), vec![&'a', &'d']);
+        assert_eq!(
+            snapshot.values().collect::<Vec<_>>(),
+            vec![&'a', &'b', &'c']
+        );
+
+        // nodes which are no longer shared are modified in place.
+        let before = Arc::as_ptr(&trie.0);
+        trie.insert_mut(vec![4], 'e');
+        assert_eq!(Arc::as_ptr(&trie.0), before);
+    }
+
+    #[test]
+    fn minimize() {
+        let mut trie = SetTrie::new();
+        trie.insert(vec![1, 5, 6], 'a');
+        trie.insert(vec![2, 5, 6], 'a');
+        trie.insert(vec![3, 5, 6], 'b');
+        trie.insert(vec![4, 6], 'a');
+
+        let dawg = trie.minimize();
+        let children = &dawg.0.children;
+        assert!(Arc::ptr_eq(&children[0].1, &children[1].1));
+        assert!(!Arc::ptr_eq(&children[0].1, &children[2].1));
+        // the [6] tail of [1, 5, 6] and [4, 6] is shared as well.
+        assert!(Arc::ptr_eq(&children[0].1.children[0].1, &children[3].1));
+
+        assert_eq!(
+            dawg.supersets(&[5]).collect::<Vec<_>>(),
+            vec![&'a', &'a', &'b']
+        );
+        assert_eq!(
+            dawg.subsets(&[2, 4, 5, 6]).collect::<Vec<_>>(),
+            vec![&'a', &'a']
+        );
+        let dawg = dawg.insert(vec![1, 5], 'c');
+        assert_eq!(dawg.supersets(&[5]).count(), 4);
+        assert_eq!(dawg.supersets(&[2, 5]).count(), 1);
+    }
+
+    #[test]
+    fn persistent_stack_overflow() {
+        let keys: Vec<_> = (0..1_000_000).collect();
+        let trie = PersistentSetTrie::new().insert(keys, 0);
+        let snapshot = trie.insert(vec![0, 1], 1);
+        drop(trie);
+        assert_eq!(snapshot.values().count(), 2);
+    }
+}
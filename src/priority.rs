@@ -0,0 +1,190 @@
+use crate::{best_by, Node, SetTrie};
+use std::collections::VecDeque;
+
+impl<K, T> SetTrie<K, T>
+where
+    K: Ord,
+{
+    /// Computes the highest priority below every node, so that repeated
+    /// [best subset](Priorities::best_subset) and [best superset](Priorities::best_superset)
+    /// queries skip every branch which cannot hold a value of a higher priority than the best one
+    /// found so far. Every value is prioritized once, in `O(n)`.
+    ///
+    /// ```rust
+    /// let mut rules = set_trie::SetTrie::new();
+    /// rules.insert(&["GET"], ("read", 1));
+    /// rules.insert(&["/admin"], ("deny", 10));
+    /// rules.insert(&["/admin", "GET"], ("admin", 5));
+    ///
+    /// let priorities = rules.priorities(|(_, priority)| *priority);
+    /// assert_eq!(priorities.best_subset(&[&"/admin", &"GET"]), Some(&("deny", 10)));
+    /// assert_eq!(priorities.best_superset(&[&"GET"]), Some(&("admin", 5)));
+    /// ```
+    #[must_use]
+    pub fn priorities<P, F>(&self, priority: F) -> Priorities<'_, K, T, P>
+    where
+        P: Ord,
+        F: FnMut(&T) -> P,
+    {
+        Priorities::new(&self.root, priority)
+    }
+}
+
+/// The priorities of the values of a [`SetTrie`], created by [priorities](SetTrie::priorities),
+/// which answer best match queries by branch and bound.
+///
+/// The nodes are numbered in BFS order, as in [`FrozenSetTrie`](crate::FrozenSetTrie), so that
+/// the children of every node are numbered consecutively. Queries return the same value as
+/// [`SetTrie::best_subset`] and [`SetTrie::best_superset`], ties included: nodes are visited in
+/// the same order, and a branch is only skipped if its highest priority does not exceed the best
+/// one found before it.
+#[derive(Debug, Clone)]
+pub struct Priorities<'a, K, T, P> {
+    root: &'a Node<K, T>,
+    // the highest priority of the values of node `n`, and the index of the first value with it.
+    own: Vec<Option<(P, usize)>>,
+    // the node of which the values have the highest priority below node `n`, if any.
+    bounds: Vec<Option<usize>>,
+    // the first child of node `n`.
+    children: Vec<usize>,
+}
+
+impl<'a, K, T, P> Priorities<'a, K, T, P>
+where
+    K: Ord,
+    P: Ord,
+{
+    fn new(root: &'a Node<K, T>, mut priority: impl FnMut(&T) -> P) -> Self {
+        let mut own = vec![];
+        let mut children = vec![];
+        let mut parents = vec![0];
+        let mut queue = VecDeque::new();
+        queue.push_back(root);
+        while let Some(node) = queue.pop_front() {
+            let id = own.len();
+            children.push(parents.len());
+            for child in &node.children {
+                parents.push(id);
+                queue.push_back(child);
+            }
+            let values = node.leaves.iter().enumerate();
+            own.push(best_by(values, |&(_, value)| priority(value)).map(|(p, (idx, _))| (p, idx)));
+        }
+
+        let mut priorities = Self {
+            root,
+            bounds: (0..own.len())
+                .map(|id| own[id].as_ref().map(|_| id))
+                .collect(),
+            own,
+            children,
+        };
+        // children are numbered after their parents, so every bound is final before it is used.
+        for id in (1..parents.len()).rev() {
+            let parent = parents[id];
+            if priorities.beats(priorities.bounds[id], priorities.bounds[parent]) {
+                priorities.bounds[parent] = priorities.bounds[id];
+            }
+        }
+        priorities
+    }
+
+    /// Whether the values of node `id` have a higher priority than those of node `best`.
+    fn beats(&self, id: Option<usize>, best: Option<usize>) -> bool {
+        let priority = |id: Option<usize>| id.and_then(|id| self.own[id].as_ref());
+        match (priority(id), priority(best)) {
+            (Some((p, _)), Some((max, _))) => p > max,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    /// Adopts the values of node `id` as the best ones, if they have a higher priority.
+    fn visit(&self, id: usize, node: &'a Node<K, T>, best: &mut Option<(usize, &'a T)>) {
+        if self.beats(Some(id), best.map(|(id, _)| id)) {
+            if let Some((_, idx)) = self.own[id] {
+                *best = Some((id, &node.leaves[idx]));
+            }
+        }
+    }
+
+    /// The value stored under a subset of `keys` with the highest priority. See
+    /// [`SetTrie::best_subset`].
+    #[must_use]
+    pub fn best_subset(&self, keys: &[K]) -> Option<&'a T> {
+        let mut best = None;
+        let mut stack = vec![(0, self.root, keys)];
+        while let Some((id, node, rest)) = stack.pop() {
+            if !self.beats(self.bounds[id], best.map(|(id, _)| id)) {
+                continue;
+            }
+            self.visit(id, node, &mut best);
+            for (i, key) in rest.iter().enumerate().rev() {
+                if let Ok(idx) = node.search(key) {
+                    let child = (self.children[id] + idx, &node.children[idx], &rest[i + 1..]);
+                    stack.push(child);
+                }
+            }
+        }
+        best.map(|(_, value)| value)
+    }
+
+    /// The value stored under a superset of `keys` with the highest priority. See
+    /// [`SetTrie::best_superset`].
+    #[must_use]
+    pub fn best_superset(&self, keys: &[K]) -> Option<&'a T> {
+        let mut best = None;
+        // (number of the node, number of query keys on its path, node)
+        let mut stack = vec![(0, 0, self.root)];
+        while let Some((id, matched, node)) = stack.pop() {
+            if !self.beats(self.bounds[id], best.map(|(id, _)| id)) {
+                continue;
+            }
+            if matched == keys.len() {
+                self.visit(id, node, &mut best);
+            }
+            // children past the next key of the query can no longer contain it.
+            let end = keys.get(matched).map_or(node.keys.len(), |next| {
+                node.search(next).map_or_else(|idx| idx, |idx| idx + 1)
+            });
+            for (idx, (key, child)) in node.children().take(end).enumerate().rev() {
+                let matched = matched + usize::from(keys.get(matched) == Some(key));
+                stack.push((self.children[id] + idx, matched, child));
+            }
+        }
+        best.map(|(_, value)| value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SetTrie;
+
+    #[test]
+    fn priorities() {
+        let mut trie = SetTrie::new();
+        trie.insert(vec![1], ('a', 2));
+        trie.insert(vec![1, 2], ('b', 3));
+        trie.insert(vec![2], ('c', 3));
+        trie.insert(vec![1, 2, 3], ('d', 1));
+        trie.insert(vec![1, 3], ('e', 3));
+        trie.insert(vec![], ('f', 0));
+
+        let priority = |(_, p): &(char, u8)| *p;
+        let priorities = trie.priorities(priority);
+        let queries: [&[u32]; 7] = [&[], &[1], &[2], &[3], &[1, 2], &[1, 3], &[4]];
+        for query in queries {
+            assert_eq!(
+                priorities.best_subset(query),
+                trie.best_subset(query, priority)
+            );
+            assert_eq!(
+                priorities.best_superset(query),
+                trie.best_superset(query, priority)
+            );
+        }
+        assert_eq!(priorities.best_subset(&[1, 3]), Some(&('e', 3)));
+        assert_eq!(priorities.best_superset(&[3]), Some(&('e', 3)));
+        assert_eq!(priorities.best_superset(&[4]), None);
+    }
+}
@@ -0,0 +1,217 @@
+use crate::stats::PendingAt;
+use crate::{Node, SetTrie};
+use rand::Rng;
+use std::collections::BTreeSet;
+
+impl<K, T> SetTrie<K, T> {
+    /// Returns `n` distinct values chosen uniformly at random, together with their sets, in DFS
+    /// order. Returns every value if the trie holds no more than `n`.
+    ///
+    /// Every node counts the values below it, so `n` of their positions are drawn and each is
+    /// found by descending from the root into the child holding it. Rather than traversing the
+    /// whole trie, this takes time linear in `n` and in the length and the number of siblings of
+    /// the path of every chosen value.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// for i in 0..100 {
+    ///     trie.insert(vec![i % 7, 10 + i % 3], i);
+    /// }
+    ///
+    /// # struct Counter(u64);
+    /// # impl rand::RngCore for Counter {
+    /// #     fn next_u32(&mut self) -> u32 { self.next_u64() as u32 }
+    /// #     fn next_u64(&mut self) -> u64 { self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1); self.0 >> 11 }
+    /// #     fn fill_bytes(&mut self, dest: &mut [u8]) { rand::RngCore::try_fill_bytes(self, dest).unwrap() }
+    /// #     fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+    /// #         dest.iter_mut().for_each(|byte| *byte = self.next_u64().to_le_bytes()[0]);
+    /// #         Ok(())
+    /// #     }
+    /// # }
+    /// # let mut rng = Counter(7);
+    /// let sample = trie.sample(5, &mut rng);
+    /// assert_eq!(sample.len(), 5);
+    /// for (keys, value) in sample {
+    ///     assert_eq!(keys, vec![&(value % 7), &(10 + value % 3)]);
+    /// }
+    /// ```
+    pub fn sample<R>(&self, n: usize, rng: &mut R) -> Vec<(Vec<&K>, &T)>
+    where
+        R: Rng + ?Sized,
+    {
+        let pending = self.pending.root();
+        let total = pending.apply(self.root.counts).values;
+        let chosen: BTreeSet<usize> = if n >= total {
+            (0..total).collect()
+        } else {
+            // Floyd's algorithm, drawing every position with equal probability.
+            let mut chosen = BTreeSet::new();
+            for upper in total - n..total {
+                let idx = rng.gen_range(0..=upper);
+                if !chosen.insert(idx) {
+                    chosen.insert(upper);
+                }
+            }
+            chosen
+        };
+
+        chosen
+            .into_iter()
+            .map(|idx| nth(&self.root, pending, idx))
+            .collect()
+    }
+}
+
+/// The value at position `idx` among the values below `node` in DFS order, and its key path.
+/// `pending` holds the changes yet to be applied to the counts of `node`.
+fn nth<'a, K, T>(
+    mut node: &'a Node<K, T>,
+    mut pending: PendingAt<'_>,
+    mut idx: usize,
+) -> (Vec<&'a K>, &'a T) {
+    let mut path = vec![];
+    loop {
+        if let Some(item) = node.leaves.get(idx) {
+            return (path, item);
+        }
+        idx -= node.leaves.len();
+        for (child_idx, (key, child)) in node.children().enumerate() {
+            let at = pending.child(child_idx);
+            let values = at.apply(child.counts).values;
+            if idx < values {
+                path.push(key);
+                node = child;
+                pending = at;
+                break;
+            }
+            idx -= values;
+        }
+    }
+}
+
+impl<K, T> SetTrie<K, T>
+where
+    K: Ord,
+{
+    /// Returns `n` distinct values chosen uniformly at random among those stored under subsets
+    /// of `keys`, in no particular order. Returns every match if there are no more than `n`.
+    ///
+    /// The matches are streamed through a reservoir of `n` values, so memory use does not grow
+    /// with the number of matches, although every match is still visited.
+    pub fn subsets_sampled<R>(&self, keys: &[K], n: usize, rng: &mut R) -> Vec<&T>
+    where
+        R: Rng + ?Sized,
+    {
+        reservoir(self.subsets(keys), n, rng)
+    }
+
+    /// Returns `n` distinct values chosen uniformly at random among those stored under supersets
+    /// of `keys`, in no particular order. See [subsets sampled](Self::subsets_sampled).
+    pub fn supersets_sampled<R>(&self, keys: &[K], n: usize, rng: &mut R) -> Vec<&T>
+    where
+        R: Rng + ?Sized,
+    {
+        reservoir(self.supersets(keys), n, rng)
+    }
+}
+
+/// Reservoir sampling: the `i`th item replaces a random item of the reservoir with probability
+/// `n / (i + 1)`, which leaves every item in the reservoir with equal probability.
+fn reservoir<I, R>(items: I, n: usize, rng: &mut R) -> Vec<I::Item>
+where
+    I: Iterator,
+    R: Rng + ?Sized,
+{
+    let mut sample = Vec::new();
+    for (seen, item) in items.enumerate() {
+        if seen < n {
+            sample.push(item);
+        } else {
+            let idx = rng.gen_range(0..=seen);
+            if let Some(slot) = sample.get_mut(idx) {
+                *slot = item;
+            }
+        }
+    }
+    sample
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SetTrie;
+    use rand::RngCore;
+    use std::collections::BTreeSet;
+
+    /// A deterministic generator, as the `rand` dependency comes without one.
+    struct XorShift(u64);
+
+    impl RngCore for XorShift {
+        fn next_u32(&mut self) -> u32 {
+            (self.next_u64() >> 32) as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for byte in dest {
+                *byte = self.next_u64().to_le_bytes()[0];
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn sample() {
+        let mut trie = SetTrie::new();
+        for i in 0..40 {
+            trie.insert(vec![i % 5, 5 + i % 4], i);
+        }
+        trie.insert(vec![], 40);
+        let mut rng = XorShift(0x9E37_79B9_7F4A_7C15);
+
+        let mut hits = [0; 41];
+        for _ in 0..2000 {
+            let sample = trie.sample(10, &mut rng);
+            let values: BTreeSet<_> = sample.iter().map(|(_, &value)| value).collect();
+            assert_eq!(values.len(), 10);
+            for (keys, &value) in sample {
+                if value < 40 {
+                    assert_eq!(keys, vec![&(value % 5), &(5 + value % 4)]);
+                }
+                hits[value] += 1;
+            }
+        }
+        // every value is expected 500 times.
+        assert!(hits.iter().all(|hits| (400..600).contains(hits)));
+
+        assert_eq!(trie.sample(100, &mut rng).len(), 41);
+
+        let mut hits = [0; 41];
+        for _ in 0..2000 {
+            // the 8 values stored under supersets of [1], each expected 1000 times.
+            let sample = trie.supersets_sampled(&[1], 4, &mut rng);
+            assert_eq!(sample.iter().collect::<BTreeSet<_>>().len(), 4);
+            for &value in sample {
+                assert_eq!(value % 5, 1);
+                hits[value] += 1;
+            }
+        }
+        assert!(hits
+            .iter()
+            .all(|hits| *hits == 0 || (900..1100).contains(hits)));
+        assert_eq!(
+            trie.subsets_sampled(&[1, 5], 4, &mut rng),
+            vec![&40, &16, &36]
+        );
+        assert!(SetTrie::<u8, u8>::new().sample(1, &mut rng).is_empty());
+    }
+}
@@ -0,0 +1,224 @@
+use crate::stats::{KeyCounts, PendingCounts};
+use crate::{Node, SetTrie};
+use serde::de::{self, DeserializeSeed, Deserializer, SeqAccess, Unexpected, Visitor};
+use serde::ser::{SerializeSeq, SerializeTuple, Serializer};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Formatter};
+use std::marker::PhantomData;
+
+/// Iterates over the nodes below `root` in pre-order, together with their depth and key.
+fn descendants<K, T>(root: &Node<K, T>) -> impl Iterator<Item = (usize, &K, &Node<K, T>)> {
+    let mut stack: Vec<_> = root.children().rev().map(|(k, n)| (1, k, n)).collect();
+    std::iter::from_fn(move || {
+        let (depth, key, node) = stack.pop()?;
+        stack.extend(node.children().rev().map(|(k, n)| (depth + 1, k, n)));
+        Some((depth, key, node))
+    })
+}
+
+/// Serializes the trie as the pair of the values of the root and the sequence of all other nodes
+/// in pre-order, every node as the triple of its depth, key and values. Neither serializing nor
+/// deserializing recurses per node, so that deep tries do not overflow the stack.
+///
+/// ```rust
+/// use set_trie::SetTrie;
+///
+/// let mut trie = SetTrie::new();
+/// trie.insert(&[1, 2], "foo");
+/// trie.insert(&[], "bar");
+///
+/// let json = serde_json::to_string(&trie).unwrap();
+/// assert_eq!(json, r#"[["bar"],[[1,1,[]],[2,2,["foo"]]]]"#);
+///
+/// let trie: SetTrie<u32, String> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(trie.subsets(&[1, 2]).collect::<Vec<_>>(), vec!["bar", "foo"]);
+/// ```
+impl<K, T> Serialize for SetTrie<K, T>
+where
+    K: Serialize,
+    T: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(2)?;
+        tuple.serialize_element(&self.root.leaves)?;
+        tuple.serialize_element(&Descendants(&self.root))?;
+        tuple.end()
+    }
+}
+
+/// The nodes below a node, serialized in pre-order.
+struct Descendants<'a, K, T>(&'a Node<K, T>);
+
+impl<K, T> Serialize for Descendants<'_, K, T>
+where
+    K: Serialize,
+    T: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(descendants(self.0).count()))?;
+        for (depth, key, node) in descendants(self.0) {
+            seq.serialize_element(&(depth, key, &node.leaves))?;
+        }
+        seq.end()
+    }
+}
+
+/// The trie is deserialized with the settings of [`SetTrie::new`].
+impl<'de, K, T> Deserialize<'de> for SetTrie<K, T>
+where
+    K: Ord + Clone + Deserialize<'de>,
+    T: Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_tuple(2, TrieVisitor(PhantomData))
+    }
+}
+
+struct TrieVisitor<K, T>(PhantomData<(K, T)>);
+
+impl<'de, K, T> Visitor<'de> for TrieVisitor<K, T>
+where
+    K: Ord + Clone + Deserialize<'de>,
+    T: Deserialize<'de>,
+{
+    type Value = SetTrie<K, T>;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("the values of the root and the nodes below it")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let leaves = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let mut root = seq
+            .next_element_seed(DescendantsVisitor(PhantomData))?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        root.leaves = leaves;
+        root.recount();
+        let sets = root.count_sets();
+        Ok(SetTrie {
+            keys: KeyCounts::count(&root),
+            root,
+            prune: false,
+            sets,
+            pending: PendingCounts::new(),
+        })
+    }
+}
+
+/// Builds the nodes below the root from their pre-order sequence.
+struct DescendantsVisitor<K, T>(PhantomData<(K, T)>);
+
+impl<'de, K, T> DeserializeSeed<'de> for DescendantsVisitor<K, T>
+where
+    K: Ord + Deserialize<'de>,
+    T: Deserialize<'de>,
+{
+    type Value = Node<K, T>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, K, T> Visitor<'de> for DescendantsVisitor<K, T>
+where
+    K: Ord + Deserialize<'de>,
+    T: Deserialize<'de>,
+{
+    type Value = Node<K, T>;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence of nodes in pre-order")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut root = Node::new();
+        // the nodes on the path to the last node read, which are attached to their parent once all
+        // of their children have been read.
+        let mut path: Vec<(K, Node<K, T>)> = vec![];
+        while let Some((depth, key, leaves)) = seq.next_element::<(usize, K, Vec<T>)>()? {
+            if depth == 0 || depth > path.len() + 1 {
+                return Err(de::Error::invalid_value(
+                    Unexpected::Unsigned(depth as u64),
+                    &"a depth of at most one more than the depth of the previous node",
+                ));
+            }
+            while path.len() >= depth {
+                close(&mut root, &mut path);
+            }
+            let parent = path.last().map_or(&root, |(_, node)| node);
+            if parent.keys.last().is_some_and(|last| *last >= key) {
+                return Err(de::Error::custom("the keys of siblings are not increasing"));
+            }
+            let mut node = Node::new();
+            node.leaves = leaves;
+            path.push((key, node));
+        }
+        while !path.is_empty() {
+            close(&mut root, &mut path);
+        }
+        Ok(root)
+    }
+}
+
+/// Attaches the last node of `path`, of which all children have been read, to its parent.
+fn close<K, T>(root: &mut Node<K, T>, path: &mut Vec<(K, Node<K, T>)>) {
+    if let Some((key, mut node)) = path.pop() {
+        node.recount();
+        let parent = path.last_mut().map_or(root, |(_, node)| node);
+        parent.keys.push(key);
+        parent.children.push(node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SetTrie;
+
+    #[test]
+    fn round_trip() {
+        let mut trie = SetTrie::new();
+        trie.insert(vec![], 'a');
+        trie.insert(vec![1, 2], 'b');
+        trie.insert(vec![1, 2], 'c');
+        trie.insert(vec![1, 3, 4], 'd');
+        trie.insert(vec![2], 'e');
+
+        let json = serde_json::to_string(&trie).unwrap();
+        let back: SetTrie<u32, char> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.check_invariants(), Ok(()));
+        assert_eq!(
+            back.iter().collect::<Vec<_>>(),
+            trie.iter().collect::<Vec<_>>()
+        );
+
+        let invalid = [
+            "[[], [[2, 1, []]]]",
+            "[[], [[0, 1, []]]]",
+            "[[], [[1, 2, []], [1, 1, []]]]",
+            "[[]]",
+        ];
+        for json in invalid {
+            assert!(serde_json::from_str::<SetTrie<u32, char>>(json).is_err());
+        }
+    }
+
+    #[test]
+    fn round_trip_deep() {
+        let seed = 2_000_000;
+        let mut trie = SetTrie::new();
+
+        let mut current = trie.entry(0..1).or_insert(0);
+        for i in 1..seed {
+            current = current.entry(i - 1..i).or_insert(i);
+        }
+
+        let json = serde_json::to_string(&trie).unwrap();
+        let back: SetTrie<u32, u32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.max_depth(), trie.max_depth());
+        assert_eq!(back.set_count(), trie.set_count());
+        assert_eq!(serde_json::to_string(&back).unwrap(), json);
+    }
+}
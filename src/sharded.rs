@@ -0,0 +1,185 @@
+use crate::subset::Subset;
+use crate::superset::SuperSet;
+use crate::values::Values;
+use crate::{MultiTrie, SetTrie};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::iter::Flatten;
+use std::{thread, vec};
+
+/// A [`SetTrie`] split into a fixed number of shards by the hash of the first key of every set,
+/// so that bulk loads can fill the shards in parallel, one thread per shard and without locking.
+///
+/// Queries fan out to every shard, as a subset or superset of a query may start with any key.
+/// Values are yielded shard by shard, see [`MultiTrie`].
+///
+/// ```rust
+/// use set_trie::ShardedSetTrie;
+///
+/// let mut trie = ShardedSetTrie::new(4);
+/// trie.extend_parallel((0..100).map(|i| (vec![i % 10, 10 + i % 7], i)));
+/// trie.insert(vec![3], 100);
+///
+/// assert_eq!(trie.set_count(), 71);
+/// assert_eq!(trie.subsets(&[3, 13]).count(), 3);
+/// ```
+#[derive(Debug)]
+pub struct ShardedSetTrie<K, T> {
+    shards: Vec<SetTrie<K, T>>,
+}
+
+impl<K, T> ShardedSetTrie<K, T> {
+    /// Create a new, empty trie with the given number of shards, of which there is at least one.
+    #[must_use]
+    pub fn new(shards: usize) -> Self {
+        Self {
+            shards: (0..shards.max(1)).map(|_| SetTrie::new()).collect(),
+        }
+    }
+
+    /// The shards of the trie.
+    #[must_use]
+    pub fn shards(&self) -> &[SetTrie<K, T>] {
+        &self.shards
+    }
+
+    /// Returns the shards of the trie.
+    #[must_use]
+    pub fn into_shards(self) -> Vec<SetTrie<K, T>> {
+        self.shards
+    }
+
+    /// The number of sets holding at least one value, over all shards. See
+    /// [`SetTrie::set_count`].
+    #[must_use]
+    pub fn set_count(&self) -> usize {
+        self.shards.iter().map(SetTrie::set_count).sum()
+    }
+}
+
+impl<K, T> ShardedSetTrie<K, T>
+where
+    K: Ord + Hash,
+{
+    /// The index of the shard holding the set `keys`. The empty set is held by the first shard.
+    #[must_use]
+    pub fn shard_of(&self, keys: &[K]) -> usize {
+        keys.first().map_or(0, |first| {
+            let mut hasher = DefaultHasher::new();
+            first.hash(&mut hasher);
+            // truncating the hash on narrow targets keeps it uniformly distributed.
+            #[allow(clippy::cast_possible_truncation)]
+            let hash = hasher.finish() as usize;
+            hash % self.shards.len()
+        })
+    }
+
+    /// Insert the item in the given set, in the shard of the set. Will create the set if needed.
+    pub fn insert(&mut self, keys: impl IntoIterator<Item = K>, item: T)
+    where
+        K: Clone,
+    {
+        let keys: Vec<K> = keys.into_iter().collect();
+        let shard = self.shard_of(&keys);
+        self.shards[shard].insert(keys, item);
+    }
+
+    /// Removes the set `keys` from its shard, returning its values if it was stored. See
+    /// [`SetTrie::remove`].
+    pub fn remove(&mut self, keys: &[K]) -> Option<Vec<T>>
+    where
+        K: Clone,
+    {
+        let shard = self.shard_of(keys);
+        self.shards[shard].remove(keys)
+    }
+
+    /// Inserts every item in its set, filling the shards in parallel on a thread per shard.
+    pub fn extend_parallel(&mut self, items: impl IntoIterator<Item = (Vec<K>, T)>)
+    where
+        K: Send + Clone,
+        T: Send,
+    {
+        let mut batches: Vec<Vec<(Vec<K>, T)>> = self.shards.iter().map(|_| vec![]).collect();
+        for (keys, item) in items {
+            batches[self.shard_of(&keys)].push((keys, item));
+        }
+
+        thread::scope(|scope| {
+            for (shard, batch) in self.shards.iter_mut().zip(batches) {
+                if !batch.is_empty() {
+                    scope.spawn(move || shard.extend(batch));
+                }
+            }
+        });
+    }
+
+    /// Iterates over all values, shard by shard. See [`SetTrie::values`].
+    pub fn values(&self) -> Flatten<vec::IntoIter<Values<'_, K, T>>> {
+        MultiTrie::new(&self.shards).values()
+    }
+
+    /// Iterates over all values stored under subsets of `keys`, shard by shard. See
+    /// [`SetTrie::subsets`].
+    pub fn subsets<'a, 'b>(
+        &'a self,
+        keys: &'b [K],
+    ) -> Flatten<vec::IntoIter<Subset<'a, 'b, K, T>>> {
+        MultiTrie::new(&self.shards).subsets(keys)
+    }
+
+    /// Iterates over all values stored under supersets of `keys`, shard by shard. See
+    /// [`SetTrie::supersets`].
+    pub fn supersets<'a, 'b>(
+        &'a self,
+        keys: &'b [K],
+    ) -> Flatten<vec::IntoIter<SuperSet<'a, 'b, K, T>>> {
+        MultiTrie::new(&self.shards).supersets(keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{SetTrie, ShardedSetTrie};
+
+    #[test]
+    fn sharded() {
+        let items: Vec<(Vec<u32>, u32)> = (0..500)
+            .map(|i| (vec![i % 13, 13 + i % 5, 20 + i % 3], i))
+            .collect();
+
+        let mut sharded = ShardedSetTrie::new(3);
+        sharded.extend_parallel(items.clone());
+        sharded.insert(vec![], 500);
+        let mut trie: SetTrie<u32, u32> = items.into_iter().collect();
+        trie.insert(vec![], 500);
+
+        assert_eq!(sharded.shards().len(), 3);
+        assert_eq!(sharded.set_count(), trie.set_count());
+        assert_eq!(sharded.shard_of(&[]), 0);
+        for shard in sharded.shards() {
+            assert_eq!(shard.check_invariants(), Ok(()));
+        }
+
+        let sorted = |values: Vec<&u32>| {
+            let mut values: Vec<u32> = values.into_iter().copied().collect();
+            values.sort_unstable();
+            values
+        };
+        let query = [2, 15, 21];
+        assert_eq!(
+            sorted(sharded.subsets(&query).collect()),
+            sorted(trie.subsets(&query).collect())
+        );
+        assert_eq!(
+            sorted(sharded.supersets(&[15]).collect()),
+            sorted(trie.supersets(&[15]).collect())
+        );
+        assert_eq!(sharded.values().count(), 501);
+
+        let keys = [2, 15, 21];
+        assert_eq!(sharded.remove(&keys), trie.remove(&keys));
+        assert_eq!(sharded.remove(&keys), None);
+        assert_eq!(sharded.into_shards().len(), 3);
+    }
+}
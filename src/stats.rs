@@ -0,0 +1,595 @@
+use crate::Node;
+use std::collections::{BTreeMap, TryReserveError};
+#[cfg(feature = "metrics")]
+use std::ops::Add;
+
+/// Work done by a [subset](crate::SetTrie::subsets) or [superset](crate::SetTrie::supersets)
+/// query so far, as returned by the `stats` method of its iterator.
+///
+/// Comparing the number of visited nodes to the number of yielded values shows how much of the
+/// trie a query had to traverse in vain, which helps when tuning the [key order](crate::KeyOrder).
+///
+/// ```rust
+/// use set_trie::SetTrie;
+///
+/// let mut trie = SetTrie::new();
+/// trie.insert(vec![1, 2], 'a');
+/// trie.insert(vec![1, 3], 'b');
+/// trie.insert(vec![2], 'c');
+///
+/// let mut supersets = trie.supersets(&[2]);
+/// assert_eq!(supersets.by_ref().count(), 2);
+///
+/// let stats = supersets.stats();
+/// assert_eq!(stats.nodes_visited, 4);
+/// assert_eq!(stats.leaves_yielded, 2);
+/// ```
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryStats {
+    /// Number of nodes visited, including the root.
+    pub nodes_visited: usize,
+    /// Number of children of visited nodes which were considered for descending into.
+    pub children_scanned: usize,
+    /// Number of values yielded.
+    pub leaves_yielded: usize,
+}
+
+#[cfg(feature = "metrics")]
+impl Add for QueryStats {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            nodes_visited: self.nodes_visited + other.nodes_visited,
+            children_scanned: self.children_scanned + other.children_scanned,
+            leaves_yielded: self.leaves_yielded + other.leaves_yielded,
+        }
+    }
+}
+
+/// Structure and memory use of a [`SetTrie`](crate::SetTrie), as returned by
+/// [`SetTrie::stats`](crate::SetTrie::stats).
+///
+/// The gap between the length and capacity of the children and leaves shows how much memory
+/// [`shrink_to_fit`](crate::SetTrie::shrink_to_fit) would reclaim.
+///
+/// ```rust
+/// let mut trie = set_trie::SetTrie::new();
+/// trie.insert(&[1, 2], 'a');
+/// trie.insert(&[1, 3], 'b');
+///
+/// let stats = trie.stats();
+/// assert_eq!(stats.nodes, 4);
+/// assert_eq!(stats.children_len, 3);
+/// assert_eq!(stats.leaves_len, 2);
+/// assert_eq!(stats.max_depth, 2);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TrieStats {
+    /// Number of nodes, including the root.
+    pub nodes: usize,
+    /// Number of children over all nodes, which is one less than the number of nodes.
+    pub children_len: usize,
+    /// Number of children the nodes have allocated room for.
+    pub children_capacity: usize,
+    /// Number of values over all nodes.
+    pub leaves_len: usize,
+    /// Number of values the nodes have allocated room for.
+    pub leaves_capacity: usize,
+    /// Length of the longest key path.
+    pub max_depth: usize,
+}
+
+/// The number of nodes holding values, in total and by the length of their key path, which a
+/// [`SetTrie`](crate::SetTrie) keeps up to date on every update.
+#[allow(clippy::redundant_pub_crate)]
+#[derive(Debug, Default)]
+pub(crate) struct SetCounts {
+    total: usize,
+    // the number of nodes for every length, without trailing zeros.
+    by_len: Vec<usize>,
+}
+
+impl SetCounts {
+    pub(crate) const fn new() -> Self {
+        Self {
+            total: 0,
+            by_len: Vec::new(),
+        }
+    }
+
+    pub(crate) const fn total(&self) -> usize {
+        self.total
+    }
+
+    /// The length of the longest key path of a node holding values, or 0 if there is none.
+    pub(crate) const fn max_len(&self) -> usize {
+        self.by_len.len().saturating_sub(1)
+    }
+
+    pub(crate) fn by_len(&self) -> &[usize] {
+        &self.by_len
+    }
+
+    /// The first length of which the number of sets differs from `actual`, together with both
+    /// numbers.
+    pub(crate) fn mismatch(&self, actual: &Self) -> Option<(usize, usize, usize)> {
+        let len = self.by_len.len().max(actual.by_len.len());
+        (0..len)
+            .map(|len| {
+                let count = |sets: &Self| sets.by_len.get(len).copied().unwrap_or(0);
+                (len, count(self), count(actual))
+            })
+            .find(|(_, counted, actual)| counted != actual)
+    }
+
+    pub(crate) fn add(&mut self, len: usize) {
+        if self.by_len.len() <= len {
+            self.by_len.resize(len + 1, 0);
+        }
+        self.by_len[len] += 1;
+        self.total += 1;
+    }
+
+    /// Reserves room for counting sets of length `len`, so that [add](Self::add) does not
+    /// allocate.
+    pub(crate) fn try_reserve(&mut self, len: usize) -> Result<(), TryReserveError> {
+        let additional = (len + 1).saturating_sub(self.by_len.len());
+        self.by_len.try_reserve(additional)
+    }
+
+    pub(crate) fn remove(&mut self, len: usize) {
+        self.by_len[len] -= 1;
+        self.total -= 1;
+        while self.by_len.last() == Some(&0) {
+            self.by_len.pop();
+        }
+    }
+
+    /// Removes the nodes counted by `other`, such as those of a subtree which has been moved out.
+    pub(crate) fn remove_all(&mut self, other: &Self) {
+        for (len, &count) in other.by_len.iter().enumerate() {
+            self.by_len[len] -= count;
+        }
+        self.total -= other.total;
+        while self.by_len.last() == Some(&0) {
+            self.by_len.pop();
+        }
+    }
+
+    /// Applies `f` to the values of a node with a key path of length `len`, counting the node when
+    /// it gains its first value, or no longer counting it when it loses its last one.
+    pub(crate) fn update<T, R>(
+        &mut self,
+        len: usize,
+        leaves: &mut Vec<T>,
+        f: impl FnOnce(&mut Vec<T>) -> R,
+    ) -> R {
+        let had = !leaves.is_empty();
+        let result = f(leaves);
+        match (had, leaves.is_empty()) {
+            (false, false) => self.add(len),
+            (true, true) => self.remove(len),
+            _ => {}
+        }
+        result
+    }
+}
+
+/// The number of nodes of every key below which values are stored, which a
+/// [`SetTrie`](crate::SetTrie) keeps up to date on every update, so that whether any stored set
+/// contains a key is a lookup. The counts follow the [counts](SubtreeCounts) the nodes keep, so
+/// those of the nodes with [pending](PendingCounts) changes are yet to be updated.
+///
+/// Nodes rather than sets are counted, so that adding a set only changes the counts of the nodes
+/// which held no values below them before.
+#[allow(clippy::redundant_pub_crate)]
+#[derive(Debug)]
+pub(crate) struct KeyCounts<K>(BTreeMap<K, usize>);
+
+impl<K> KeyCounts<K> {
+    pub(crate) const fn new() -> Self {
+        Self(BTreeMap::new())
+    }
+
+    /// The number of nodes of `key` below which values are stored.
+    pub(crate) fn get(&self, key: &K) -> usize
+    where
+        K: Ord,
+    {
+        self.0.get(key).copied().unwrap_or(0)
+    }
+}
+
+impl<K> Default for KeyCounts<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K> KeyCounts<K>
+where
+    K: Ord + Clone,
+{
+    /// Counts the node `key` when its counts gain their first value, or no longer counts it when
+    /// they lose their last one.
+    pub(crate) fn update(&mut self, key: &K, before: SubtreeCounts, after: SubtreeCounts) {
+        match (before.values > 0, after.values > 0) {
+            (false, true) => *self.0.entry(key.clone()).or_insert(0) += 1,
+            (true, false) => {
+                if let Some(count) = self.0.get_mut(key) {
+                    *count -= 1;
+                    if *count == 0 {
+                        self.0.remove(key);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Counts the nodes below `node` from the counts they keep. Every node is visited, as those
+    /// with pending changes may keep no values above descendants holding some.
+    pub(crate) fn count<T>(node: &Node<K, T>) -> Self {
+        let mut keys = Self::new();
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            for (key, child) in node.children() {
+                keys.update(key, SubtreeCounts::new(), child.counts);
+                stack.push(child);
+            }
+        }
+        keys
+    }
+
+    /// The first key of which the number of nodes differs from `actual`, together with both
+    /// numbers.
+    pub(crate) fn mismatch(&self, actual: &Self) -> Option<(K, usize, usize)> {
+        self.0
+            .keys()
+            .chain(actual.0.keys())
+            .map(|key| (key, self.get(key), actual.get(key)))
+            .find(|(_, counted, actual)| counted != actual)
+            .map(|(key, counted, actual)| (key.clone(), counted, actual))
+    }
+}
+
+/// The number of values, and of nodes holding values, of a node and its descendants, which every
+/// node keeps up to date. Changes are passed around as the wrapping difference of two counts, so
+/// that both additions and removals are applied with [add](Self::add).
+///
+/// The height is only ever [raised](Self::raise), as lowering it after removing the longest set
+/// below a node would require visiting the siblings of every node on its path. It is exact once
+/// the node is recounted, and an upper bound otherwise.
+#[allow(clippy::redundant_pub_crate)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SubtreeCounts {
+    pub(crate) values: usize,
+    pub(crate) sets: usize,
+    // the number of keys the longest set below the node has in addition to those of the node.
+    pub(crate) height: usize,
+}
+
+impl SubtreeCounts {
+    pub(crate) const fn new() -> Self {
+        Self {
+            values: 0,
+            sets: 0,
+            height: 0,
+        }
+    }
+
+    /// The counts of a single node holding `values` values.
+    pub(crate) fn of(values: usize) -> Self {
+        Self {
+            values,
+            sets: usize::from(values > 0),
+            height: 0,
+        }
+    }
+
+    /// Adds the values and sets of `other`, and raises the height to that of `other`.
+    pub(crate) const fn add(&mut self, other: Self) {
+        self.values = self.values.wrapping_add(other.values);
+        self.sets = self.sets.wrapping_add(other.sets);
+        self.raise(other.height);
+    }
+
+    /// Raises the height to at least `height`.
+    pub(crate) const fn raise(&mut self, height: usize) {
+        if height > self.height {
+            self.height = height;
+        }
+    }
+
+    /// The counts of a child as seen from its parent, which is one key above its sets.
+    pub(crate) const fn above(self) -> Self {
+        Self {
+            height: if self.sets > 0 { self.height + 1 } else { 0 },
+            ..self
+        }
+    }
+
+    /// Whether these counts are those of `exact`, with a height of at least that of `exact`.
+    pub(crate) const fn bounds(self, exact: Self) -> bool {
+        self.values == exact.values && self.sets == exact.sets && self.height >= exact.height
+    }
+
+    /// The difference in values and sets which turns `self` into `to`, to be [added](Self::add)
+    /// to other counts.
+    pub(crate) const fn diff(self, to: Self) -> Self {
+        Self {
+            values: to.values.wrapping_sub(self.values),
+            sets: to.sets.wrapping_sub(self.sets),
+            height: 0,
+        }
+    }
+
+    /// The difference which removes `self` from other counts.
+    pub(crate) const fn negate(self) -> Self {
+        self.diff(Self::new())
+    }
+}
+
+/// The changes to the counts of the ancestors of the node last updated through an
+/// [entry](crate::Entry), which are yet to be applied.
+///
+/// An entry keeps the counts of its own node up to date, but leaves those of the nodes above it
+/// to the trie, which applies them before the next change to its shape. A chain of entries which
+/// each descend one level and insert a value thus takes linear rather than quadratic time. Until
+/// then, reads of the counts add the changes yet to be applied through [`PendingAt`].
+///
+/// The heights are raised by the length of the longest set given values through an entry, which
+/// may lie below a branch the path has since left. The heights of the nodes of the path are thus
+/// upper bounds, as they are for removals.
+#[allow(clippy::redundant_pub_crate)]
+#[derive(Debug, Default)]
+pub(crate) struct PendingCounts {
+    // the index of the child taken at every depth, from the root to the node last updated.
+    path: Vec<usize>,
+    // `diffs[i]` is yet to be added to the counts of the nodes at depth `0..=i` of the path.
+    diffs: Vec<SubtreeCounts>,
+    // the length of the longest set given values since the path was last flushed.
+    longest: usize,
+}
+
+impl PendingCounts {
+    pub(crate) const fn new() -> Self {
+        Self {
+            path: Vec::new(),
+            diffs: Vec::new(),
+            longest: 0,
+        }
+    }
+
+    /// Descends from the last node of the path into its child at `idx`.
+    pub(crate) fn push(&mut self, idx: usize) {
+        self.path.push(idx);
+        self.diffs.push(SubtreeCounts::new());
+    }
+
+    /// Adds `diff` to the counts of the nodes of the path above `depth`.
+    pub(crate) fn add_above(&mut self, depth: usize, diff: SubtreeCounts) {
+        if let Some(last) = depth.checked_sub(1) {
+            self.diffs[last].add(diff);
+        }
+    }
+
+    /// Raises the heights of the nodes of the path above the set of length `len`, which was given
+    /// values.
+    pub(crate) fn lengthen(&mut self, len: usize) {
+        self.longest = self.longest.max(len);
+    }
+
+    /// Applies the changes to `node`, the node `key` at `depth` of the path, and to the nodes below
+    /// it, and ends the path at `node`. The changes to the nodes above it remain pending.
+    pub(crate) fn truncate<K, T>(
+        &mut self,
+        depth: usize,
+        key: Option<&K>,
+        node: &mut Node<K, T>,
+        keys: &mut KeyCounts<K>,
+    ) where
+        K: Ord + Clone,
+    {
+        let mut total = SubtreeCounts::new();
+        for diff in &self.diffs[depth..] {
+            total.add(*diff);
+        }
+
+        let mut node = node;
+        let mut rest = total;
+        let before = node.counts;
+        node.counts.add(rest);
+        node.counts.raise(self.longest.saturating_sub(depth));
+        if let Some(key) = key {
+            keys.update(key, before, node.counts);
+        }
+        for (at, (&idx, diff)) in self.path[depth..]
+            .iter()
+            .zip(&self.diffs[depth..])
+            .enumerate()
+        {
+            rest.add(diff.negate());
+            let Node {
+                keys: child_keys,
+                children,
+                ..
+            } = node;
+            node = &mut children[idx];
+            let before = node.counts;
+            node.counts.add(rest);
+            node.counts
+                .raise(self.longest.saturating_sub(depth + at + 1));
+            keys.update(&child_keys[idx], before, node.counts);
+        }
+
+        self.path.truncate(depth);
+        self.diffs.truncate(depth);
+        self.add_above(depth, total);
+        if depth == 0 {
+            self.longest = 0;
+        }
+    }
+
+    /// Applies all changes to the nodes below and including `root`.
+    pub(crate) fn flush<K, T>(&mut self, root: &mut Node<K, T>, keys: &mut KeyCounts<K>)
+    where
+        K: Ord + Clone,
+    {
+        self.truncate(0, None, root, keys);
+    }
+
+    /// The index of the child taken at every depth, from the root to the node last updated.
+    pub(crate) fn path(&self) -> &[usize] {
+        &self.path
+    }
+
+    /// The changes yet to be applied to the root.
+    pub(crate) fn root(&self) -> PendingAt<'_> {
+        let mut total = SubtreeCounts::new();
+        for diff in &self.diffs {
+            total.add(*diff);
+        }
+        PendingAt {
+            pending: self,
+            depth: 0,
+            diff: Some(total),
+        }
+    }
+}
+
+/// The changes yet to be applied to the counts of a node, found by descending from the
+/// [root](PendingCounts::root).
+#[allow(clippy::redundant_pub_crate)]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PendingAt<'a> {
+    pending: &'a PendingCounts,
+    depth: usize,
+    // `None` once the descent has left the path.
+    diff: Option<SubtreeCounts>,
+}
+
+impl PendingAt<'_> {
+    /// The counts of the node, given the counts it keeps.
+    pub(crate) const fn apply(self, mut counts: SubtreeCounts) -> SubtreeCounts {
+        if let Some(diff) = self.diff {
+            counts.add(diff);
+            counts.raise(self.pending.longest.saturating_sub(self.depth));
+        }
+        counts
+    }
+
+    /// The changes yet to be applied to the child at `idx`.
+    pub(crate) fn child(self, idx: usize) -> Self {
+        let on_path = self.pending.path.get(self.depth) == Some(&idx);
+        Self {
+            pending: self.pending,
+            depth: self.depth + 1,
+            diff: self.diff.filter(|_| on_path).map(|mut diff| {
+                diff.add(self.pending.diffs[self.depth].negate());
+                diff
+            }),
+        }
+    }
+}
+
+/// Records [`QueryStats`] if the `metrics` feature is enabled, and does nothing otherwise.
+#[allow(clippy::redundant_pub_crate)]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Counters {
+    #[cfg(feature = "metrics")]
+    stats: QueryStats,
+}
+
+#[allow(
+    clippy::unused_self,
+    clippy::missing_const_for_fn,
+    clippy::needless_pass_by_ref_mut
+)]
+impl Counters {
+    pub(crate) const fn new() -> Self {
+        Self {
+            #[cfg(feature = "metrics")]
+            stats: QueryStats {
+                nodes_visited: 0,
+                children_scanned: 0,
+                leaves_yielded: 0,
+            },
+        }
+    }
+
+    /// Counters of a query which starts out by visiting the root.
+    pub(crate) const fn from_root() -> Self {
+        Self {
+            #[cfg(feature = "metrics")]
+            stats: QueryStats {
+                nodes_visited: 1,
+                children_scanned: 0,
+                leaves_yielded: 0,
+            },
+        }
+    }
+
+    pub(crate) fn visit(&mut self) {
+        #[cfg(feature = "metrics")]
+        {
+            self.stats.nodes_visited += 1;
+        }
+    }
+
+    pub(crate) fn scan(&mut self, children: usize) {
+        #[cfg(feature = "metrics")]
+        {
+            self.stats.children_scanned += children;
+        }
+        #[cfg(not(feature = "metrics"))]
+        let _ = children;
+    }
+
+    pub(crate) fn yielded(&mut self) {
+        #[cfg(feature = "metrics")]
+        {
+            self.stats.leaves_yielded += 1;
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    pub(crate) const fn stats(self) -> QueryStats {
+        self.stats
+    }
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use crate::SetTrie;
+
+    #[test]
+    fn stats() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[1, 2, 3], 'a');
+        trie.insert(&[1, 4], 'b');
+        trie.insert(&[2, 3], 'c');
+        trie.insert(&[5], 'd');
+
+        let mut subsets = trie.subsets(&[&1, &2, &3]);
+        assert_eq!(subsets.by_ref().count(), 2);
+        let stats = subsets.stats();
+        // the root, 1, 1-2, 1-2-3, 2, 2-3
+        assert_eq!(stats.nodes_visited, 6);
+        // 1, 2 below the root; 2 below 1; 3 below 1-2; 3 below 2
+        assert_eq!(stats.children_scanned, 5);
+        assert_eq!(stats.leaves_yielded, 2);
+
+        let mut supersets = trie.supersets(&[&3]);
+        assert_eq!(supersets.by_ref().count(), 2);
+        assert_eq!(supersets.stats().leaves_yielded, 2);
+
+        // the fast paths are accounted for as well.
+        let mut all = trie.supersets(&[]);
+        assert_eq!(all.by_ref().count(), 4);
+        assert_eq!(all.stats().nodes_visited, 8);
+        assert_eq!(trie.subsets(&[]).stats().nodes_visited, 1);
+    }
+}
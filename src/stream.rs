@@ -0,0 +1,122 @@
+use crate::subset::Subset;
+use crate::superset::SuperSet;
+use crate::values::Values;
+use futures_core::Stream;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// [`Stream`] over the results of a query, in chunks of values.
+///
+/// Control is handed back to the executor after every chunk, so that huge result sets do not
+/// block other tasks. The stream borrows the trie, which can therefore not be modified while it is
+/// being streamed.
+///
+/// Created by the `into_stream` method of [`Subset`], [`SuperSet`] and [`Values`].
+#[derive(Debug, Clone)]
+pub struct QueryStream<I> {
+    iter: I,
+    chunk: usize,
+    // values yielded since control was last handed back.
+    yielded: usize,
+}
+
+impl<I> QueryStream<I> {
+    fn new(iter: I, chunk: usize) -> Self {
+        Self {
+            iter,
+            chunk: chunk.max(1),
+            yielded: 0,
+        }
+    }
+
+    /// Returns the underlying query iterator.
+    pub fn into_inner(self) -> I {
+        self.iter
+    }
+}
+
+impl<I> Stream for QueryStream<I>
+where
+    I: Iterator + Unpin,
+{
+    type Item = I::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.yielded == self.chunk {
+            self.yielded = 0;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+
+        self.yielded += 1;
+        Poll::Ready(self.iter.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<K: Ord, T> Subset<'_, '_, K, T> {
+    /// Streams the subsets, yielding to the executor after every `chunk` values.
+    #[must_use]
+    pub fn into_stream(self, chunk: usize) -> QueryStream<Self> {
+        QueryStream::new(self, chunk)
+    }
+}
+
+impl<K: Ord, T> SuperSet<'_, '_, K, T> {
+    /// Streams the supersets, yielding to the executor after every `chunk` values.
+    #[must_use]
+    pub fn into_stream(self, chunk: usize) -> QueryStream<Self> {
+        QueryStream::new(self, chunk)
+    }
+}
+
+impl<K, T> Values<'_, K, T> {
+    /// Streams the values, yielding to the executor after every `chunk` values.
+    #[must_use]
+    pub fn into_stream(self, chunk: usize) -> QueryStream<Self> {
+        QueryStream::new(self, chunk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SetTrie;
+    use futures_core::Stream;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+
+    struct Noop;
+
+    impl Wake for Noop {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    #[test]
+    fn into_stream() {
+        let mut trie = SetTrie::new();
+        for i in 0..5 {
+            trie.insert(vec![i], i);
+        }
+
+        let waker = Waker::from(Arc::new(Noop));
+        let mut cx = Context::from_waker(&waker);
+        let mut stream = trie.supersets(&[]).into_stream(2);
+
+        let mut polls = vec![];
+        loop {
+            match Pin::new(&mut stream).poll_next(&mut cx) {
+                Poll::Ready(Some(item)) => polls.push(Some(*item)),
+                Poll::Ready(None) => break,
+                Poll::Pending => polls.push(None),
+            }
+        }
+        assert_eq!(
+            polls,
+            vec![Some(0), Some(1), None, Some(2), Some(3), None, Some(4)]
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{BiSetTrie, Observer};
+use std::collections::vec_deque::{self, VecDeque};
+
+/// Identifies a standing query registered with [`Subscriptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SubscriptionId(usize);
+
+/// An insertion which matched a standing query, queued by [`Subscriptions`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification<K, T> {
+    /// The matched subscription.
+    pub subscription: SubscriptionId,
+    /// The set the value was inserted under.
+    pub keys: Vec<K>,
+    /// The inserted value.
+    pub value: T,
+}
+
+/// Standing subset and superset queries, which are matched against every inserted set instead of
+/// being re-run after each insertion.
+///
+/// The subscriptions are themselves stored in set tries, so an insertion only visits the
+/// subscriptions it may match.
+///
+/// `Subscriptions` is an [`Observer`]: wrapped in an [`ObservedSetTrie`](crate::ObservedSetTrie),
+/// it queues a [`Notification`] for every match, to be [polled](Self::poll) later. Observers which
+/// need to act on matches right away can use [matching](Self::matching) instead.
+///
+/// ```rust
+/// use set_trie::{ObservedSetTrie, Subscriptions};
+///
+/// let mut trie = ObservedSetTrie::new(Subscriptions::new());
+/// let rust = trie.observer_mut().subscribe_supersets(vec!["rust"]);
+/// let basics = trie.observer_mut().subscribe_subsets(vec!["rust", "trie"]);
+///
+/// trie.insert(vec!["rust", "wasm"], "post-1");
+/// trie.insert(vec!["trie"], "post-2");
+///
+/// let matched = trie
+///     .observer_mut()
+///     .drain()
+///     .map(|notification| (notification.subscription, notification.value))
+///     .collect::<Vec<_>>();
+/// assert_eq!(matched, vec![(rust, "post-1"), (basics, "post-2")]);
+/// ```
+#[derive(Debug)]
+pub struct Subscriptions<K, T> {
+    // subscriptions matching inserted sets which contain their keys.
+    supersets: BiSetTrie<K, SubscriptionId>,
+    // subscriptions matching inserted sets which are contained in their keys.
+    subsets: BiSetTrie<K, SubscriptionId>,
+    next: usize,
+    pending: VecDeque<Notification<K, T>>,
+}
+
+impl<K, T> Default for Subscriptions<K, T> {
+    fn default() -> Self {
+        Self {
+            supersets: BiSetTrie::new(),
+            subsets: BiSetTrie::new(),
+            next: 0,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl<K, T> Subscriptions<K, T> {
+    /// Create a new `Subscriptions` without any subscriptions.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of queued notifications.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Whether no notifications are queued.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Takes the oldest queued notification.
+    pub fn poll(&mut self) -> Option<Notification<K, T>> {
+        self.pending.pop_front()
+    }
+
+    /// Takes all queued notifications, oldest first.
+    pub fn drain(&mut self) -> vec_deque::Drain<'_, Notification<K, T>> {
+        self.pending.drain(..)
+    }
+
+    const fn next_id(&mut self) -> SubscriptionId {
+        let id = SubscriptionId(self.next);
+        self.next += 1;
+        id
+    }
+}
+
+impl<K, T> Subscriptions<K, T>
+where
+    K: Ord + Clone,
+{
+    /// Subscribes to insertions into supersets of `keys`.
+    pub fn subscribe_supersets(&mut self, keys: impl IntoIterator<Item = K>) -> SubscriptionId {
+        let id = self.next_id();
+        self.supersets.insert(keys, id);
+        id
+    }
+
+    /// Subscribes to insertions into subsets of `keys`.
+    pub fn subscribe_subsets(&mut self, keys: impl IntoIterator<Item = K>) -> SubscriptionId {
+        let id = self.next_id();
+        self.subsets.insert(keys, id);
+        id
+    }
+
+    /// Removes the subscription, returning whether it was registered. Queued notifications of the
+    /// subscription are kept.
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        self.supersets.remove_value(&id) + self.subsets.remove_value(&id) > 0
+    }
+
+    /// The subscriptions matched by an insertion into the set `keys`, in order of subscription.
+    #[must_use]
+    pub fn matching(&self, keys: &[K]) -> Vec<SubscriptionId> {
+        let mut ids: Vec<SubscriptionId> = self
+            .supersets
+            .subsets(keys)
+            .chain(self.subsets.supersets(keys))
+            .copied()
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+}
+
+impl<K, T> Observer<K, T> for Subscriptions<K, T>
+where
+    K: Ord + Clone,
+    T: Clone,
+{
+    fn on_insert(&mut self, keys: &[&K], value: &T) {
+        let keys: Vec<K> = keys.iter().map(|&key| key.clone()).collect();
+        for subscription in self.matching(&keys) {
+            self.pending.push_back(Notification {
+                subscription,
+                keys: keys.clone(),
+                value: value.clone(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ObservedSetTrie, Subscriptions};
+
+    #[test]
+    fn subscriptions() {
+        let mut trie = ObservedSetTrie::new(Subscriptions::new());
+        let a = trie.observer_mut().subscribe_supersets(vec![1]);
+        let b = trie.observer_mut().subscribe_supersets(vec![1, 2]);
+        let c = trie.observer_mut().subscribe_subsets(vec![1, 2]);
+        let d = trie.observer_mut().subscribe_supersets(vec![]);
+
+        trie.insert(vec![1, 2], 'a');
+        assert_eq!(trie.observer().matching(&[1, 2]), vec![a, b, c, d]);
+        assert_eq!(trie.observer().len(), 4);
+        trie.observer_mut().drain();
+
+        trie.insert(vec![2], 'b');
+        trie.insert_many(vec![1, 3], vec!['c', 'd']);
+        let notified = |trie: &mut ObservedSetTrie<i32, char, Subscriptions<i32, char>>| {
+            std::iter::from_fn(|| trie.observer_mut().poll())
+                .map(|n| (n.subscription, n.keys, n.value))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            notified(&mut trie),
+            vec![
+                (c, vec![2], 'b'),
+                (d, vec![2], 'b'),
+                (a, vec![1, 3], 'c'),
+                (d, vec![1, 3], 'c'),
+                (a, vec![1, 3], 'd'),
+                (d, vec![1, 3], 'd'),
+            ]
+        );
+
+        assert!(trie.observer_mut().unsubscribe(d));
+        assert!(!trie.observer_mut().unsubscribe(d));
+        trie.insert(vec![1], 'e');
+        assert_eq!(
+            notified(&mut trie),
+            vec![(a, vec![1], 'e'), (c, vec![1], 'e')]
+        );
+        assert!(trie.observer().is_empty());
+    }
+}
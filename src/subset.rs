@@ -1,25 +1,104 @@
-use crate::{Node, SetTrie};
+use crate::budget::Budget;
+use crate::stats::Counters;
+#[cfg(feature = "metrics")]
+use crate::stats::QueryStats;
+use crate::stats::SetCounts;
+use crate::{is_subset, Children, Node, SetTrie};
+use std::ops::{RangeBounds, RangeInclusive};
+use std::time::Instant;
 
 /// Iterator for [subset](SetTrie::subset) method.
 #[derive(Debug, Clone)]
 pub struct Subset<'a, 'b, K, T> {
-    current: &'a Node<K, T>,
-    next: Vec<(&'a K, &'a Node<K, T>)>,
-    idx: usize,
-    keys: &'b [K],
+    leaves: std::slice::Iter<'a, T>,
+    // `None` if only the root can be a subset of the query.
+    nodes: Option<SubsetNodes<'a, 'b, K, T>>,
+    counters: Counters,
 }
 
 impl<'a, 'b, K, T> Subset<'a, 'b, K, T>
 where
     K: Ord,
 {
-    pub(crate) fn new(trie: &'a SetTrie<K, T>, keys: &'b [K]) -> Self {
+    pub(crate) fn new(root: &'a Node<K, T>, keys: &'b [K]) -> Self {
+        Self::with_len(root, keys, 0..=usize::MAX)
+    }
+
+    pub(crate) fn with_len(
+        root: &'a Node<K, T>,
+        keys: &'b [K],
+        lengths: RangeInclusive<usize>,
+    ) -> Self {
+        if keys.is_empty() || root.children.is_empty() {
+            let leaves = if lengths.contains(&0) {
+                root.leaves.iter()
+            } else {
+                [].iter()
+            };
+            return Subset {
+                leaves,
+                nodes: None,
+                counters: Counters::from_root(),
+            };
+        }
+
         Subset {
-            current: &trie.0,
-            next: vec![],
-            idx: 0,
-            keys,
+            leaves: [].iter(),
+            nodes: Some(SubsetNodes::new(root, keys, lengths)),
+            counters: Counters::new(),
+        }
+    }
+
+    /// The work done by the query so far.
+    #[cfg(feature = "metrics")]
+    #[must_use]
+    pub fn stats(&self) -> QueryStats {
+        let stats = self.counters.stats();
+        self.nodes
+            .as_ref()
+            .map_or(stats, |nodes| stats + nodes.counters.stats())
+    }
+
+    /// Stops the query once it would visit more than `nodes` nodes below the root, bounding its
+    /// work regardless of the shape of the trie. Use [`is_truncated`](Self::is_truncated) to tell
+    /// a partial result from a complete one.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1], "foo");
+    /// trie.insert(&[1, 2], "bar");
+    /// trie.insert(&[2], "baz");
+    ///
+    /// let mut subsets = trie.subsets(&[&1, &2]).with_node_budget(2);
+    /// assert_eq!(subsets.by_ref().collect::<Vec<_>>(), vec![&"foo", &"bar"]);
+    /// assert!(subsets.is_truncated());
+    /// ```
+    #[must_use]
+    pub fn with_node_budget(mut self, nodes: usize) -> Self {
+        if let Some(inner) = &mut self.nodes {
+            inner.budget.limit_nodes(nodes);
+        }
+        self
+    }
+
+    /// Stops the query once `deadline` has passed. The clock is only read every so many visited
+    /// nodes, so the query may overrun the deadline slightly.
+    #[must_use]
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        if let Some(inner) = &mut self.nodes {
+            inner.budget.limit_time(deadline);
         }
+        self
+    }
+
+    /// Whether the query was stopped by its [node budget](Self::with_node_budget) or
+    /// [deadline](Self::with_deadline), in which case the values yielded so far are only part of
+    /// the result.
+    #[must_use]
+    pub fn is_truncated(&self) -> bool {
+        self.nodes
+            .as_ref()
+            .is_some_and(|nodes| nodes.budget.exceeded())
     }
 }
 
@@ -30,47 +109,568 @@ where
     type Item = &'a T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.idx < self.current.leaves.len() {
-            self.idx += 1;
-            Some(&self.current.leaves[self.idx - 1])
+        loop {
+            if let Some(item) = self.leaves.next() {
+                self.counters.yielded();
+                return Some(item);
+            }
+            self.leaves = self.nodes.as_mut()?.next()?.1.leaves.iter();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let upper = if self.nodes.is_none() {
+            Some(self.leaves.len())
         } else {
-            if let (Some(from), Some(to)) = (self.keys.first(), self.keys.last()) {
-                self.next.extend(
-                    self.current
-                        // technically, between inclusive is only necessary on the first iteration,
-                        // where we check handle the root node. Every subsequent iter may use up-to,
-                        // which saves a single binary search. For long key lengths this may matter.
-                        .between_inclusive(from, to)
-                        .iter()
-                        .rev()
-                        .map(|n| (&n.0, &n.1)),
-                );
-
-                while let Some((k, node)) = self.next.pop() {
-                    if self.keys.binary_search(k).is_ok() {
-                        self.idx = 0;
-                        self.current = node;
-                        return self.next();
+            None
+        };
+        (self.leaves.len(), upper)
+    }
+}
+
+/// A subset query which keeps its buffers between runs, created by
+/// [subset query](SetTrie::subset_query). Running many queries through the same `SubsetQuery`
+/// only allocates until the buffers are large enough for the deepest traversal.
+#[derive(Debug, Clone)]
+pub struct SubsetQuery<'a, K, T> {
+    root: &'a Node<K, T>,
+    // (node, start of the query keys remaining below the node)
+    next: Vec<(&'a Node<K, T>, usize)>,
+}
+
+impl<'a, K, T> SubsetQuery<'a, K, T>
+where
+    K: Ord,
+{
+    pub(crate) const fn new(root: &'a Node<K, T>) -> Self {
+        Self { root, next: vec![] }
+    }
+
+    /// Iterates over all values stored under subsets of `keys`, in the same order as
+    /// [`SetTrie::subsets`].
+    pub fn run<'q, 'b>(&'q mut self, keys: &'b [K]) -> SubsetQueryIter<'q, 'a, 'b, K, T> {
+        self.next.clear();
+        self.next.push((self.root, 0));
+        SubsetQueryIter {
+            leaves: [].iter(),
+            next: &mut self.next,
+            keys,
+        }
+    }
+}
+
+/// Iterator for [run](SubsetQuery::run) method.
+#[derive(Debug)]
+pub struct SubsetQueryIter<'q, 'a, 'b, K, T> {
+    leaves: std::slice::Iter<'a, T>,
+    next: &'q mut Vec<(&'a Node<K, T>, usize)>,
+    keys: &'b [K],
+}
+
+impl<'a, K, T> Iterator for SubsetQueryIter<'_, 'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some(item);
+            }
+            let (node, start) = self.next.pop()?;
+            let rest = &self.keys[start..];
+            if let (Some(from), Some(to)) = (rest.first(), rest.last()) {
+                let pending = self.next.len();
+                let mut next = start;
+                for (key, child) in node.between_inclusive(from, to) {
+                    match self.keys[next..].binary_search(key) {
+                        Ok(idx) => {
+                            next += idx + 1;
+                            self.next.push((child, next));
+                        }
+                        Err(idx) => next += idx,
                     }
-                    self.next.extend(
-                        node.between_inclusive(from, to)
-                            .iter()
-                            .map(|n| (&n.0, &n.1)),
-                    )
                 }
+                // the smallest key is visited first.
+                self.next[pending..].reverse();
             }
-            None
+            self.leaves = node.leaves.iter();
         }
     }
+}
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        (self.current.leaves.len() - self.idx, None)
+/// A node which is yet to be visited: (depth, key, node, remaining query).
+type Pending<'a, 'b, K, T> = (usize, &'a K, &'a Node<K, T>, &'b [K]);
+
+/// DFS over the nodes whose key path is a subset of the query, yielding every node of which the
+/// depth lies within `lengths` together with its depth.
+///
+/// Every pending node carries the suffix of the query after its own key. Since the keys of its
+/// descendants are larger, only that suffix needs to be considered below it.
+#[allow(clippy::redundant_pub_crate)]
+#[derive(Debug, Clone)]
+pub(crate) struct SubsetNodes<'a, 'b, K, T> {
+    root: Option<&'a Node<K, T>>,
+    // the last yielded node, of which the children have not yet been pushed.
+    expand: Option<(usize, &'a Node<K, T>, &'b [K])>,
+    next: Vec<Pending<'a, 'b, K, T>>,
+    path: Vec<&'a K>,
+    keys: &'b [K],
+    lengths: RangeInclusive<usize>,
+    counters: Counters,
+    budget: Budget,
+}
+
+impl<'a, 'b, K, T> SubsetNodes<'a, 'b, K, T>
+where
+    K: Ord,
+{
+    pub(crate) const fn new(
+        root: &'a Node<K, T>,
+        keys: &'b [K],
+        lengths: RangeInclusive<usize>,
+    ) -> Self {
+        SubsetNodes {
+            root: Some(root),
+            expand: None,
+            next: vec![],
+            path: vec![],
+            keys,
+            lengths,
+            counters: Counters::new(),
+            budget: Budget::unlimited(),
+        }
+    }
+
+    /// The key path of the last yielded node.
+    pub(crate) fn path(&self) -> &[&'a K] {
+        &self.path
+    }
+
+    /// Pushes the children of `node` which are part of the remaining query `keys`, unless they are
+    /// too deep to ever be yielded.
+    fn push_children(&mut self, depth: usize, node: &'a Node<K, T>, keys: &'b [K]) {
+        if depth >= *self.lengths.end() {
+            return;
+        }
+
+        if let (Some(from), Some(to)) = (keys.first(), keys.last()) {
+            let start = self.next.len();
+            let mut rest = keys;
+            for (key, child) in node.between_inclusive(from, to) {
+                self.counters.scan(1);
+                match rest.binary_search(key) {
+                    Ok(idx) => {
+                        rest = &rest[idx + 1..];
+                        self.next.push((depth + 1, key, child, rest));
+                    }
+                    Err(idx) => rest = &rest[idx..],
+                }
+            }
+            // the smallest key is visited first.
+            self.next[start..].reverse();
+        }
+    }
+}
+
+impl<'a, K, T> Iterator for SubsetNodes<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = (usize, &'a Node<K, T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(root) = self.root.take() {
+            self.counters.visit();
+            if self.lengths.contains(&0) {
+                self.expand = Some((0, root, self.keys));
+                return Some((0, root));
+            }
+            self.push_children(0, root, self.keys);
+        }
+
+        if let Some((depth, node, keys)) = self.expand.take() {
+            self.push_children(depth, node, keys);
+        }
+
+        loop {
+            let (depth, key, node, keys) = self.next.pop()?;
+            if !self.budget.visit() {
+                self.next.clear();
+                return None;
+            }
+            self.counters.visit();
+            self.path.truncate(depth - 1);
+            self.path.push(key);
+
+            if self.lengths.contains(&depth) {
+                self.expand = Some((depth, node, keys));
+                return Some((depth, node));
+            }
+            self.push_children(depth, node, keys);
+        }
+    }
+}
+
+/// Iterator for [maximal subsets](SetTrie::maximal_subsets) method.
+///
+/// The subsets of the query are visited in post-order. A stored set `S` is not maximal if a stored
+/// subset of the query lies below it, which every node passes on to its parent, or if it is
+/// contained in a maximal subset visited before it: a larger subset of the query which does not
+/// extend the path of `S` takes a smaller key at some point, and is thus visited first.
+#[derive(Debug, Clone)]
+pub struct MaximalSubsets<'a, 'b, K, T> {
+    leaves: std::slice::Iter<'a, T>,
+    stack: Vec<MaximalFrame<'a, 'b, K, T>>,
+    path: Vec<&'a K>,
+    // the key paths of the maximal subsets yielded so far.
+    found: Vec<Vec<&'a K>>,
+}
+
+/// A node of which the children are being visited by [`MaximalSubsets`].
+#[derive(Debug, Clone)]
+struct MaximalFrame<'a, 'b, K, T> {
+    node: &'a Node<K, T>,
+    children: Children<'a, K, T>,
+    // the keys of the query which the remaining children may still take.
+    rest: &'b [K],
+    // whether a stored subset of the query lies below the node.
+    below: bool,
+}
+
+impl<'a, 'b, K, T> MaximalFrame<'a, 'b, K, T>
+where
+    K: Ord,
+{
+    fn new(node: &'a Node<K, T>, rest: &'b [K]) -> Self {
+        let children = match (rest.first(), rest.last()) {
+            (Some(from), Some(to)) => node.between_inclusive(from, to),
+            _ => node.keys[..0].iter().zip(&node.children[..0]),
+        };
+        Self {
+            node,
+            children,
+            rest,
+            below: false,
+        }
+    }
+
+    /// The next child which is part of the query, and the keys of the query after its key.
+    fn next_child(&mut self) -> Option<(&'a K, &'a Node<K, T>, &'b [K])> {
+        for (key, child) in self.children.by_ref() {
+            match self.rest.binary_search(key) {
+                Ok(idx) => {
+                    self.rest = &self.rest[idx + 1..];
+                    return Some((key, child, self.rest));
+                }
+                Err(idx) => self.rest = &self.rest[idx..],
+            }
+        }
+        None
+    }
+}
+
+impl<'a, 'b, K, T> MaximalSubsets<'a, 'b, K, T>
+where
+    K: Ord,
+{
+    pub(crate) fn new(trie: &'a SetTrie<K, T>, keys: &'b [K]) -> Self {
+        MaximalSubsets {
+            leaves: [].iter(),
+            stack: vec![MaximalFrame::new(&trie.root, keys)],
+            path: vec![],
+            found: vec![],
+        }
+    }
+}
+
+impl<'a, K, T> Iterator for MaximalSubsets<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some(item);
+            }
+
+            let frame = self.stack.last_mut()?;
+            if let Some((key, child, rest)) = frame.next_child() {
+                self.path.push(key);
+                self.stack.push(MaximalFrame::new(child, rest));
+                continue;
+            }
+
+            let frame = self.stack.pop()?;
+            let stored = !frame.node.leaves.is_empty();
+            if let Some(parent) = self.stack.last_mut() {
+                parent.below |= stored || frame.below;
+            }
+            if stored
+                && !frame.below
+                && !self
+                    .found
+                    .iter()
+                    .any(|set| is_subset::<K, _, _>(&self.path, set))
+            {
+                self.found.push(self.path.clone());
+                self.leaves = frame.node.leaves.iter();
+            }
+            self.path.pop();
+        }
+    }
+}
+
+/// Iterator for [subsets grouped](SetTrie::subsets_grouped) method.
+#[derive(Debug, Clone)]
+pub struct SubsetsGrouped<'a, 'b, K, T>(SubsetNodes<'a, 'b, K, T>);
+
+impl<'a, 'b, K, T> SubsetsGrouped<'a, 'b, K, T>
+where
+    K: Ord,
+{
+    pub(crate) const fn new(root: &'a Node<K, T>, keys: &'b [K]) -> Self {
+        Self(SubsetNodes::new(root, keys, 0..=usize::MAX))
+    }
+}
+
+impl<'a, K, T> Iterator for SubsetsGrouped<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = (Vec<&'a K>, &'a [T]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (_, node) = self.0.next()?;
+            if !node.leaves.is_empty() {
+                return Some((self.0.path().to_vec(), &node.leaves));
+            }
+        }
+    }
+}
+
+impl<K, T> Node<K, T>
+where
+    K: Ord,
+{
+    /// The largest stored subset of `keys` below this node and its values, preferring the first
+    /// in lexicographic order among equally large ones. No stored set is longer than `max_len`.
+    ///
+    /// As sets are visited in lexicographic order, a subtree can only replace the best set so far
+    /// if it may hold a larger one, which bounds the search by the number of unused query keys.
+    pub(crate) fn most_specific_subset<'a>(
+        &'a self,
+        keys: &[K],
+        max_len: usize,
+    ) -> Option<(Vec<&'a K>, &'a [T])> {
+        let limit = keys.len().min(max_len);
+        let mut best: Option<(usize, Vec<&K>, &[T])> = None;
+        let mut path = vec![];
+        let mut stack = vec![(0_usize, None, self, 0)];
+        while let Some((depth, key, node, start)) = stack.pop() {
+            let best_len = best.as_ref().map(|(len, ..)| *len);
+            let bound = (depth + keys.len() - start).min(limit);
+            if best_len.is_some_and(|len| len >= bound) {
+                continue;
+            }
+            path.truncate(depth.saturating_sub(1));
+            path.extend(key);
+
+            if !node.leaves.is_empty() && best_len.is_none_or(|len| len < depth) {
+                best = Some((depth, path.clone(), &node.leaves));
+                if depth == limit {
+                    break;
+                }
+            }
+
+            let rest = &keys[start..];
+            if let (Some(from), Some(to)) = (rest.first(), rest.last()) {
+                let pending = stack.len();
+                let mut next = start;
+                for (key, child) in node.between_inclusive(from, to) {
+                    match keys[next..].binary_search(key) {
+                        Ok(idx) => {
+                            next += idx + 1;
+                            stack.push((depth + 1, Some(key), child, next));
+                        }
+                        Err(idx) => next += idx,
+                    }
+                }
+                // the smallest key is visited first.
+                stack[pending..].reverse();
+            }
+        }
+        best.map(|(_, path, leaves)| (path, leaves))
+    }
+}
+
+impl<K, T> Node<K, T>
+where
+    K: Ord + Clone,
+    T: Clone,
+{
+    /// Copies the subsets of `keys` below this node, leaving out the branches which do not hold
+    /// any, and adds the copied sets to `sets`.
+    pub(crate) fn clone_subsets(&self, keys: &[K], sets: &mut SetCounts) -> Self {
+        self.copy_where(
+            sets,
+            0,
+            |_| true,
+            |node, &start| {
+                let mut children = vec![];
+                let rest = &keys[start..];
+                if let (Some(from), Some(to)) = (rest.first(), rest.last()) {
+                    let mut next = start;
+                    for (key, child) in node.between_inclusive(from, to) {
+                        match keys[next..].binary_search(key) {
+                            Ok(idx) => {
+                                next += idx + 1;
+                                children.push((key, child, next));
+                            }
+                            Err(idx) => next += idx,
+                        }
+                    }
+                }
+                children
+            },
+        )
+    }
+}
+
+/// Iterator for [subsets in](SetTrie::subsets_in) method.
+#[derive(Debug, Clone)]
+pub struct SubsetsIn<'a, K, T, R> {
+    leaves: std::slice::Iter<'a, T>,
+    next: Vec<&'a Node<K, T>>,
+    range: R,
+}
+
+impl<'a, K, T, R> SubsetsIn<'a, K, T, R> {
+    pub(crate) fn new(root: &'a Node<K, T>, range: R) -> Self {
+        Self {
+            leaves: [].iter(),
+            next: vec![root],
+            range,
+        }
+    }
+}
+
+impl<'a, K, T, R> Iterator for SubsetsIn<'a, K, T, R>
+where
+    K: Ord,
+    R: RangeBounds<K>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some(item);
+            }
+            let node = self.next.pop()?;
+            self.next
+                .extend(node.within(&self.range).rev().map(|(_, child)| child));
+            self.leaves = node.leaves.iter();
+        }
+    }
+}
+
+/// Iterator for [subset keys](SetTrie::subset_keys) method.
+#[derive(Debug, Clone)]
+pub struct SubsetKeys<'a, 'b, K, T>(SubsetNodes<'a, 'b, K, T>);
+
+impl<'a, 'b, K, T> SubsetKeys<'a, 'b, K, T>
+where
+    K: Ord,
+{
+    pub(crate) const fn new(root: &'a Node<K, T>, keys: &'b [K]) -> Self {
+        Self(SubsetNodes::new(root, keys, 0..=usize::MAX))
+    }
+}
+
+impl<'a, K, T> Iterator for SubsetKeys<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = Vec<&'a K>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (_, node) = self.0.next()?;
+            if !node.leaves.is_empty() {
+                return Some(self.0.path().to_vec());
+            }
+        }
+    }
+}
+
+/// Iterator for [subsets many](SetTrie::subsets_many) method.
+#[derive(Debug, Clone)]
+pub struct SubsetsMany<'a, 'b, K, T> {
+    queries: &'b [&'b [K]],
+    leaves: std::slice::Iter<'a, T>,
+    // the queries of which the key path of the current node is a subset.
+    matches: Vec<usize>,
+    next: Vec<(&'a Node<K, T>, Vec<usize>)>,
+}
+
+impl<'a, 'b, K, T> SubsetsMany<'a, 'b, K, T>
+where
+    K: Ord,
+{
+    pub(crate) fn new(root: &'a Node<K, T>, queries: &'b [&'b [K]]) -> Self {
+        let mut next = vec![];
+        if !queries.is_empty() {
+            next.push((root, (0..queries.len()).collect()));
+        }
+
+        SubsetsMany {
+            queries,
+            leaves: [].iter(),
+            matches: vec![],
+            next,
+        }
+    }
+}
+
+impl<'a, K, T> Iterator for SubsetsMany<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = (&'a T, Vec<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some((item, self.matches.clone()));
+            }
+
+            let (node, matches) = self.next.pop()?;
+            for (key, child) in node.children().rev() {
+                let queries = self.queries;
+                let remaining: Vec<_> = matches
+                    .iter()
+                    .copied()
+                    .filter(|i| queries[*i].binary_search(key).is_ok())
+                    .collect();
+                if !remaining.is_empty() {
+                    self.next.push((child, remaining));
+                }
+            }
+            self.leaves = node.leaves.iter();
+            self.matches = matches;
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::SetTrie;
+    use std::ops::Bound;
 
     #[test]
     fn subsets_small() {
@@ -101,6 +701,210 @@ mod tests {
         assert_eq!(v.subsets(&[&6]).collect::<Vec<_>>(), vec![&'f']);
     }
 
+    #[test]
+    fn subsets_skip_foreign_keys() {
+        let mut v = SetTrie::new();
+        v.insert(&[1, 2], 'a');
+        v.insert(&[0, 1], 'b');
+
+        assert_eq!(
+            v.subsets(&[&0, &2]).collect::<Vec<_>>(),
+            Vec::<&char>::new()
+        );
+        assert_eq!(
+            v.subsets(&[&0, &1, &2]).collect::<Vec<_>>(),
+            vec![&'b', &'a']
+        );
+    }
+
+    #[test]
+    fn subsets_fast_paths() {
+        let mut v = SetTrie::new();
+        assert_eq!(v.subsets(&[&1]).size_hint(), (0, Some(0)));
+
+        v.insert(&[], 'a');
+        v.insert(&[1], 'b');
+        assert_eq!(v.subsets(&[]).size_hint(), (1, Some(1)));
+        assert_eq!(v.subsets(&[]).collect::<Vec<_>>(), vec![&'a']);
+        assert_eq!(v.subsets_with_len(&[], 1..).count(), 0);
+    }
+
+    #[test]
+    fn subsets_with_len() {
+        let mut v = SetTrie::new();
+        v.insert(&[], 'a');
+        v.insert(&[1], 'b');
+        v.insert(&[1, 2], 'c');
+        v.insert(&[2, 3], 'd');
+        v.insert(&[1, 2, 3], 'e');
+
+        let q = [&1, &2, &3];
+        assert_eq!(
+            v.subsets_with_len(&q, 1..=2).collect::<Vec<_>>(),
+            vec![&'b', &'c', &'d']
+        );
+        assert_eq!(v.subsets_with_len(&q, 3..).collect::<Vec<_>>(), vec![&'e']);
+        assert_eq!(v.subsets_with_len(&q, ..1).collect::<Vec<_>>(), vec![&'a']);
+        assert_eq!(v.subsets_with_len(&q, 2..2).count(), 0);
+    }
+
+    #[test]
+    fn maximal_subsets() {
+        let mut v = SetTrie::new();
+        v.insert(&[], 'a');
+        v.insert(&[1], 'b');
+        v.insert(&[1, 2], 'c');
+        v.insert(&[1, 3], 'd');
+        v.insert(&[2, 3], 'e');
+        v.insert(&[3], 'f');
+        v.insert(&[1, 2, 4], 'g');
+
+        assert_eq!(
+            v.maximal_subsets(&[&1, &2, &3]).collect::<Vec<_>>(),
+            vec![&'c', &'d', &'e']
+        );
+        assert_eq!(v.maximal_subsets(&[&3]).collect::<Vec<_>>(), vec![&'f']);
+        assert_eq!(v.maximal_subsets(&[&4]).collect::<Vec<_>>(), vec![&'a']);
+    }
+
+    #[test]
+    fn subset_keys() {
+        let mut v = SetTrie::new();
+        v.insert(&[], 'a');
+        v.insert(&[1, 2], 'b');
+        v.insert(&[1, 2], 'c');
+        v.insert(&[1, 3], 'd');
+        v.entry(vec![&2]).or_create();
+
+        assert_eq!(
+            v.subset_keys(&[&1, &2]).collect::<Vec<_>>(),
+            vec![vec![], vec![&&1, &&2]]
+        );
+        // the node of 2 holds no values.
+        assert_eq!(v.subset_keys(&[&2]).count(), 1);
+    }
+
+    #[test]
+    fn subset_query() {
+        let mut v = SetTrie::new();
+        v.insert(vec![], 'a');
+        v.insert(vec![1, 2], 'b');
+        v.insert(vec![1, 3], 'c');
+        v.insert(vec![2, 3], 'd');
+        v.insert(vec![1, 2, 3], 'e');
+
+        let mut query = v.subset_query();
+        for keys in [vec![], vec![1, 2], vec![1, 2, 3], vec![3, 4], vec![1, 2]] {
+            assert_eq!(
+                query.run(&keys).collect::<Vec<_>>(),
+                v.subsets(&keys).collect::<Vec<_>>()
+            );
+        }
+        // a query which is dropped early leaves nothing behind for the next one.
+        assert_eq!(query.run(&[1, 2, 3]).next(), Some(&'a'));
+        assert_eq!(query.run(&[2, 3]).collect::<Vec<_>>(), vec![&'a', &'d']);
+    }
+
+    #[test]
+    fn subsets_in() {
+        let mut v = SetTrie::new();
+        v.insert(vec![], 'a');
+        v.insert(vec![1, 2], 'b');
+        v.insert(vec![1, 4], 'c');
+        v.insert(vec![2, 3], 'd');
+        v.insert(vec![4], 'e');
+
+        assert_eq!(
+            v.subsets_in(1..4).collect::<Vec<_>>(),
+            vec![&'a', &'b', &'d']
+        );
+        assert_eq!(
+            v.subsets_in(2..=4).collect::<Vec<_>>(),
+            vec![&'a', &'d', &'e']
+        );
+        assert_eq!(v.subsets_in(..).count(), 5);
+        let empty = (Bound::Included(3), Bound::Excluded(2));
+        assert_eq!(v.subsets_in(empty).collect::<Vec<_>>(), vec![&'a']);
+        assert_eq!(
+            v.subsets_in(0..10).collect::<Vec<_>>(),
+            v.subsets(&(0..10).collect::<Vec<_>>()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn clone_subsets() {
+        let mut v = SetTrie::new();
+        v.insert(vec![], 'a');
+        v.insert(vec![1, 2], 'b');
+        v.insert(vec![1, 2, 3], 'c');
+        v.insert(vec![1, 3], 'd');
+        v.insert(vec![2, 4], 'e');
+        v.insert(vec![3], 'f');
+        v.entry(vec![1]).or_create();
+
+        let copy = v.clone_subsets(&[1, 3]);
+        assert_eq!(copy.set_count(), 3);
+        assert_eq!(copy.check_invariants(), Ok(()));
+        assert_eq!(
+            copy.iter().collect::<Vec<_>>(),
+            vec![(vec![], &'a'), (vec![&1, &3], &'d'), (vec![&3], &'f')]
+        );
+
+        let empty = v.clone_subsets(&[4]);
+        assert_eq!(empty.set_count(), 1);
+        assert_eq!(empty.max_depth(), 0);
+    }
+
+    #[test]
+    fn most_specific_subset() {
+        let mut v = SetTrie::new();
+        assert_eq!(v.most_specific_subset(&[&1]), None);
+
+        v.insert(&[], 'a');
+        v.insert(&[1], 'b');
+        v.insert(&[1, 3], 'c');
+        v.insert(&[2, 3], 'd');
+        v.insert(&[1, 2, 4], 'e');
+        v.entry(vec![&1, &2, &3]).or_create();
+
+        assert_eq!(v.most_specific_subset(&[]), Some((vec![], &['a'][..])));
+        assert_eq!(
+            v.most_specific_subset(&[&1, &2, &3]),
+            Some((vec![&&1, &&3], &['c'][..]))
+        );
+        assert_eq!(
+            v.most_specific_subset(&[&2, &3, &4]),
+            Some((vec![&&2, &&3], &['d'][..]))
+        );
+        assert_eq!(
+            v.most_specific_subset(&[&1, &2, &3, &4]),
+            Some((vec![&&1, &&2, &&4], &['e'][..]))
+        );
+        assert_eq!(v.most_specific_subset(&[&5]), Some((vec![], &['a'][..])));
+    }
+
+    #[test]
+    fn subsets_many() {
+        let mut v = SetTrie::new();
+        v.insert(&[], 'a');
+        v.insert(&[1], 'b');
+        v.insert(&[1, 2], 'c');
+        v.insert(&[2, 3], 'd');
+        v.insert(&[4], 'e');
+
+        assert_eq!(
+            v.subsets_many(&[&[&1, &2], &[&2, &3], &[&5]])
+                .collect::<Vec<_>>(),
+            vec![
+                (&'a', vec![0, 1, 2]),
+                (&'b', vec![0]),
+                (&'c', vec![0]),
+                (&'d', vec![1])
+            ]
+        );
+        assert_eq!(v.subsets_many(&[]).count(), 0);
+    }
+
     mod proptest {
         use crate::SetTrie;
         use ::proptest::prelude::*;
@@ -0,0 +1,206 @@
+use crate::stats::{KeyCounts, PendingCounts, SetCounts, SubtreeCounts};
+use crate::subset::Subset;
+use crate::superset::SuperSet;
+use crate::values::Values;
+use crate::{Entry, EntryBuilder, Node};
+
+/// Read-only view into the sets of a [`SetTrie`](crate::SetTrie) which start with a common prefix,
+/// created by the [subtrie](crate::SetTrie::subtrie) method.
+///
+/// Queries on the view are relative to the prefix: they match the remainder of the stored sets
+/// after the prefix, and only visit the nodes below the prefix.
+#[derive(Debug)]
+pub struct SubTrie<'a, K, T> {
+    node: &'a Node<K, T>,
+}
+
+impl<K, T> Clone for SubTrie<'_, K, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, T> Copy for SubTrie<'_, K, T> {}
+
+impl<'a, K, T> SubTrie<'a, K, T>
+where
+    K: Ord,
+{
+    pub(crate) const fn new(node: &'a Node<K, T>) -> Self {
+        Self { node }
+    }
+
+    /// Iterates over all values stored under the prefix using DFS.
+    #[must_use]
+    pub const fn values(self) -> Values<'a, K, T> {
+        Values::new(self.node)
+    }
+
+    /// Iterates over all sets under the prefix of which the remainder is a subset of `keys`, using
+    /// DFS.
+    #[must_use]
+    pub fn subsets<'b>(self, keys: &'b [K]) -> Subset<'a, 'b, K, T> {
+        Subset::new(self.node, keys)
+    }
+
+    /// Iterates over all sets under the prefix of which the remainder is a superset of `keys`,
+    /// using DFS.
+    #[must_use]
+    pub fn supersets<'b>(self, keys: &'b [K]) -> SuperSet<'a, 'b, K, T> {
+        SuperSet::new(self.node, keys)
+    }
+}
+
+/// Mutable view into the sets of a [`SetTrie`](crate::SetTrie) which start with a common prefix,
+/// created by the [subtrie mut](crate::SetTrie::subtrie_mut) method.
+///
+/// Like [`SubTrie`], operations on the view are relative to the prefix, so batch updates under
+/// the prefix do not repeatedly traverse it.
+#[derive(Debug)]
+pub struct SubTrieMut<'a, K, T> {
+    node: &'a mut Node<K, T>,
+    prefix: Vec<&'a K>,
+    // the number of nodes holding values in the whole trie.
+    sets: &'a mut SetCounts,
+    // the changes to the counts of the nodes on the path to the last update, yet to be applied.
+    pending: &'a mut PendingCounts,
+    // the number of nodes of every key below which values are stored in the whole trie.
+    keys: &'a mut KeyCounts<K>,
+}
+
+impl<'a, K, T> SubTrieMut<'a, K, T>
+where
+    K: Ord + Clone,
+{
+    pub(crate) const fn new(
+        node: &'a mut Node<K, T>,
+        prefix: Vec<&'a K>,
+        sets: &'a mut SetCounts,
+        pending: &'a mut PendingCounts,
+        keys: &'a mut KeyCounts<K>,
+    ) -> Self {
+        Self {
+            node,
+            prefix,
+            sets,
+            pending,
+            keys,
+        }
+    }
+
+    /// A read-only view of the same sets.
+    #[must_use]
+    pub const fn as_subtrie(&self) -> SubTrie<'_, K, T> {
+        SubTrie::new(self.node)
+    }
+
+    /// A view into a single node below the prefix; which must either be created or already
+    /// exists.
+    #[must_use]
+    pub fn entry<IK: IntoIterator<Item = K>>(
+        &mut self,
+        keys: IK,
+    ) -> EntryBuilder<'_, K, T, IK::IntoIter> {
+        // the entry starts a new path from the prefix.
+        self.pending.truncate(
+            self.prefix.len(),
+            self.prefix.last().copied(),
+            self.node,
+            self.keys,
+        );
+        EntryBuilder::from_node(
+            self.node,
+            self.prefix.clone(),
+            self.sets,
+            self.pending,
+            self.keys,
+            keys.into_iter(),
+        )
+    }
+
+    /// Insert the item in the given node below the prefix. Will create the node if needed.
+    pub fn insert(&mut self, keys: impl IntoIterator<Item = K>, item: T) -> Entry<'_, K, T> {
+        self.entry(keys).and_insert(item)
+    }
+
+    /// Inserts multiple items in the given node below the prefix.
+    pub fn insert_many<IK: IntoIterator<Item = K>, IT: IntoIterator<Item = T>>(
+        &mut self,
+        keys: IK,
+        item: IT,
+    ) {
+        self.entry(keys).and_extend(item);
+    }
+
+    /// Removes the set `keys` below the prefix, returning its values if it was stored. See
+    /// [`SetTrie::remove`](crate::SetTrie::remove).
+    pub fn remove(&mut self, keys: &[K]) -> Option<Vec<T>> {
+        let before = self.node.counts;
+        let removed = self.node.remove(keys, self.keys)?;
+        if let Some(key) = self.prefix.last() {
+            self.keys.update(key, before, self.node.counts);
+        }
+        self.pending
+            .add_above(self.prefix.len(), SubtreeCounts::of(removed.len()).negate());
+        self.sets.remove(self.prefix.len() + keys.len());
+        Some(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SetTrie;
+
+    #[test]
+    fn subtrie() {
+        let mut trie = SetTrie::new();
+        trie.insert(&["accounting"], 'a');
+        trie.insert(&["accounting", "banking"], 'b');
+        trie.insert(&["accounting", "banking", "crime"], 'c');
+        trie.insert(&["accounting", "crime"], 'd');
+        trie.insert(&["banking", "crime"], 'e');
+
+        let accounting = trie.subtrie(&[&"accounting"]).unwrap();
+        assert_eq!(
+            accounting.values().collect::<Vec<_>>(),
+            vec![&'a', &'b', &'c', &'d']
+        );
+        assert_eq!(
+            accounting.subsets(&[&"banking"]).collect::<Vec<_>>(),
+            vec![&'a', &'b']
+        );
+        assert_eq!(
+            accounting.supersets(&[&"crime"]).collect::<Vec<_>>(),
+            vec![&'c', &'d']
+        );
+
+        assert!(trie.subtrie(&[&"crime"]).is_none());
+        assert_eq!(trie.subtrie(&[]).unwrap().values().count(), 5);
+    }
+
+    #[test]
+    fn subtrie_mut() {
+        let mut trie = SetTrie::new();
+        trie.insert(vec![1, 2], 'a');
+
+        let mut view = trie.subtrie_mut(vec![1]);
+        view.insert(vec![], 'b');
+        view.insert(vec![3, 4], 'c');
+        assert_eq!(view.entry(vec![3]).and_insert('d').path(), vec![&1, &3]);
+        assert_eq!(view.remove(&[2]), Some(vec!['a']));
+        assert_eq!(view.remove(&[2]), None);
+        assert_eq!(
+            view.as_subtrie().values().collect::<Vec<_>>(),
+            vec![&'b', &'d', &'c']
+        );
+
+        let mut view = trie.subtrie_mut(vec![5]);
+        view.insert_many(vec![6], vec!['e', 'f']);
+
+        assert_eq!(
+            trie.values().collect::<Vec<_>>(),
+            vec![&'b', &'d', &'c', &'e', &'f']
+        );
+        assert_eq!(trie.remove(&[1, 3, 4]), Some(vec!['c']));
+    }
+}
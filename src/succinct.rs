@@ -0,0 +1,360 @@
+use crate::{FrozenSetTrie, SetTrie};
+use std::ops::Range;
+
+/// Number of words of a [`Bits`] per sample of its rank.
+const BLOCK: usize = 8;
+
+/// An append-only bitvector supporting `select0` in `O(log n)`, through a sample of the number of
+/// zeros before every block of [`BLOCK`] words.
+#[derive(Debug, Clone, Default)]
+struct Bits {
+    words: Vec<u64>,
+    len: usize,
+    // zeros before every block.
+    ranks: Vec<usize>,
+}
+
+impl Bits {
+    /// Encodes the differences of the increasing `offsets` in unary: ones for the difference,
+    /// followed by a zero.
+    fn unary(offsets: &[usize]) -> Self {
+        let mut bits = Self::default();
+        for window in offsets.windows(2) {
+            for _ in window[0]..window[1] {
+                bits.push(true);
+            }
+            bits.push(false);
+        }
+        bits.words.shrink_to_fit();
+
+        let mut zeros = 0;
+        for (idx, word) in bits.words.iter().enumerate() {
+            if idx.is_multiple_of(BLOCK) {
+                bits.ranks.push(zeros);
+            }
+            zeros += word.count_zeros() as usize;
+        }
+        bits
+    }
+
+    fn push(&mut self, bit: bool) {
+        if self.len.is_multiple_of(64) {
+            self.words.push(0);
+        }
+        if bit {
+            self.words[self.len / 64] |= 1 << (self.len % 64);
+        }
+        self.len += 1;
+    }
+
+    /// The position of the zero of rank `k`, which must exist.
+    fn select0(&self, mut k: usize) -> usize {
+        // blocks before the one holding the zero; the first block has rank 0.
+        let block = self.ranks.partition_point(|&zeros| zeros <= k) - 1;
+        k -= self.ranks[block];
+        for (idx, word) in self.words.iter().enumerate().skip(block * BLOCK) {
+            let zeros = word.count_zeros() as usize;
+            if k < zeros {
+                let mut unset = !word;
+                for _ in 0..k {
+                    unset &= unset - 1;
+                }
+                return idx * 64 + unset.trailing_zeros() as usize;
+            }
+            k -= zeros;
+        }
+        unreachable!("bitvector holds fewer zeros than selected")
+    }
+
+    /// Decodes the range of the `idx`th difference encoded by [unary](Self::unary).
+    fn range(&self, idx: usize) -> Range<usize> {
+        let start = if idx == 0 {
+            0
+        } else {
+            self.select0(idx - 1) + 1
+        };
+        // every zero before the range ends one of the previous differences.
+        start - idx..self.select0(idx) - idx
+    }
+
+    const fn heap_size(&self) -> usize {
+        (self.words.capacity() * 64 + self.ranks.capacity() * usize::BITS as usize) / 8
+    }
+}
+
+/// A read-only [`SetTrie`] in a succinct encoding, created by
+/// [into succinct](SetTrie::into_succinct).
+///
+/// The structure of the trie is stored as a LOUDS bitvector, which lists the number of children
+/// of every node in BFS order in unary, taking two bits per node. The values are assigned to the
+/// nodes in the same way, and the keys and values themselves are packed in two arrays. Navigating
+/// the trie selects zeros in the bitvectors, which is slower than following the offsets of a
+/// [`FrozenSetTrie`], but takes only a few bits per node instead of two words.
+///
+/// ```rust
+/// let mut trie = set_trie::SetTrie::new();
+/// trie.insert(&[1, 2], "foo");
+/// trie.insert(&[1, 3], "bar");
+/// trie.insert(&[2], "baz");
+///
+/// let succinct = trie.into_succinct();
+/// assert_eq!(succinct.subsets(&[&1, &2]).collect::<Vec<_>>(), vec![&"foo", &"baz"]);
+/// assert_eq!(succinct.supersets(&[&1]).collect::<Vec<_>>(), vec![&"foo", &"bar"]);
+/// assert_eq!(succinct.get(&[&2]), Some(&["baz"][..]));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SuccinctSetTrie<K, T> {
+    // the key of node `n` is at `n - 1`, as the root has no key.
+    keys: Vec<K>,
+    structure: Bits,
+    values: Bits,
+    leaves: Vec<T>,
+}
+
+impl<K, T> From<FrozenSetTrie<K, T>> for SuccinctSetTrie<K, T> {
+    fn from(frozen: FrozenSetTrie<K, T>) -> Self {
+        let (keys, children, values, leaves) = frozen.into_parts();
+        Self {
+            keys,
+            structure: Bits::unary(&children),
+            values: Bits::unary(&values),
+            leaves,
+        }
+    }
+}
+
+impl<K, T> SetTrie<K, T>
+where
+    K: Ord,
+{
+    /// Compiles the trie into a [`SuccinctSetTrie`], which answers the same queries using a
+    /// fraction of the memory of the nodes, but can no longer be modified.
+    #[must_use]
+    pub fn into_succinct(self) -> SuccinctSetTrie<K, T>
+    where
+        K: Clone,
+    {
+        self.freeze().into()
+    }
+}
+
+impl<K, T> SuccinctSetTrie<K, T> {
+    /// The number of values in the trie.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Whether the trie holds no values.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Iterates over all values in the trie, in BFS order of their sets.
+    pub fn values(&self) -> std::slice::Iter<'_, T> {
+        self.leaves.iter()
+    }
+
+    /// The number of bytes taken by the structure of the trie, which excludes the keys and the
+    /// values themselves.
+    #[must_use]
+    pub const fn structure_size(&self) -> usize {
+        self.structure.heap_size() + self.values.heap_size()
+    }
+
+    /// The range of the keys of the children of `node`. The child with key `keys[idx]` is the
+    /// node `idx + 1`.
+    fn children(&self, node: usize) -> Range<usize> {
+        self.structure.range(node)
+    }
+
+    /// The values of `node`.
+    fn leaves(&self, node: usize) -> &[T] {
+        &self.leaves[self.values.range(node)]
+    }
+}
+
+impl<K, T> SuccinctSetTrie<K, T>
+where
+    K: Ord,
+{
+    /// The values of the set `keys`, if it is stored.
+    #[must_use]
+    pub fn get(&self, keys: &[K]) -> Option<&[T]> {
+        let mut node = 0;
+        for key in keys {
+            let children = self.children(node);
+            node = children.start + self.keys[children].binary_search(key).ok()? + 1;
+        }
+        Some(self.leaves(node)).filter(|leaves| !leaves.is_empty())
+    }
+
+    /// Iterates over all values whose set is a subset of `keys`. See [`SetTrie::subsets`].
+    #[must_use]
+    pub fn subsets<'a, 'b>(&'a self, keys: &'b [K]) -> SuccinctSubsets<'a, 'b, K, T> {
+        SuccinctSubsets {
+            trie: self,
+            keys,
+            leaves: [].iter(),
+            stack: vec![(0, 0)],
+        }
+    }
+
+    /// Iterates over all values whose set is a superset of `keys`. See [`SetTrie::supersets`].
+    #[must_use]
+    pub fn supersets<'a, 'b>(&'a self, keys: &'b [K]) -> SuccinctSuperSets<'a, 'b, K, T> {
+        SuccinctSuperSets {
+            trie: self,
+            keys,
+            leaves: [].iter(),
+            stack: vec![(0, 0)],
+        }
+    }
+}
+
+/// Iterator for [`SuccinctSetTrie::subsets`].
+#[derive(Debug, Clone)]
+pub struct SuccinctSubsets<'a, 'b, K, T> {
+    trie: &'a SuccinctSetTrie<K, T>,
+    keys: &'b [K],
+    leaves: std::slice::Iter<'a, T>,
+    // (node, start of the query keys which may still follow the key path of the node).
+    stack: Vec<(usize, usize)>,
+}
+
+impl<'a, K, T> Iterator for SuccinctSubsets<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some(item);
+            }
+
+            let (node, start) = self.stack.pop()?;
+            self.leaves = self.trie.leaves(node).iter();
+
+            let rest = &self.keys[start..];
+            let (Some(from), Some(to)) = (rest.first(), rest.last()) else {
+                continue;
+            };
+            let children = self.trie.children(node);
+            let keys = &self.trie.keys[children.clone()];
+            let lower = keys.partition_point(|key| key < from);
+            let upper = keys.partition_point(|key| key <= to);
+            // pushed in reverse, so that the children are visited in order.
+            for idx in (lower..upper).rev() {
+                if let Ok(found) = rest.binary_search(&keys[idx]) {
+                    self.stack
+                        .push((children.start + idx + 1, start + found + 1));
+                }
+            }
+        }
+    }
+}
+
+/// Iterator for [`SuccinctSetTrie::supersets`].
+#[derive(Debug, Clone)]
+pub struct SuccinctSuperSets<'a, 'b, K, T> {
+    trie: &'a SuccinctSetTrie<K, T>,
+    keys: &'b [K],
+    leaves: std::slice::Iter<'a, T>,
+    // (node, number of query keys on the key path of the node).
+    stack: Vec<(usize, usize)>,
+}
+
+impl<'a, K, T> Iterator for SuccinctSuperSets<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some(item);
+            }
+
+            let (node, matched) = self.stack.pop()?;
+            let children = self.trie.children(node);
+            let Some(want) = self.keys.get(matched) else {
+                self.leaves = self.trie.leaves(node).iter();
+                self.stack
+                    .extend(children.rev().map(|idx| (idx + 1, matched)));
+                continue;
+            };
+
+            // as children are sorted, only those up to the next key of the query may still lead
+            // to a superset.
+            let keys = &self.trie.keys[children.clone()];
+            let upper = keys.partition_point(|key| key <= want);
+            for idx in (0..upper).rev() {
+                let matched = matched + usize::from(&keys[idx] == want);
+                self.stack.push((children.start + idx + 1, matched));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bits;
+    use crate::SetTrie;
+
+    #[test]
+    fn select0() {
+        // offsets spanning several blocks, with empty and long runs of ones.
+        let offsets: Vec<usize> = (0..2000)
+            .map(|i| i * (i % 7))
+            .scan(0, |sum, d| {
+                *sum += d;
+                Some(*sum)
+            })
+            .collect();
+        let bits = Bits::unary(&offsets);
+        for idx in 0..offsets.len() - 1 {
+            assert_eq!(bits.range(idx), offsets[idx]..offsets[idx + 1]);
+        }
+    }
+
+    #[test]
+    fn into_succinct() {
+        let mut trie = SetTrie::new();
+        trie.insert(vec![1, 2, 3], 'a');
+        trie.insert(vec![1], 'b');
+        trie.insert(vec![2, 3], 'c');
+        trie.insert(vec![], 'd');
+        trie.insert(vec![1, 3], 'e');
+        trie.insert(vec![1, 2, 3], 'f');
+        trie.insert(vec![4], 'g');
+        trie.remove(&[4]);
+
+        let queries: &[&[i32]] = &[&[], &[1], &[3], &[1, 3], &[1, 2, 3], &[2, 3, 4], &[5]];
+        let expected: Vec<_> = queries
+            .iter()
+            .map(|keys| {
+                (
+                    trie.subsets(keys).copied().collect::<Vec<_>>(),
+                    trie.supersets(keys).copied().collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+
+        let succinct = trie.into_succinct();
+        assert_eq!(succinct.len(), 6);
+        for (keys, expected) in queries.iter().zip(expected) {
+            let actual = (
+                succinct.subsets(keys).copied().collect::<Vec<_>>(),
+                succinct.supersets(keys).copied().collect::<Vec<_>>(),
+            );
+            assert_eq!(actual, expected, "{keys:?}");
+        }
+
+        assert_eq!(succinct.get(&[1, 2, 3]), Some(&['a', 'f'][..]));
+        assert_eq!(succinct.get(&[2]), None);
+    }
+}
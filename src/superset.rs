@@ -1,94 +1,677 @@
-use crate::{Node, SetTrie};
+use crate::budget::Budget;
+#[cfg(feature = "metrics")]
+use crate::stats::QueryStats;
+use crate::stats::{Counters, SetCounts};
+use crate::values::Values;
+use crate::{is_subset, Node, SetTrie};
+use std::cmp::Ordering;
+use std::ops::RangeInclusive;
+use std::time::Instant;
 
 /// Iterator for [superset](SetTrie::superset) method.
 #[derive(Debug, Clone)]
 pub struct SuperSet<'a, 'b, K, T> {
-    idx: usize,
-    current: (bool, bool, &'a Node<K, T>),
-    next: Vec<(bool, &'a K, &'a Node<K, T>)>,
-    keys: &'b [K],
+    leaves: std::slice::Iter<'a, T>,
+    source: Source<'a, 'b, K, T>,
+    counters: Counters,
+}
+
+/// Where a [`SuperSet`] draws the matching nodes from.
+#[derive(Debug, Clone)]
+enum Source<'a, 'b, K, T> {
+    Nodes(SuperSetNodes<'a, 'b, K, T>),
+    // every set is a superset of the empty query.
+    Values(Values<'a, K, T>),
+    Done,
 }
 
 impl<'a, 'b, K, T> SuperSet<'a, 'b, K, T>
 where
     K: Ord,
 {
-    pub(crate) fn new(trie: &'a SetTrie<K, T>, keys: &'b [K]) -> Self {
+    pub(crate) fn new(root: &'a Node<K, T>, keys: &'b [K]) -> Self {
+        Self::with_len(root, keys, 0..=usize::MAX)
+    }
+
+    /// A query which is known to yield nothing, without visiting any node.
+    pub(crate) fn empty() -> Self {
         SuperSet {
-            current: (keys.is_empty(), keys.is_empty(), &trie.0),
-            next: vec![],
-            // if we are queried for the empty set, we want to process root, else jump to evaluating
-            // the descendants of root.
-            idx: if keys.is_empty() {
-                0
+            leaves: [].iter(),
+            source: Source::Done,
+            counters: Counters::new(),
+        }
+    }
+
+    pub(crate) fn with_len(
+        root: &'a Node<K, T>,
+        keys: &'b [K],
+        lengths: RangeInclusive<usize>,
+    ) -> Self {
+        if root.children.is_empty() {
+            // only the root can be a superset of the query.
+            let leaves = if keys.is_empty() && lengths.contains(&0) {
+                root.leaves.iter()
             } else {
-                trie.0.leaves.len()
+                [].iter()
+            };
+            return SuperSet {
+                leaves,
+                source: Source::Done,
+                counters: Counters::from_root(),
+            };
+        }
+
+        let source = if keys.is_empty() && lengths == (0..=usize::MAX) {
+            Source::Values(Values::new(root))
+        } else {
+            Source::Nodes(SuperSetNodes::new(root, keys, lengths))
+        };
+
+        SuperSet {
+            leaves: [].iter(),
+            source,
+            counters: Counters::new(),
+        }
+    }
+
+    /// The work done by the query so far.
+    #[cfg(feature = "metrics")]
+    #[must_use]
+    pub fn stats(&self) -> QueryStats {
+        let stats = self.counters.stats();
+        match &self.source {
+            Source::Nodes(nodes) => stats + nodes.counters.stats(),
+            Source::Values(values) => stats + values.counters.stats(),
+            Source::Done => stats,
+        }
+    }
+
+    /// Stops the query once it would visit more than `nodes` nodes below the root, bounding its
+    /// work regardless of the shape of the trie. Use [`is_truncated`](Self::is_truncated) to tell
+    /// a partial result from a complete one.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(&[1, 2], "foo");
+    /// trie.insert(&[1, 2, 3], "bar");
+    /// trie.insert(&[2], "baz");
+    ///
+    /// let mut supersets = trie.supersets(&[&2]).with_node_budget(2);
+    /// assert_eq!(supersets.by_ref().collect::<Vec<_>>(), vec![&"foo"]);
+    /// assert!(supersets.is_truncated());
+    /// ```
+    #[must_use]
+    pub fn with_node_budget(mut self, nodes: usize) -> Self {
+        if let Some(budget) = self.budget_mut() {
+            budget.limit_nodes(nodes);
+        }
+        self
+    }
+
+    /// Stops the query once `deadline` has passed. The clock is only read every so many visited
+    /// nodes, so the query may overrun the deadline slightly.
+    #[must_use]
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        if let Some(budget) = self.budget_mut() {
+            budget.limit_time(deadline);
+        }
+        self
+    }
+
+    /// Whether the query was stopped by its [node budget](Self::with_node_budget) or
+    /// [deadline](Self::with_deadline), in which case the values yielded so far are only part of
+    /// the result.
+    #[must_use]
+    pub const fn is_truncated(&self) -> bool {
+        match &self.source {
+            Source::Nodes(nodes) => nodes.budget.exceeded(),
+            Source::Values(values) => values.budget.exceeded(),
+            Source::Done => false,
+        }
+    }
+
+    const fn budget_mut(&mut self) -> Option<&mut Budget> {
+        match &mut self.source {
+            Source::Nodes(nodes) => Some(&mut nodes.budget),
+            Source::Values(values) => Some(&mut values.budget),
+            Source::Done => None,
+        }
+    }
+}
+
+impl<'a, 'b, K, T> Iterator for SuperSet<'a, 'b, K, T>
+where
+    K: Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                self.counters.yielded();
+                return Some(item);
+            }
+            self.leaves = match &mut self.source {
+                Source::Nodes(nodes) => nodes.next()?.1.leaves.iter(),
+                Source::Values(values) => {
+                    let item = values.next();
+                    if item.is_some() {
+                        self.counters.yielded();
+                    }
+                    return item;
+                }
+                Source::Done => return None,
+            };
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.source {
+            Source::Done => (self.leaves.len(), Some(self.leaves.len())),
+            _ => (self.leaves.len(), None),
+        }
+    }
+}
+
+/// DFS over the nodes whose key path is a superset of the query, yielding every node of which the
+/// depth lies within `lengths` together with its depth.
+///
+/// Every pending node carries the number of query keys on its path, so the next required key is
+/// known without looking ahead into its subtree. Each node is visited at most once.
+#[allow(clippy::redundant_pub_crate)]
+#[derive(Debug, Clone)]
+pub(crate) struct SuperSetNodes<'a, 'b, K, T> {
+    root: Option<&'a Node<K, T>>,
+    // the last yielded node, of which the children have not yet been pushed.
+    expand: Option<(usize, usize, &'a Node<K, T>)>,
+    // (depth, number of query keys on the path, key, node)
+    next: Vec<(usize, usize, &'a K, &'a Node<K, T>)>,
+    path: Vec<&'a K>,
+    keys: &'b [K],
+    lengths: RangeInclusive<usize>,
+    counters: Counters,
+    budget: Budget,
+}
+
+impl<'a, 'b, K, T> SuperSetNodes<'a, 'b, K, T>
+where
+    K: Ord,
+{
+    pub(crate) const fn new(
+        root: &'a Node<K, T>,
+        keys: &'b [K],
+        lengths: RangeInclusive<usize>,
+    ) -> Self {
+        SuperSetNodes {
+            root: Some(root),
+            expand: None,
+            next: vec![],
+            path: vec![],
+            keys,
+            lengths,
+            counters: Counters::new(),
+            budget: Budget::unlimited(),
+        }
+    }
+
+    /// The key path of the last yielded node.
+    pub(crate) fn path(&self) -> &[&'a K] {
+        &self.path
+    }
+
+    /// The first superset of the smallest length, and its values. As nodes are visited in
+    /// lexicographic order, every later set only needs to be considered if it is strictly smaller
+    /// than the best one so far, so the length range shrinks with each candidate. The search stops
+    /// once a candidate of the minimal length within the range is found.
+    pub(crate) fn smallest(mut self) -> Option<(Vec<&'a K>, &'a [T])> {
+        let min_len = *self.lengths.start();
+        let mut best = None;
+        while let Some((depth, node)) = self.next() {
+            if node.leaves.is_empty() {
+                continue;
+            }
+            best = Some((self.path.clone(), &node.leaves[..]));
+            if depth == min_len {
+                break;
+            }
+            self.lengths = min_len..=depth - 1;
+        }
+        best
+    }
+
+    /// Pushes the children of `node` which may still lead to a superset of the query.
+    fn push_children(&mut self, depth: usize, matched: usize, node: &'a Node<K, T>) {
+        let max_depth = *self.lengths.end();
+        // the remaining keys of the query need to fit below the children.
+        if depth >= max_depth || self.keys.len() - matched > max_depth - depth {
+            return;
+        }
+
+        match self.keys.get(matched) {
+            // every key of the query has been encountered, so any descendant is a superset.
+            None => {
+                self.counters.scan(node.children.len());
+                self.next.extend(
+                    node.children()
+                        .rev()
+                        .map(|(k, n)| (depth + 1, matched, k, n)),
+                );
+            }
+            // as children are sorted, only the children up to the next key of the query may
+            // still lead to a superset.
+            Some(want) => {
+                let len = match node.search(want) {
+                    Ok(idx) => {
+                        let (k, n) = node.child_at(idx);
+                        self.next.push((depth + 1, matched + 1, k, n));
+                        self.counters.scan(1);
+                        idx
+                    }
+                    Err(idx) => idx,
+                };
+                self.counters.scan(len);
+
+                self.next.extend(
+                    node.children()
+                        .take(len)
+                        .rev()
+                        .map(|(k, n)| (depth + 1, matched, k, n)),
+                );
+            }
+        }
+    }
+}
+
+impl<'a, K, T> Iterator for SuperSetNodes<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = (usize, &'a Node<K, T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(root) = self.root.take() {
+            self.counters.visit();
+            if self.keys.is_empty() && self.lengths.contains(&0) {
+                self.expand = Some((0, 0, root));
+                return Some((0, root));
+            }
+            self.push_children(0, 0, root);
+        }
+
+        if let Some((depth, matched, node)) = self.expand.take() {
+            self.push_children(depth, matched, node);
+        }
+
+        loop {
+            let (depth, matched, key, node) = self.next.pop()?;
+            if !self.budget.visit() {
+                self.next.clear();
+                return None;
+            }
+            self.counters.visit();
+            self.path.truncate(depth - 1);
+            self.path.push(key);
+
+            if matched == self.keys.len() && self.lengths.contains(&depth) {
+                self.expand = Some((depth, matched, node));
+                return Some((depth, node));
+            }
+            self.push_children(depth, matched, node);
+        }
+    }
+}
+
+impl<K, T> Node<K, T>
+where
+    K: Ord + Clone,
+    T: Clone,
+{
+    /// Copies the supersets of `keys` below this node which are no longer than `max_depth`,
+    /// leaving out the branches which do not hold any, and adds the copied sets to `sets`.
+    pub(crate) fn clone_supersets(
+        &self,
+        keys: &[K],
+        max_depth: usize,
+        sets: &mut SetCounts,
+    ) -> Self {
+        // (depth, number of query keys on the path)
+        self.copy_where(
+            sets,
+            (0, 0),
+            |&(_, matched)| matched == keys.len(),
+            |node, &(depth, matched)| {
+                // the remaining keys of the query need to fit below the children.
+                if depth >= max_depth || keys.len() - matched > max_depth - depth {
+                    return vec![];
+                }
+                // every descendant is a superset once every key of the query has been encountered,
+                // and otherwise only the children up to the next key of the query may lead to one.
+                let found = keys
+                    .get(matched)
+                    .map_or(Err(node.children.len()), |want| node.search(want));
+                let len = found.unwrap_or_else(|idx| idx);
+                let mut children: Vec<_> = node
+                    .children()
+                    .take(len)
+                    .map(|(k, n)| (k, n, (depth + 1, matched)))
+                    .collect();
+                if found.is_ok() {
+                    let (k, n) = node.child_at(len);
+                    children.push((k, n, (depth + 1, matched + 1)));
+                }
+                children
             },
+        )
+    }
+}
+
+/// A superset query which keeps its buffers between runs, created by
+/// [superset query](SetTrie::superset_query). Running many queries through the same
+/// `SuperSetQuery` only allocates until the buffers are large enough for the deepest traversal.
+#[derive(Debug, Clone)]
+pub struct SuperSetQuery<'a, K, T> {
+    root: &'a Node<K, T>,
+    max_depth: usize,
+    // (node, depth, number of query keys on the path)
+    next: Vec<(&'a Node<K, T>, usize, usize)>,
+}
+
+impl<'a, K, T> SuperSetQuery<'a, K, T>
+where
+    K: Ord,
+{
+    /// No stored set is longer than `max_depth`, so deeper branches are not traversed.
+    pub(crate) const fn new(root: &'a Node<K, T>, max_depth: usize) -> Self {
+        Self {
+            root,
+            max_depth,
+            next: vec![],
+        }
+    }
+
+    /// Iterates over all values stored under supersets of `keys`, in the same order as
+    /// [`SetTrie::supersets`].
+    pub fn run<'q, 'b>(&'q mut self, keys: &'b [K]) -> SuperSetQueryIter<'q, 'a, 'b, K, T> {
+        self.next.clear();
+        if keys.len() <= self.max_depth {
+            self.next.push((self.root, 0, 0));
+        }
+        SuperSetQueryIter {
+            leaves: [].iter(),
+            next: &mut self.next,
             keys,
+            max_depth: self.max_depth,
         }
     }
 }
 
-impl<'a, 'b, K, T> Iterator for SuperSet<'a, 'b, K, T>
+/// Iterator for [run](SuperSetQuery::run) method.
+#[derive(Debug)]
+pub struct SuperSetQueryIter<'q, 'a, 'b, K, T> {
+    leaves: std::slice::Iter<'a, T>,
+    next: &'q mut Vec<(&'a Node<K, T>, usize, usize)>,
+    keys: &'b [K],
+    max_depth: usize,
+}
+
+impl<'a, K, T> Iterator for SuperSetQueryIter<'_, 'a, '_, K, T>
 where
     K: Ord,
 {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let (encountered_first, is_superset, current) = self.current;
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some(item);
+            }
+            let (node, depth, matched) = self.next.pop()?;
+            if matched == self.keys.len() {
+                self.leaves = node.leaves.iter();
+            }
 
-        if is_superset && self.idx < current.leaves.len() {
-            self.idx += 1;
-            Some(&current.leaves[self.idx - 1])
-        } else if let (Some(first), Some(last)) = (self.keys.first(), self.keys.last()) {
+            // the remaining keys of the query need to fit below the children.
+            if depth >= self.max_depth || self.keys.len() - matched > self.max_depth - depth {
+                continue;
+            }
+            // every descendant is a superset once every key of the query has been encountered,
+            // and otherwise only the children up to the next key of the query may lead to one.
+            let found = self
+                .keys
+                .get(matched)
+                .map_or(Err(node.children.len()), |want| node.search(want));
+            let len = found.unwrap_or_else(|idx| idx);
+            if found.is_ok() {
+                self.next
+                    .push((node.child_at(len).1, depth + 1, matched + 1));
+            }
             self.next.extend(
-                current
-                    .children
-                    .iter()
-                    .map(|(k, n)| {
-                        (
-                            // If we have encountered a first, any child is a candidate. If our
-                            // own key is greater than the first key, and we have not yet
-                            // encountered the first key, then we can never be a superset.
-                            (n.has_descendant(first) || k == first || encountered_first)
-                                && (k <= first || encountered_first),
-                            k,
-                            n,
-                        )
-                    })
-                    .filter(|n| n.0)
-                    .rev(),
+                node.children()
+                    .take(len)
+                    .rev()
+                    .map(|(_, child)| (child, depth + 1, matched)),
             );
+        }
+    }
+}
+
+/// Iterator for [supersets grouped](SetTrie::supersets_grouped) method.
+#[derive(Debug, Clone)]
+pub struct SuperSetsGrouped<'a, 'b, K, T>(SuperSetNodes<'a, 'b, K, T>);
 
-            if let Some((b, k, n)) = self.next.pop() {
-                self.current = (b, k >= last || is_superset, n);
-                self.idx = 0;
-                return self.next();
+impl<'a, 'b, K, T> SuperSetsGrouped<'a, 'b, K, T>
+where
+    K: Ord,
+{
+    pub(crate) const fn new(root: &'a Node<K, T>, keys: &'b [K]) -> Self {
+        Self(SuperSetNodes::new(root, keys, 0..=usize::MAX))
+    }
+}
+
+impl<'a, K, T> Iterator for SuperSetsGrouped<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = (Vec<&'a K>, &'a [T]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (_, node) = self.0.next()?;
+            if !node.leaves.is_empty() {
+                return Some((self.0.path().to_vec(), &node.leaves));
+            }
+        }
+    }
+}
+
+/// Iterator for [superset keys](SetTrie::superset_keys) method.
+#[derive(Debug, Clone)]
+pub struct SuperSetKeys<'a, 'b, K, T>(SuperSetNodes<'a, 'b, K, T>);
+
+impl<'a, 'b, K, T> SuperSetKeys<'a, 'b, K, T>
+where
+    K: Ord,
+{
+    /// No stored set is longer than `max_depth`, so deeper branches are not traversed.
+    pub(crate) const fn new(root: &'a Node<K, T>, keys: &'b [K], max_depth: usize) -> Self {
+        Self(SuperSetNodes::new(root, keys, 0..=max_depth))
+    }
+}
+
+impl<'a, K, T> Iterator for SuperSetKeys<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = Vec<&'a K>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (_, node) = self.0.next()?;
+            if !node.leaves.is_empty() {
+                return Some(self.0.path().to_vec());
+            }
+        }
+    }
+}
+
+/// A node which is yet to be visited: (depth, number of query keys on the path, key, node).
+type Pending<'a, K, T> = (usize, usize, Option<&'a K>, &'a Node<K, T>);
+
+/// Iterator for [minimal supersets](SetTrie::minimal_supersets) method.
+///
+/// The supersets of the query are visited in pre-order, taking the children of every node in
+/// descending order of their keys. No stored set is descended into, as all sets below it contain
+/// it. Any other stored subset of a stored set `S` takes a larger key than `S` where their paths
+/// part, so it is visited before `S`, and it suffices to compare every stored set with the minimal
+/// supersets found so far.
+#[derive(Debug, Clone)]
+pub struct MinimalSuperSets<'a, 'b, K, T> {
+    leaves: std::slice::Iter<'a, T>,
+    next: Vec<Pending<'a, K, T>>,
+    path: Vec<&'a K>,
+    keys: &'b [K],
+    found: Vec<Vec<&'a K>>,
+}
+
+impl<'a, 'b, K, T> MinimalSuperSets<'a, 'b, K, T>
+where
+    K: Ord,
+{
+    pub(crate) fn new(trie: &'a SetTrie<K, T>, keys: &'b [K]) -> Self {
+        MinimalSuperSets {
+            leaves: [].iter(),
+            next: vec![(0, 0, None, &trie.root)],
+            path: vec![],
+            keys,
+            found: vec![],
+        }
+    }
+}
+
+impl<'a, K, T> Iterator for MinimalSuperSets<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some(item);
+            }
+
+            let (depth, matched, key, node) = self.next.pop()?;
+            self.path.truncate(depth.saturating_sub(1));
+            self.path.extend(key);
+
+            if matched == self.keys.len() && !node.leaves.is_empty() {
+                let path = &self.path;
+                if !self.found.iter().any(|set| is_subset::<K, _, _>(set, path)) {
+                    self.found.push(self.path.clone());
+                    self.leaves = node.leaves.iter();
+                }
+                continue;
             }
-            None
-        // empty set is queried, thus we can include every single item
-        } else {
-            let next = current.children.iter().map(|(k, n)| (true, k, n));
-            self.next.extend(next.rev());
 
-            if let Some((b, _, n)) = self.next.pop() {
-                self.current = (b, true, n);
-                self.idx = 0;
-                return self.next();
+            // children past the next key of the query can no longer contain it.
+            let end = self.keys.get(matched).map_or(node.keys.len(), |next| {
+                node.search(next).map_or_else(|idx| idx, |idx| idx + 1)
+            });
+            for (key, child) in node.children().take(end) {
+                let matched = matched + usize::from(self.keys.get(matched) == Some(key));
+                self.next.push((depth + 1, matched, Some(key), child));
             }
+        }
+    }
+}
+
+/// Pairs of (query, number of matched keys).
+type Progress = Vec<(usize, usize)>;
+
+/// Iterator for [supersets of any](SetTrie::supersets_of_any) method.
+#[derive(Debug, Clone)]
+pub struct SuperSetsOfAny<'a, 'b, K, T> {
+    queries: &'b [&'b [K]],
+    leaves: std::slice::Iter<'a, T>,
+    // every node is paired with the progress of the queries which can still
+    // be matched below it. Once a query is fully matched, the list is emptied: every set below the
+    // node is a match.
+    next: Vec<(&'a Node<K, T>, Progress)>,
+}
+
+impl<'a, 'b, K, T> SuperSetsOfAny<'a, 'b, K, T>
+where
+    K: Ord,
+{
+    pub(crate) fn new(root: &'a Node<K, T>, queries: &'b [&'b [K]]) -> Self {
+        let mut next = vec![];
+        if queries.iter().any(|q| q.is_empty()) {
+            next.push((root, vec![]));
+        } else if !queries.is_empty() {
+            next.push((root, (0..queries.len()).map(|i| (i, 0)).collect()));
+        }
+
+        SuperSetsOfAny {
+            queries,
+            leaves: [].iter(),
+            next,
+        }
+    }
+
+    /// The progress of the queries below the child `key`, or `None` if none of them can be
+    /// matched anymore.
+    fn advance(&self, progress: &[(usize, usize)], key: &K) -> Option<Progress> {
+        let mut remaining = Vec::with_capacity(progress.len());
+        for &(query, matched) in progress {
+            let query_keys = self.queries[query];
+            match key.cmp(&query_keys[matched]) {
+                Ordering::Less => remaining.push((query, matched)),
+                Ordering::Equal if matched + 1 == query_keys.len() => return Some(vec![]),
+                Ordering::Equal => remaining.push((query, matched + 1)),
+                Ordering::Greater => (),
+            }
+        }
+
+        if remaining.is_empty() {
             None
+        } else {
+            Some(remaining)
         }
     }
+}
 
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        (self.current.2.leaves.len() - self.idx, None)
+impl<'a, K, T> Iterator for SuperSetsOfAny<'a, '_, K, T>
+where
+    K: Ord,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaves.next() {
+                return Some(item);
+            }
+
+            let (node, progress) = self.next.pop()?;
+            for (key, child) in node.children().rev() {
+                if progress.is_empty() {
+                    self.next.push((child, vec![]));
+                } else if let Some(remaining) = self.advance(&progress, key) {
+                    self.next.push((child, remaining));
+                }
+            }
+
+            if progress.is_empty() {
+                self.leaves = node.leaves.iter();
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::SetTrie;
+    use std::time::Instant;
 
     #[test]
     fn superset_small() {
@@ -133,6 +716,286 @@ mod tests {
         );
     }
 
+    #[test]
+    fn superset_requires_every_key() {
+        let mut v = SetTrie::new();
+        v.insert(&[1, 3], 'a');
+        v.insert(&[1, 2, 3], 'b');
+        v.insert(&[0, 1, 2, 3], 'c');
+        v.insert(&[1, 2, 4], 'd');
+
+        assert_eq!(
+            v.supersets(&[&1, &2, &3]).collect::<Vec<_>>(),
+            vec![&'c', &'b']
+        );
+        assert_eq!(v.supersets(&[&2, &3]).collect::<Vec<_>>(), vec![&'c', &'b']);
+    }
+
+    #[test]
+    fn supersets_fast_paths() {
+        let mut v = SetTrie::new();
+        assert_eq!(v.supersets(&[]).size_hint(), (0, Some(0)));
+        assert_eq!(v.supersets(&[&1]).count(), 0);
+
+        v.insert(&[], 'a');
+        assert_eq!(v.supersets(&[]).collect::<Vec<_>>(), vec![&'a']);
+        assert_eq!(v.supersets(&[&1]).count(), 0);
+
+        v.insert(&[1, 2], 'b');
+        v.insert(&[2], 'c');
+        assert_eq!(
+            v.supersets(&[]).collect::<Vec<_>>(),
+            v.values().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            v.supersets_with_len(&[], 1..).collect::<Vec<_>>(),
+            vec![&'b', &'c']
+        );
+    }
+
+    #[test]
+    fn supersets_with_len() {
+        let mut v = SetTrie::new();
+        v.insert(&[1], 'a');
+        v.insert(&[1, 2], 'b');
+        v.insert(&[0, 1, 2], 'c');
+        v.insert(&[1, 2, 3, 4], 'd');
+
+        let q = [&1, &2];
+        assert_eq!(
+            v.supersets_with_len(&q, ..=3).collect::<Vec<_>>(),
+            vec![&'c', &'b']
+        );
+        assert_eq!(
+            v.supersets_with_len(&q, 4..).collect::<Vec<_>>(),
+            vec![&'d']
+        );
+        assert_eq!(v.supersets_with_len(&q, ..2).count(), 0);
+    }
+
+    #[test]
+    fn minimal_supersets() {
+        let mut v = SetTrie::new();
+        v.insert(&[1, 2], 'a');
+        v.insert(&[1, 2, 3], 'b');
+        v.insert(&[0, 1, 3], 'c');
+        v.insert(&[0, 1, 2, 4], 'd');
+        v.insert(&[2, 3], 'e');
+        v.insert(&[1, 3], 'f');
+        v.insert(&[1, 3], 'g');
+
+        assert_eq!(
+            v.minimal_supersets(&[&1]).collect::<Vec<_>>(),
+            vec![&'f', &'g', &'a']
+        );
+        assert_eq!(
+            v.minimal_supersets(&[&2, &3]).collect::<Vec<_>>(),
+            vec![&'e']
+        );
+        assert_eq!(
+            v.minimal_supersets(&[&0]).collect::<Vec<_>>(),
+            vec![&'c', &'d']
+        );
+    }
+
+    #[test]
+    fn supersets_of_any() {
+        let mut v = SetTrie::new();
+        v.insert(&[], 'a');
+        v.insert(&[1], 'b');
+        v.insert(&[1, 2], 'c');
+        v.insert(&[1, 2, 3], 'd');
+        v.insert(&[2, 3], 'e');
+        v.insert(&[3], 'f');
+
+        assert_eq!(
+            v.supersets_of_any(&[&[&1, &2], &[&3]]).collect::<Vec<_>>(),
+            vec![&'c', &'d', &'e', &'f']
+        );
+        assert_eq!(v.supersets_of_any(&[&[&2], &[]]).count(), 6);
+        assert_eq!(v.supersets_of_any(&[]).count(), 0);
+    }
+
+    #[test]
+    fn supersets_with_wildcards() {
+        let mut v = SetTrie::new();
+        v.insert(&[1], 'a');
+        v.insert(&[1, 2], 'b');
+        v.insert(&[1, 2, 3], 'c');
+        v.insert(&[2, 3], 'd');
+
+        assert_eq!(
+            v.supersets_with_wildcards(&[&1], 1).collect::<Vec<_>>(),
+            vec![&'b', &'c']
+        );
+        assert_eq!(
+            v.supersets_with_wildcards(&[&3], 2).collect::<Vec<_>>(),
+            vec![&'c']
+        );
+        assert_eq!(v.supersets_with_wildcards(&[], 2).count(), 3);
+        assert_eq!(v.supersets_with_wildcards(&[&1], 0).count(), 3);
+        assert_eq!(v.supersets_with_wildcards(&[&1], usize::MAX).count(), 0);
+    }
+
+    #[test]
+    fn least_specific_superset() {
+        let mut v = SetTrie::new();
+        assert_eq!(v.least_specific_superset(&[]), None);
+
+        v.insert(&[1, 2, 3], 'a');
+        v.insert(&[1, 3, 4], 'b');
+        v.insert(&[2, 3], 'c');
+        v.insert(&[1, 2, 3, 4], 'd');
+        v.entry(vec![&3]).or_create();
+
+        assert_eq!(
+            v.least_specific_superset(&[&3]),
+            Some((vec![&&2, &&3], &['c'][..]))
+        );
+        assert_eq!(
+            v.least_specific_superset(&[&1]),
+            Some((vec![&&1, &&2, &&3], &['a'][..]))
+        );
+        assert_eq!(
+            v.least_specific_superset(&[&4]),
+            Some((vec![&&1, &&3, &&4], &['b'][..]))
+        );
+        assert_eq!(v.least_specific_superset(&[&5]), None);
+        assert_eq!(v.least_specific_superset(&[&1, &2, &3, &4, &5]), None);
+    }
+
+    #[test]
+    fn superset_query() {
+        let mut v = SetTrie::new();
+        v.insert(vec![], 'a');
+        v.insert(vec![1, 2], 'b');
+        v.insert(vec![1, 3], 'c');
+        v.insert(vec![2, 3], 'd');
+        v.insert(vec![1, 2, 3], 'e');
+
+        let mut query = v.superset_query();
+        for keys in [
+            vec![],
+            vec![1],
+            vec![2, 3],
+            vec![4],
+            vec![1, 2, 3, 4],
+            vec![3],
+        ] {
+            assert_eq!(
+                query.run(&keys).collect::<Vec<_>>(),
+                v.supersets(&keys).collect::<Vec<_>>()
+            );
+        }
+        assert_eq!(query.run(&[]).next(), Some(&'a'));
+        assert_eq!(query.run(&[1, 2]).collect::<Vec<_>>(), vec![&'b', &'e']);
+    }
+
+    #[test]
+    fn clone_supersets() {
+        let mut v = SetTrie::new();
+        v.insert(vec![], 'a');
+        v.insert(vec![1, 2], 'b');
+        v.insert(vec![1, 2, 3], 'c');
+        v.insert(vec![1, 3], 'd');
+        v.insert(vec![2, 4], 'e');
+        v.entry(vec![2, 5]).or_create();
+
+        let copy = v.clone_supersets(&[2]);
+        assert_eq!(copy.set_count(), 3);
+        assert_eq!(copy.check_invariants(), Ok(()));
+        assert_eq!(
+            copy.iter().collect::<Vec<_>>(),
+            vec![
+                (vec![&1, &2], &'b'),
+                (vec![&1, &2, &3], &'c'),
+                (vec![&2, &4], &'e')
+            ]
+        );
+
+        assert_eq!(v.clone_supersets(&[]).set_count(), v.set_count());
+        let empty = v.clone_supersets(&[1, 2, 3, 4]);
+        assert_eq!(empty.set_count(), 0);
+        assert_eq!(empty.check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn supersets_beyond_max_depth() {
+        let mut v = SetTrie::new();
+        assert_eq!(v.max_depth(), 0);
+        v.insert(&[1, 2, 3], 'a');
+        v.insert(&[1, 2], 'b');
+        v.insert(&[2], 'c');
+        assert_eq!(v.max_depth(), 3);
+
+        assert_eq!(v.supersets(&[&1, &2, &3]).collect::<Vec<_>>(), vec![&'a']);
+        assert_eq!(v.supersets(&[&1, &2, &3, &4]).size_hint(), (0, Some(0)));
+        assert_eq!(v.supersets_with_len(&[&2], 4..).count(), 0);
+        assert_eq!(v.drain_supersets(&[&0, &1, &2, &3]).count(), 0);
+
+        v.remove(&[&1, &2, &3]);
+        assert_eq!(v.max_depth(), 2);
+        assert_eq!(v.supersets(&[&1, &2, &3]).count(), 0);
+        assert_eq!(v.supersets(&[&1, &2]).collect::<Vec<_>>(), vec![&'b']);
+    }
+
+    #[test]
+    fn superset_keys() {
+        let mut v = SetTrie::new();
+        v.insert(&[], 'a');
+        v.insert(&[1, 2], 'b');
+        v.insert(&[1, 2], 'c');
+        v.insert(&[1, 2, 3], 'd');
+        v.entry(vec![&2, &3]).or_create();
+
+        assert_eq!(
+            v.superset_keys(&[&2]).collect::<Vec<_>>(),
+            vec![vec![&&1, &&2], vec![&&1, &&2, &&3]]
+        );
+        assert_eq!(v.superset_keys(&[]).count(), 3);
+        assert_eq!(v.superset_keys(&[&0, &1, &2, &3]).count(), 0);
+    }
+
+    #[test]
+    fn supersets_grouped() {
+        let mut v = SetTrie::new();
+        v.insert(&[], 'a');
+        v.insert(&[1], 'b');
+        v.insert(&[1, 2], 'c');
+        v.insert(&[1, 2], 'd');
+
+        assert_eq!(
+            v.supersets_grouped(&[]).collect::<Vec<_>>(),
+            vec![
+                (vec![], &['a'][..]),
+                (vec![&&1], &['b'][..]),
+                (vec![&&1, &&2], &['c', 'd'][..])
+            ]
+        );
+        assert_eq!(v.supersets_grouped(&[&3]).count(), 0);
+    }
+
+    #[test]
+    fn supersets_with_budget() {
+        let mut v = SetTrie::new();
+        v.insert(&[1], 'a');
+        v.insert(&[1, 2], 'b');
+        v.insert(&[2], 'c');
+
+        // the fast path of the empty query is bounded as well.
+        let mut all = v.supersets(&[]).with_node_budget(2);
+        assert_eq!(all.by_ref().collect::<Vec<_>>(), vec![&'a', &'b']);
+        assert!(all.is_truncated());
+
+        let mut complete = v.supersets(&[&2]).with_node_budget(3);
+        assert_eq!(complete.by_ref().count(), 2);
+        assert!(!complete.is_truncated());
+
+        let mut expired = v.supersets(&[&2]).with_deadline(Instant::now());
+        assert_eq!(expired.by_ref().count(), 0);
+        assert!(expired.is_truncated());
+    }
+
     mod proptest {
         use crate::SetTrie;
         use ::proptest::prelude::*;
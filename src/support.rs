@@ -0,0 +1,71 @@
+use crate::SetTrie;
+
+impl<K, T> SetTrie<K, T>
+where
+    K: Ord,
+{
+    /// The number of values stored under sets starting with `prefix`, including `prefix` itself.
+    /// Equal to `self.with_prefix(prefix).count()`, but found in `O(depth)`, as every node counts
+    /// the values at or below it.
+    ///
+    /// This makes the trie usable as the counting structure of frequent itemset mining, such as
+    /// Apriori or Eclat, where every transaction is inserted as a sorted set. The support of a set
+    /// which is not a prefix, such as the number of transactions containing an arbitrary itemset,
+    /// is still found with [supersets](Self::supersets).
+    ///
+    /// ```rust
+    /// let mut transactions = set_trie::SetTrie::new();
+    /// transactions.insert(vec!["bread", "butter", "milk"], 1);
+    /// transactions.insert(vec!["bread", "butter"], 2);
+    /// transactions.insert(vec!["bread", "jam"], 3);
+    ///
+    /// assert_eq!(transactions.support(&["bread"]), 3);
+    /// assert_eq!(transactions.support(&["bread", "butter"]), 2);
+    /// assert_eq!(transactions.supersets(&["butter"]).count(), 2);
+    /// ```
+    #[must_use]
+    pub fn support(&self, prefix: &[K]) -> usize {
+        let mut node = &self.root;
+        let mut pending = self.pending.root();
+        for key in prefix {
+            let Ok(idx) = node.search(key) else {
+                return 0;
+            };
+            node = &node.children[idx];
+            pending = pending.child(idx);
+        }
+        pending.apply(node.counts).values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SetTrie;
+
+    #[test]
+    fn support() {
+        let mut trie = SetTrie::with_pruning();
+        trie.insert(vec![], 'a');
+        trie.insert(vec![1, 2], 'b');
+        trie.insert(vec![1, 2], 'c');
+        trie.insert(vec![1, 2, 3], 'd');
+        trie.insert(vec![1, 3], 'e');
+        trie.insert(vec![2], 'f');
+
+        let prefixes: [&[u32]; 7] = [&[], &[1], &[1, 2], &[1, 2, 3], &[1, 3], &[2], &[3]];
+        for prefix in prefixes {
+            assert_eq!(trie.support(prefix), trie.with_prefix(prefix).count());
+        }
+        assert_eq!(trie.support(&[1, 2]), 3);
+
+        assert_eq!(trie.remove(&[1, 2]), Some(vec!['b', 'c']));
+        assert_eq!(trie.remove(&[1, 2]), None);
+        assert_eq!(trie.remove(&[1, 3]), Some(vec!['e']));
+        for prefix in prefixes {
+            assert_eq!(trie.support(prefix), trie.with_prefix(prefix).count());
+        }
+        assert_eq!(trie.support(&[1, 2, 3]), 1);
+        assert_eq!(trie.support(&[1, 3]), 0);
+        assert_eq!(trie.set_count(), 3);
+    }
+}
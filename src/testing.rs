@@ -0,0 +1,137 @@
+//! Proptest strategies and reference implementations for property testing code built on top of
+//! [`SetTrie`], available behind the `testing` feature.
+//!
+//! ```rust
+//! use proptest::prelude::*;
+//! use set_trie::{testing, SetTrie};
+//!
+//! proptest! {
+//!     fn subsets(
+//!         entries in testing::entries(0..16u8, any::<u32>(), 0..32, 0..8),
+//!         query in testing::sorted_set(0..16u8, 0..8),
+//!     ) {
+//!         let trie: SetTrie<_, _> = entries.iter().cloned().collect();
+//!         let mut got: Vec<_> = trie.subsets(&query).collect();
+//!         let mut want: Vec<_> = testing::subsets(&entries, &query).collect();
+//!         got.sort();
+//!         want.sort();
+//!         prop_assert_eq!(got, want);
+//!     }
+//! }
+//! # subsets();
+//! ```
+
+use crate::SetTrie;
+use proptest::collection::{btree_set, vec, SizeRange};
+use proptest::strategy::Strategy;
+use std::fmt::Debug;
+
+/// Strategy generating sorted sets of distinct keys, usable both as the key of a set and as a
+/// query.
+pub fn sorted_set<K>(key: K, len: impl Into<SizeRange>) -> impl Strategy<Value = Vec<K::Value>>
+where
+    K: Strategy,
+    K::Value: Ord,
+{
+    btree_set(key, len).prop_map(|set| set.into_iter().collect())
+}
+
+/// Strategy generating `(keys, value)` pairs, with `len` pairs of at most `set_len` keys each.
+/// The same set may occur multiple times.
+pub fn entries<K, T>(
+    key: K,
+    value: T,
+    len: impl Into<SizeRange>,
+    set_len: impl Into<SizeRange>,
+) -> impl Strategy<Value = Vec<(Vec<K::Value>, T::Value)>>
+where
+    K: Strategy,
+    K::Value: Ord,
+    T: Strategy,
+{
+    vec((sorted_set(key, set_len), value), len)
+}
+
+/// Strategy generating tries. Use [`entries`] instead to keep the inserted pairs around for
+/// comparison against [`subsets`] and [`supersets`].
+pub fn set_trie<K, T>(
+    key: K,
+    value: T,
+    len: impl Into<SizeRange>,
+    set_len: impl Into<SizeRange>,
+) -> impl Strategy<Value = SetTrie<K::Value, T::Value>>
+where
+    K: Strategy,
+    K::Value: Ord + Clone,
+    T: Strategy,
+    T::Value: Debug,
+{
+    entries(key, value, len, set_len).prop_map(|entries| entries.into_iter().collect())
+}
+
+/// Returns whether every key of the sorted set `a` occurs in the sorted set `b`.
+pub fn is_subset<K: Ord>(a: &[K], b: &[K]) -> bool {
+    let mut b = b.iter();
+    a.iter().all(|k| b.any(|other| other == k))
+}
+
+/// Reference implementation of [`SetTrie::subsets`]: the values of all `entries` whose set is a
+/// subset of `keys`, in order of `entries`.
+pub fn subsets<'a, K: Ord, T>(
+    entries: &'a [(Vec<K>, T)],
+    keys: &'a [K],
+) -> impl Iterator<Item = &'a T> {
+    entries
+        .iter()
+        .filter(move |(set, _)| is_subset(set, keys))
+        .map(|(_, value)| value)
+}
+
+/// Reference implementation of [`SetTrie::supersets`]: the values of all `entries` whose set is
+/// a superset of `keys`, in order of `entries`.
+pub fn supersets<'a, K: Ord, T>(
+    entries: &'a [(Vec<K>, T)],
+    keys: &'a [K],
+) -> impl Iterator<Item = &'a T> {
+    entries
+        .iter()
+        .filter(move |(set, _)| is_subset(keys, set))
+        .map(|(_, value)| value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing;
+    use crate::SetTrie;
+    use proptest::prelude::*;
+
+    #[test]
+    fn is_subset() {
+        assert!(testing::is_subset::<i32>(&[], &[]));
+        assert!(testing::is_subset(&[1, 3], &[1, 2, 3]));
+        assert!(!testing::is_subset(&[1, 4], &[1, 2, 3]));
+        assert!(!testing::is_subset(&[1, 2, 3], &[1, 3]));
+    }
+
+    proptest! {
+        #[test]
+        fn reference(
+            entries in testing::entries(0..16i32, any::<u16>(), 0..32, 0..8),
+            query in testing::sorted_set(0..16i32, 0..8),
+        ) {
+            let trie: SetTrie<_, _> = entries.iter().cloned().collect();
+
+            let mut got: Vec<_> = trie.subsets(&query).collect();
+            let mut want: Vec<_> = testing::subsets(&entries, &query).collect();
+            got.sort();
+            want.sort();
+            prop_assert_eq!(got, want);
+
+            let mut got: Vec<_> = trie.supersets(&query).collect();
+            let mut want: Vec<_> = testing::supersets(&entries, &query).collect();
+            got.sort();
+            want.sort();
+            prop_assert_eq!(got, want);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+/// Heap entry ordered by score. On equal scores, the item seen first ranks higher, which keeps the
+/// results stable with respect to the traversal order.
+struct Ranked<S, I> {
+    score: S,
+    seq: usize,
+    item: I,
+}
+
+impl<S: Ord, I> Ord for Ranked<S, I> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .cmp(&other.score)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl<S: Ord, I> PartialOrd for Ranked<S, I> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S: Ord, I> PartialEq for Ranked<S, I> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<S: Ord, I> Eq for Ranked<S, I> {}
+
+/// Selects the `k` highest scoring items, best first, while holding at most `k` items in memory.
+pub fn top_k<'a, T, S, F>(items: impl Iterator<Item = &'a T>, k: usize, mut score: F) -> Vec<&'a T>
+where
+    T: 'a,
+    S: Ord,
+    F: FnMut(&T) -> S,
+{
+    if k == 0 {
+        return vec![];
+    }
+
+    // min-heap on the rank, so the worst of the current best k is always on top.
+    let mut heap = BinaryHeap::with_capacity(k);
+    for (seq, item) in items.enumerate() {
+        let ranked = Ranked {
+            score: score(item),
+            seq,
+            item,
+        };
+
+        if heap.len() < k {
+            heap.push(Reverse(ranked));
+        } else if let Some(mut worst) = heap.peek_mut() {
+            if ranked > worst.0 {
+                *worst = Reverse(ranked);
+            }
+        }
+    }
+
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(ranked)| ranked.item)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SetTrie;
+
+    #[test]
+    fn top_k() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[1], 3);
+        trie.insert(&[1, 2], 7);
+        trie.insert(&[1, 2, 3], 1);
+        trie.insert(&[2], 7);
+        trie.insert(&[3], 5);
+
+        assert_eq!(
+            trie.subsets_top_k(&[&1, &2, &3], 3, |v| *v),
+            vec![&7, &7, &5]
+        );
+        assert_eq!(trie.subsets_top_k(&[&1, &2], 10, |v| *v), vec![&7, &7, &3]);
+        assert_eq!(
+            trie.subsets_top_k(&[&1, &2, &3], 0, |v| *v),
+            Vec::<&i32>::new()
+        );
+        assert_eq!(trie.supersets_top_k(&[&1], 2, |v| -v), vec![&1, &3]);
+    }
+}
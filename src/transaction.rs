@@ -0,0 +1,125 @@
+use crate::SetTrie;
+
+#[derive(Debug)]
+enum Op<K, T> {
+    Insert(Vec<K>, T),
+    Remove(Vec<K>),
+}
+
+/// A batch of insertions and removals, created by [transaction](SetTrie::transaction).
+///
+/// Operations are buffered, and only applied to the trie by [commit](Self::commit). Dropping the
+/// transaction without committing it discards them, so that a bulk update which fails half way
+/// leaves the trie untouched. Until then the trie can still be read through [trie](Self::trie).
+#[derive(Debug)]
+#[must_use = "operations are only applied by committing the transaction"]
+pub struct Transaction<'a, K, T> {
+    trie: &'a mut SetTrie<K, T>,
+    ops: Vec<Op<K, T>>,
+}
+
+impl<'a, K, T> Transaction<'a, K, T>
+where
+    K: Ord,
+{
+    pub(crate) const fn new(trie: &'a mut SetTrie<K, T>) -> Self {
+        Self { trie, ops: vec![] }
+    }
+
+    /// The trie as it was before the transaction.
+    #[must_use]
+    pub const fn trie(&self) -> &SetTrie<K, T> {
+        self.trie
+    }
+
+    /// The number of buffered operations.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Whether no operations have been buffered.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Buffers the insertion of the item in the given set. See [`SetTrie::insert`].
+    pub fn insert(&mut self, keys: impl IntoIterator<Item = K>, item: T) -> &mut Self {
+        self.ops.push(Op::Insert(keys.into_iter().collect(), item));
+        self
+    }
+
+    /// Buffers the removal of the given set. See [`SetTrie::remove`].
+    pub fn remove(&mut self, keys: impl IntoIterator<Item = K>) -> &mut Self {
+        self.ops.push(Op::Remove(keys.into_iter().collect()));
+        self
+    }
+
+    /// Applies the buffered operations in order, returning the values taken by every removal.
+    // committing only for the effect on the trie is the common case.
+    #[allow(clippy::must_use_candidate)]
+    pub fn commit(self) -> Vec<Option<Vec<T>>>
+    where
+        K: Clone,
+    {
+        let mut removed = vec![];
+        for op in self.ops {
+            match op {
+                Op::Insert(keys, item) => {
+                    self.trie.insert(keys, item);
+                }
+                Op::Remove(keys) => removed.push(self.trie.remove(&keys)),
+            }
+        }
+        removed
+    }
+}
+
+impl<K, T> SetTrie<K, T>
+where
+    K: Ord,
+{
+    /// Starts a [`Transaction`], which applies a batch of insertions and removals all at once on
+    /// commit, or not at all if it is dropped.
+    ///
+    /// ```rust
+    /// let mut trie = set_trie::SetTrie::new();
+    /// trie.insert(vec![1], "foo");
+    ///
+    /// let mut transaction = trie.transaction();
+    /// transaction.insert(vec![1, 2], "bar").remove(vec![1]);
+    /// drop(transaction);
+    /// assert_eq!(trie.values().collect::<Vec<_>>(), vec![&"foo"]);
+    ///
+    /// let mut transaction = trie.transaction();
+    /// transaction.insert(vec![1, 2], "bar").remove(vec![1]);
+    /// assert_eq!(transaction.commit(), vec![Some(vec!["foo"])]);
+    /// assert_eq!(trie.values().collect::<Vec<_>>(), vec![&"bar"]);
+    /// ```
+    pub const fn transaction(&mut self) -> Transaction<'_, K, T> {
+        Transaction::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SetTrie;
+
+    #[test]
+    fn transaction() {
+        let mut trie = SetTrie::new();
+        trie.insert(vec![1, 2], 'a');
+
+        let mut transaction = trie.transaction();
+        transaction.insert(vec![1], 'b');
+        transaction.remove(vec![1, 2]);
+        transaction.insert(vec![1, 2], 'c');
+        transaction.remove(vec![3]);
+        assert_eq!(transaction.len(), 4);
+        assert_eq!(transaction.trie().values().count(), 1);
+
+        assert_eq!(transaction.commit(), vec![Some(vec!['a']), None]);
+        assert_eq!(trie.values().collect::<Vec<_>>(), vec![&'b', &'c']);
+    }
+}
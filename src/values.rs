@@ -1,4 +1,6 @@
-use crate::{Node, SetTrie};
+use crate::budget::Budget;
+use crate::stats::Counters;
+use crate::Node;
 
 /// Iterator for [`SetTrie::values`].
 #[derive(Debug, Clone)]
@@ -6,15 +8,19 @@ pub struct Values<'a, K, T> {
     idx: usize,
     current: &'a Node<K, T>,
     nodes: Vec<&'a Node<K, T>>,
+    pub(crate) counters: Counters,
+    pub(crate) budget: Budget,
 }
 
 impl<'a, K, T> Values<'a, K, T> {
     #[must_use]
-    pub(crate) const fn new(trie: &SetTrie<K, T>) -> Values<K, T> {
+    pub(crate) const fn new(root: &'a Node<K, T>) -> Self {
         Values {
             idx: 0,
-            current: &trie.0,
+            current: root,
             nodes: vec![],
+            counters: Counters::from_root(),
+            budget: Budget::unlimited(),
         }
     }
 }
@@ -23,24 +29,105 @@ impl<'a, K, T> Iterator for Values<'a, K, T> {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.idx < self.current.leaves.len() {
-            self.idx += 1;
-            Some(&self.current.leaves[self.idx - 1])
-        } else {
-            self.nodes
-                .extend(self.current.children.iter().map(|n| &n.1).rev());
-            if let Some(next) = self.nodes.pop() {
-                self.current = next;
-                self.idx = 0;
-                return self.next();
+        while self.idx >= self.current.leaves.len() {
+            self.counters.scan(self.current.children.len());
+            self.nodes.extend(self.current.children.iter().rev());
+            let next = self.nodes.pop()?;
+            if !self.budget.visit() {
+                self.nodes.clear();
+                return None;
             }
-            None
+            self.current = next;
+            self.counters.visit();
+            self.idx = 0;
         }
+        self.idx += 1;
+        Some(&self.current.leaves[self.idx - 1])
     }
 }
 
+/// Iterator for [with prefix](crate::SetTrie::with_prefix) method.
+#[derive(Debug, Clone)]
+pub struct WithPrefix<'a, K, T>(Option<Values<'a, K, T>>);
+
+impl<'a, K, T> WithPrefix<'a, K, T> {
+    pub(crate) fn new(node: Option<&'a Node<K, T>>) -> Self {
+        Self(node.map(Values::new))
+    }
+}
+
+impl<'a, K, T> Iterator for WithPrefix<'a, K, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.as_mut()?.next()
+    }
+}
+
+/// Iterator for [matching](crate::SetTrie::matching) method.
+#[derive(Debug, Clone)]
+pub struct Matching<'a, T>(std::slice::Iter<'a, T>);
+
+impl<'a, T> Matching<'a, T> {
+    pub(crate) fn new(leaves: Option<&'a [T]>) -> Self {
+        Self(leaves.unwrap_or_default().iter())
+    }
+}
+
+impl<'a, T> Iterator for Matching<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for Matching<'_, T> {}
+
 #[cfg(test)]
 mod tests {
+    use crate::SetTrie;
+
+    #[test]
+    fn with_prefix() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[], 'a');
+        trie.insert(&[1], 'b');
+        trie.insert(&[1, 2], 'c');
+        trie.insert(&[1, 3, 4], 'd');
+        trie.insert(&[2, 3], 'e');
+
+        assert_eq!(
+            trie.with_prefix(&[&1]).collect::<Vec<_>>(),
+            vec![&'b', &'c', &'d']
+        );
+        assert_eq!(trie.with_prefix(&[&1, &3]).collect::<Vec<_>>(), vec![&'d']);
+        assert_eq!(trie.with_prefix(&[&3]).count(), 0);
+        assert_eq!(trie.with_prefix(&[]).count(), 5);
+    }
+
+    #[test]
+    fn matching() {
+        let mut trie = SetTrie::new();
+        trie.insert(&[], 'a');
+        trie.insert(&[1], 'b');
+        trie.insert(&[1, 2], 'c');
+        trie.insert(&[1, 2], 'd');
+        trie.insert(&[1, 2, 3], 'e');
+
+        assert_eq!(trie.matching(&[]).collect::<Vec<_>>(), vec![&'a']);
+        assert_eq!(
+            trie.matching(&[&1, &2]).collect::<Vec<_>>(),
+            vec![&'c', &'d']
+        );
+        assert_eq!(trie.matching(&[&1, &2]).len(), 2);
+        assert_eq!(trie.matching(&[&2]).len(), 0);
+    }
+
     mod proptest {
         use crate::SetTrie;
         use ::proptest::prelude::*;
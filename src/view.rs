@@ -0,0 +1,224 @@
+use crate::{is_subset, Observer, SetTrie};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Subsets,
+    Supersets,
+}
+
+/// The result of a subset or superset query, kept up to date as values are inserted and removed,
+/// so that reading it costs nothing and every write only costs the change it makes.
+///
+/// A view is created from the current state of a trie, and is then kept in sync as the
+/// [`Observer`] of an [`ObservedSetTrie`](crate::ObservedSetTrie) wrapping that trie.
+///
+/// ```rust
+/// use set_trie::{MaterializedView, ObservedSetTrie, SetTrie};
+/// use std::collections::BTreeMap;
+///
+/// let mut trie = SetTrie::new();
+/// trie.insert(vec!["rust"], "post-1");
+///
+/// let view = MaterializedView::supersets(&trie, vec!["rust"]);
+/// let mut trie = ObservedSetTrie::from_parts(trie, view);
+/// trie.insert(vec!["rust", "wasm"], "post-2");
+/// trie.insert(vec!["go"], "post-3");
+/// trie.remove(&["rust"]);
+///
+/// assert_eq!(
+///     trie.observer().results(),
+///     &BTreeMap::from([(vec!["rust", "wasm"], vec!["post-2"])])
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct MaterializedView<K, T> {
+    kind: Kind,
+    keys: Vec<K>,
+    results: BTreeMap<Vec<K>, Vec<T>>,
+    // the number of values in `results`.
+    len: usize,
+}
+
+impl<K, T> MaterializedView<K, T>
+where
+    K: Ord + Clone,
+    T: Clone,
+{
+    /// A view of the values stored under subsets of `keys`. See [`SetTrie::subsets`].
+    pub fn subsets(trie: &SetTrie<K, T>, keys: impl IntoIterator<Item = K>) -> Self {
+        let keys: Vec<K> = keys.into_iter().collect();
+        let (results, len) = collect(trie.subsets_grouped(&keys));
+        Self {
+            kind: Kind::Subsets,
+            keys,
+            results,
+            len,
+        }
+    }
+
+    /// A view of the values stored under supersets of `keys`. See [`SetTrie::supersets`].
+    pub fn supersets(trie: &SetTrie<K, T>, keys: impl IntoIterator<Item = K>) -> Self {
+        let keys: Vec<K> = keys.into_iter().collect();
+        let (results, len) = collect(trie.supersets_grouped(&keys));
+        Self {
+            kind: Kind::Supersets,
+            keys,
+            results,
+            len,
+        }
+    }
+}
+
+impl<K, T> MaterializedView<K, T> {
+    /// The query keys of the view.
+    #[must_use]
+    pub fn keys(&self) -> &[K] {
+        &self.keys
+    }
+
+    /// The matching sets together with their values, which are kept in the same order as in the
+    /// trie.
+    #[must_use]
+    pub const fn results(&self) -> &BTreeMap<Vec<K>, Vec<T>> {
+        &self.results
+    }
+
+    /// The number of matching values.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no values match.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the matching sets together with their values.
+    #[must_use]
+    pub fn into_results(self) -> BTreeMap<Vec<K>, Vec<T>> {
+        self.results
+    }
+}
+
+impl<K, T> MaterializedView<K, T>
+where
+    K: Ord,
+{
+    /// Whether a value stored under the set `keys` is part of the view.
+    fn matches(&self, keys: &[&K]) -> bool {
+        match self.kind {
+            Kind::Subsets => is_subset(keys, &self.keys),
+            Kind::Supersets => is_subset(&self.keys, keys),
+        }
+    }
+}
+
+impl<K, T> Observer<K, T> for MaterializedView<K, T>
+where
+    K: Ord + Clone,
+    T: Clone + PartialEq,
+{
+    fn on_insert(&mut self, keys: &[&K], value: &T) {
+        if self.matches(keys) {
+            let keys = keys.iter().map(|&key| key.clone()).collect();
+            self.results.entry(keys).or_default().push(value.clone());
+            self.len += 1;
+        }
+    }
+
+    /// Values may be removed from anywhere in a set, so the first value of the set equal to
+    /// `value` is the one removed.
+    fn on_remove(&mut self, keys: &[&K], value: &T) {
+        if !self.matches(keys) {
+            return;
+        }
+        let keys: Vec<K> = keys.iter().map(|&key| key.clone()).collect();
+        let Some(values) = self.results.get_mut(&keys) else {
+            return;
+        };
+        let Some(idx) = values.iter().position(|v| v == value) else {
+            return;
+        };
+        values.remove(idx);
+        self.len -= 1;
+        if values.is_empty() {
+            self.results.remove(&keys);
+        }
+    }
+}
+
+/// The matching sets and their values, and the number of values.
+fn collect<'a, K, T>(
+    groups: impl Iterator<Item = (Vec<&'a K>, &'a [T])>,
+) -> (BTreeMap<Vec<K>, Vec<T>>, usize)
+where
+    K: Ord + Clone + 'a,
+    T: Clone + 'a,
+{
+    let mut results = BTreeMap::new();
+    let mut len = 0;
+    for (keys, values) in groups {
+        len += values.len();
+        results.insert(keys.into_iter().cloned().collect(), values.to_vec());
+    }
+    (results, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MaterializedView, ObservedSetTrie, SetTrie};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn materialized_view() {
+        let mut trie = SetTrie::new();
+        trie.insert(vec![1], 'a');
+        trie.insert(vec![1, 2], 'b');
+        trie.insert(vec![3], 'c');
+
+        let view = MaterializedView::subsets(&trie, vec![1, 2]);
+        assert_eq!(
+            view.results(),
+            &BTreeMap::from([(vec![1], vec!['a']), (vec![1, 2], vec!['b'])])
+        );
+        let mut trie = ObservedSetTrie::from_parts(trie, view);
+
+        trie.insert(vec![2], 'd');
+        trie.insert(vec![2, 3], 'e');
+        trie.insert_many(vec![], vec!['f', 'f']);
+        trie.insert(vec![2], 'g');
+        trie.remove(&[1]);
+        assert_eq!(
+            trie.observer().results(),
+            &BTreeMap::from([
+                (vec![], vec!['f', 'f']),
+                (vec![1, 2], vec!['b']),
+                (vec![2], vec!['d', 'g']),
+            ])
+        );
+        assert_eq!(trie.observer().len(), 5);
+        trie.remove(&[2]);
+        assert_eq!(trie.observer().len(), 3);
+        trie.insert_many(vec![1, 2], vec!['h', 'i']);
+        let extracted: Vec<_> = trie.extract_if(|_, value| *value == 'h').collect();
+        assert_eq!(extracted, vec![(vec![1, 2], 'h')]);
+        assert_eq!(trie.observer().results()[&vec![1, 2]], vec!['b', 'i']);
+        trie.pop_last();
+        trie.pop_first();
+        assert_eq!(trie.observer().len(), 2);
+
+        let (trie, view) = trie.into_parts();
+        let mut expected: Vec<_> = trie.subsets(&[1, 2]).collect();
+        let mut actual: Vec<_> = view.results().values().flatten().collect();
+        expected.sort_unstable();
+        actual.sort_unstable();
+        assert_eq!(actual, expected);
+
+        let view = MaterializedView::supersets(&trie, vec![2]);
+        assert_eq!(view.keys(), &[2]);
+        assert_eq!(view.len(), 3);
+    }
+}
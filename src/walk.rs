@@ -0,0 +1,168 @@
+use crate::stats::{KeyCounts, SetCounts};
+use crate::{IntoChildren, Node};
+
+/// A node which is being visited by [`Node::walk_mut`]. The children of the node are moved out of
+/// it, and moved back into `keys` and `kept` once visited, unless they turned out to be empty.
+struct Frame<K, T, S> {
+    node: Option<Node<K, T>>,
+    children: IntoChildren<K, T>,
+    keys: Vec<K>,
+    kept: Vec<Node<K, T>>,
+    state: S,
+}
+
+impl<K, T, S> Frame<K, T, S> {
+    fn new(node: Option<Node<K, T>>, children: IntoChildren<K, T>, state: S) -> Self {
+        Self {
+            node,
+            keys: Vec::with_capacity(children.len()),
+            kept: Vec::with_capacity(children.len()),
+            children,
+            state,
+        }
+    }
+
+    fn keep(&mut self, key: K, child: Node<K, T>) {
+        self.keys.push(key);
+        self.kept.push(child);
+    }
+}
+
+/// A node which is being copied by [`Node::copy_where`], together with its children which are still
+/// to be copied, the smallest key last.
+struct CopyFrame<'a, K, T, S> {
+    key: Option<&'a K>,
+    pending: Vec<(&'a K, &'a Node<K, T>, S)>,
+    copy: Node<K, T>,
+}
+
+impl<K, T> Node<K, T>
+where
+    K: Clone,
+    T: Clone,
+{
+    /// Copies this node and the descendants for which `children` returns the state, given the
+    /// state of their parent, in key order. The values of a copied node are only kept if `keep`
+    /// holds for its state, and `sets` is updated for the copied nodes which hold values.
+    ///
+    /// The copy is built bottom-up, so that every copied child is complete once it is attached to
+    /// its parent, and branches left without values are never attached.
+    pub(crate) fn copy_where<'a, S, V, C>(
+        &'a self,
+        sets: &mut SetCounts,
+        state: S,
+        keep: V,
+        mut children: C,
+    ) -> Self
+    where
+        V: Fn(&S) -> bool,
+        C: FnMut(&'a Self, &S) -> Vec<(&'a K, &'a Self, S)>,
+    {
+        let mut frame = |key, node: &'a Self, state: S| {
+            let mut copy = Self::new();
+            if keep(&state) {
+                copy.leaves.clone_from(&node.leaves);
+            }
+            let mut pending = children(node, &state);
+            pending.reverse();
+            CopyFrame { key, pending, copy }
+        };
+
+        let mut stack = vec![frame(None, self, state)];
+        loop {
+            let last = stack.last_mut().expect("stack is not empty");
+            if let Some((key, child, state)) = last.pending.pop() {
+                stack.push(frame(Some(key), child, state));
+                continue;
+            }
+
+            let mut done = stack.pop().expect("stack is not empty");
+            if !done.copy.leaves.is_empty() {
+                sets.add(stack.len());
+            }
+            done.copy.recount();
+            match (stack.last_mut(), done.key) {
+                (Some(parent), Some(key)) => {
+                    if !done.copy.is_empty() {
+                        parent.copy.keys.push(key.clone());
+                        parent.copy.children.push(done.copy);
+                    }
+                }
+                _ => return done.copy,
+            }
+        }
+    }
+}
+
+impl<K, T> Node<K, T> {
+    /// Mutably visits this node and its descendants in DFS order. A child is only visited if
+    /// `descend` returns the state for the child, given the state of its parent and the key of the
+    /// child. `visit` receives the key path, state and values of every visited node, and `sets`
+    /// is updated for the nodes of which it adds or removes all values. The counts of the visited
+    /// nodes are recomputed once their subtree has been visited, and `keys` is updated for them, so
+    /// this node must be the root.
+    ///
+    /// Visited descendants without values or children are removed once their subtree has been
+    /// visited. Returns the number of removed nodes.
+    ///
+    /// The walk is iterative, and moves every visited subtree out of the trie while it is visited;
+    /// so that the key path can be provided as a contiguous slice.
+    pub(crate) fn walk_mut<S, D, V>(
+        &mut self,
+        sets: &mut SetCounts,
+        keys: &mut KeyCounts<K>,
+        state: S,
+        mut descend: D,
+        mut visit: V,
+    ) -> usize
+    where
+        D: FnMut(&S, &K) -> Option<S>,
+        V: FnMut(&[K], &S, &mut Vec<T>),
+        K: Ord + Clone,
+    {
+        let mut path = vec![];
+        let mut pruned = 0;
+
+        sets.update(0, &mut self.leaves, |leaves| {
+            visit(&path, &state, leaves);
+        });
+        let mut stack = vec![Frame::new(None, self.take_children(), state)];
+
+        while let Some(frame) = stack.last_mut() {
+            if let Some((key, mut child)) = frame.children.next() {
+                match descend(&frame.state, &key) {
+                    None => frame.keep(key, child),
+                    Some(state) => {
+                        path.push(key);
+                        sets.update(path.len(), &mut child.leaves, |leaves| {
+                            visit(&path, &state, leaves);
+                        });
+                        let children = child.take_children();
+                        stack.push(Frame::new(Some(child), children, state));
+                    }
+                }
+                continue;
+            }
+
+            let frame = stack.pop().expect("stack is not empty");
+            if let (Some(mut node), Some(parent)) = (frame.node, stack.last_mut()) {
+                node.keys = frame.keys;
+                node.children = frame.kept;
+                let before = node.counts;
+                node.recount();
+                let key = path.pop().expect("every descendant has a key");
+                keys.update(&key, before, node.counts);
+                if node.is_empty() {
+                    pruned += 1;
+                } else {
+                    parent.keep(key, node);
+                }
+            } else {
+                self.keys = frame.keys;
+                self.children = frame.kept;
+                self.recount();
+            }
+        }
+        pruned
+    }
+}